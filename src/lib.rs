@@ -0,0 +1,4076 @@
+//! Core of ba: the issue model, its state machine, and the `.ba/` store.
+//!
+//! The `ba` binary is a CLI over this crate. Load a [`Store`], change issues
+//! through its methods, and `save()` it; the on-disk format is the same
+//! `issues.jsonl` the CLI reads and writes.
+//!
+//! ```no_run
+//! use ba::{BaError, LockMode, Store};
+//! use std::path::Path;
+//!
+//! fn main() -> Result<(), BaError> {
+//!     let mut store = Store::load(Path::new(".ba"), LockMode::Exclusive)?;
+//!     let issue = store.create_issue("Fix login".into(), "task", 1, String::new(), &[], &[])?;
+//!     store.claim(&issue.id, "agent-1", Some(30))?;
+//!     store.save()?;
+//!     Ok(())
+//! }
+//! ```
+
+use chrono::{DateTime, Utc};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const ISSUES_FILE: &str = "issues.jsonl";
+pub const CONFIG_FILE: &str = "config.json";
+const LOCK_FILE: &str = "issues.lock";
+pub const SNAPSHOTS_DIR: &str = "snapshots";
+const AUDIT_FILE: &str = "audit.jsonl";
+pub const ARCHIVE_FILE: &str = "archive.jsonl";
+pub const TEMPLATES_DIR: &str = "templates";
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Data Types
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Open,
+    InProgress,
+    Closed,
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Open => write!(f, "open"),
+            Status::InProgress => write!(f, "in_progress"),
+            Status::Closed => write!(f, "closed"),
+        }
+    }
+}
+
+impl std::str::FromStr for Status {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(Status::Open),
+            "in_progress" => Ok(Status::InProgress),
+            "closed" => Ok(Status::Closed),
+            _ => Err(format!("Unknown status: {}", s)),
+        }
+    }
+}
+
+// AIDEV-NOTE: Issue types are minimal by design. Only types that signal
+// different work patterns exist - priority handles urgency, title describes
+// the work. Types:
+// - task: default, general work
+// - epic: container for grouping related issues
+// - refactor: improving existing code (no new behavior)
+// - spike: research/investigation (may not produce code)
+// Legacy types (bug, feature, chore) deserialize to Task for backwards compat.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueType {
+    Epic,
+    Refactor,
+    Spike,
+    #[serde(other)]
+    Task,
+}
+
+impl std::fmt::Display for IssueType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IssueType::Task => write!(f, "task"),
+            IssueType::Epic => write!(f, "epic"),
+            IssueType::Refactor => write!(f, "refactor"),
+            IssueType::Spike => write!(f, "spike"),
+        }
+    }
+}
+
+impl std::str::FromStr for IssueType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "task" => Ok(IssueType::Task),
+            "epic" => Ok(IssueType::Epic),
+            "refactor" => Ok(IssueType::Refactor),
+            "spike" => Ok(IssueType::Spike),
+            _ => Err(format!(
+                "Unknown issue type: {} (valid: task, epic, refactor, spike)",
+                s
+            )),
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// State Machine
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Transitions that can be applied to an issue.
+/// Status is a side-effect of ownership transitions, not set directly.
+#[derive(Debug, Clone)]
+pub enum Transition {
+    /// Take ownership: (Open|Closed) → InProgress
+    Claim { session: String },
+    /// Abandon work: InProgress → Open
+    Release,
+    /// Complete work: InProgress → Closed
+    Finish,
+    /// Close unclaimed issue: Open → Closed (escape hatch)
+    Close,
+    /// Back to the pool without claiming: Closed → Open
+    Reopen,
+    /// Hand a claim to another session: InProgress stays InProgress
+    Reassign { from: String, to: String },
+}
+
+// AIDEV-NOTE: Dependency types mirror the beads relationship vocabulary so
+// imports don't lose meaning. Every type is a `blocked_by` edge; the type only
+// records *why* the edge exists. Whether it gates `ready` is `Edge::hard`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DepType {
+    #[default]
+    Blocks,
+    DiscoveredFrom,
+    ParentChild,
+}
+
+impl std::fmt::Display for DepType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DepType::Blocks => write!(f, "blocks"),
+            DepType::DiscoveredFrom => write!(f, "discovered-from"),
+            DepType::ParentChild => write!(f, "parent-child"),
+        }
+    }
+}
+
+impl std::str::FromStr for DepType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "blocks" => Ok(DepType::Blocks),
+            "discovered-from" => Ok(DepType::DiscoveredFrom),
+            "parent-child" => Ok(DepType::ParentChild),
+            _ => Err(format!(
+                "Unknown dependency type: {} (valid: blocks, discovered-from, parent-child)",
+                s
+            )),
+        }
+    }
+}
+
+/// Metadata for a `blocked_by` edge, keyed by blocker id in `Issue::edges`.
+/// Plain hard `blocks` edges have no entry, keeping existing files unchanged.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Edge {
+    #[serde(rename = "type", default)]
+    pub dep_type: DepType,
+    /// Soft edges are advisory: shown by show/tree but don't gate `ready`.
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub hard: bool,
+}
+
+impl Default for Edge {
+    fn default() -> Self {
+        Edge {
+            dep_type: DepType::Blocks,
+            hard: true,
+        }
+    }
+}
+
+impl Edge {
+    /// Annotation for display, e.g. `discovered-from, soft`; None for plain hard blocks.
+    pub fn label(&self) -> Option<String> {
+        let mut parts = vec![];
+        if self.dep_type != DepType::Blocks {
+            parts.push(self.dep_type.to_string());
+        }
+        if !self.hard {
+            parts.push("soft".to_string());
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Comment {
+    pub author: String,
+    pub text: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edited_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Issue {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    pub status: Status,
+    #[serde(default = "default_priority")]
+    pub priority: u8,
+    /// Manual order within a priority level, set by `ba rank`; lower comes first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rank: Option<f64>,
+    pub issue_type: IssueType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// When the current claim lapses; past it anyone may reap or take over
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claim_expires_at: Option<DateTime<Utc>>,
+    /// Person responsible, for reporting; unrelated to session claims
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub comments: Vec<Comment>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closed_at: Option<DateTime<Utc>>,
+    /// Free-text `--reason` given on close or finish (e.g. wontfix, duplicate)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub close_reason: Option<String>,
+    /// Canonical issue this one was closed as a duplicate of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<String>,
+    /// Done for good: hidden from listings even with --all (closed issues only)
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub archived: bool,
+    #[serde(default)]
+    pub blocks: Vec<String>,
+    #[serde(default)]
+    pub blocked_by: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub edges: BTreeMap<String, Edge>,
+    /// Non-blocking cross-references, mirrored on both issues
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<String>,
+    /// Epic containing this issue
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<DateTime<Utc>>,
+    /// Effort in whatever unit the project uses (points, hours)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<u32>,
+    /// Tracked work intervals; an open interval has no end yet
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub time_entries: Vec<(DateTime<Utc>, Option<DateTime<Utc>>)>,
+    /// When `ba groom` last changed the priority
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub groomed_at: Option<DateTime<Utc>>,
+    /// Last change before grooming began; idle time still counts from here
+    /// while `updated_at` is `groomed_at`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_since: Option<DateTime<Utc>>,
+}
+
+impl Issue {
+    /// Metadata of the `blocked_by` edge to `blocker` (plain hard block if absent).
+    pub fn edge(&self, blocker: &str) -> Edge {
+        self.edges.get(blocker).copied().unwrap_or_default()
+    }
+
+    /// After `added` joined `labels`: if any of them has a `label-priority`
+    /// rule, move to the most urgent priority the rules give any label the
+    /// issue carries. Returns the old priority if that changed it. Every path
+    /// that adds labels goes through here.
+    pub fn apply_label_priority(
+        &mut self,
+        rules: &BTreeMap<String, u8>,
+        added: &[String],
+    ) -> Option<u8> {
+        if !added.iter().any(|l| rules.contains_key(l)) {
+            return None;
+        }
+        let priority = self.labels.iter().filter_map(|l| rules.get(l)).min()?;
+        if *priority == self.priority {
+            return None;
+        }
+        Some(std::mem::replace(&mut self.priority, *priority))
+    }
+
+    /// Record the metadata of the `blocked_by` edge to `blocker`.
+    pub fn set_edge(&mut self, blocker: &str, edge: Edge) {
+        if edge == Edge::default() {
+            self.edges.remove(blocker);
+        } else {
+            self.edges.insert(blocker.to_string(), edge);
+        }
+    }
+
+    /// `blocker` rendered with its edge annotation when it isn't a plain block.
+    pub fn describe_blocker(&self, blocker: &str) -> String {
+        match self.edge(blocker).label() {
+            Some(label) => format!("{} ({})", blocker, label),
+            None => blocker.to_string(),
+        }
+    }
+
+    /// First field containing `needle` (already lowercased): title, description or comment.
+    pub fn search_field(&self, needle: &str) -> Option<&'static str> {
+        if self.title.to_lowercase().contains(needle) {
+            Some("title")
+        } else if self.description.to_lowercase().contains(needle) {
+            Some("description")
+        } else if self
+            .comments
+            .iter()
+            .any(|c| c.text.to_lowercase().contains(needle))
+        {
+            Some("comment")
+        } else {
+            None
+        }
+    }
+
+    /// Total tracked time, counting a running interval up to `now`.
+    pub fn tracked_time(&self, now: DateTime<Utc>) -> chrono::Duration {
+        self.time_entries
+            .iter()
+            .map(|(start, end)| end.unwrap_or(now) - *start)
+            .sum()
+    }
+
+    /// Work order: priority, then manual rank (ranked issues first), then age.
+    pub fn cmp_priority(&self, other: &Issue) -> std::cmp::Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| match (self.rank, other.rank) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            })
+            .then_with(|| self.created_at.cmp(&other.created_at))
+    }
+
+    /// In progress under a claim whose lease has run out.
+    pub fn lease_expired(&self, now: DateTime<Utc>) -> bool {
+        self.status == Status::InProgress && self.claim_expires_at.is_some_and(|t| t <= now)
+    }
+
+    /// Apply a state transition to this issue.
+    /// Returns the previous session_id if relevant (for release/finish messages).
+    /// Any successful transition drops the claim lease; `claim --ttl` sets a new one.
+    pub fn apply(&mut self, transition: Transition) -> Result<Option<String>, BaError> {
+        let now = Utc::now();
+        let result = self.apply_at(transition, now);
+        if result.is_ok() {
+            self.claim_expires_at = None;
+            // Reopening (or claiming) an archived issue brings it back, and
+            // the reason it was closed (duplicate or otherwise) no longer applies
+            if self.status != Status::Closed {
+                self.archived = false;
+                self.close_reason = None;
+                self.duplicate_of = None;
+            }
+        }
+        result
+    }
+
+    pub fn apply_at(
+        &mut self,
+        transition: Transition,
+        now: DateTime<Utc>,
+    ) -> Result<Option<String>, BaError> {
+        match (&self.status, &self.session_id, transition) {
+            // Claim: Open + unclaimed → InProgress
+            (Status::Open, None, Transition::Claim { session }) => {
+                self.session_id = Some(session);
+                self.status = Status::InProgress;
+                self.updated_at = now;
+                Ok(None)
+            }
+
+            // Claim: Open + already claimed by same session
+            (Status::Open, Some(existing), Transition::Claim { session })
+                if existing == &session =>
+            {
+                Err(BaError::AlreadyClaimed(format!(
+                    "{} already claimed by this session",
+                    self.id
+                )))
+            }
+
+            // Claim: Open + already claimed by different session
+            (Status::Open, Some(existing), Transition::Claim { .. }) => {
+                Err(BaError::AlreadyClaimed(format!(
+                    "{} already claimed by session {}",
+                    self.id, existing
+                )))
+            }
+
+            // Claim: Closed → InProgress (reopen)
+            (Status::Closed, _, Transition::Claim { session }) => {
+                self.session_id = Some(session);
+                self.status = Status::InProgress;
+                self.closed_at = None;
+                self.updated_at = now;
+                Ok(None)
+            }
+
+            // Claim: InProgress, but the owner's lease lapsed → take over
+            (Status::InProgress, Some(_), Transition::Claim { session })
+                if self.lease_expired(now) =>
+            {
+                let old_session = self.session_id.replace(session);
+                self.updated_at = now;
+                Ok(old_session)
+            }
+
+            // Claim: InProgress + already claimed
+            (Status::InProgress, Some(existing), Transition::Claim { session })
+                if existing == &session =>
+            {
+                Err(BaError::AlreadyClaimed(format!(
+                    "{} already claimed by this session",
+                    self.id
+                )))
+            }
+
+            (Status::InProgress, Some(existing), Transition::Claim { .. }) => {
+                Err(BaError::AlreadyClaimed(format!(
+                    "{} already claimed by session {}",
+                    self.id, existing
+                )))
+            }
+
+            // Release: InProgress + claimed → Open
+            (Status::InProgress, Some(_), Transition::Release) => {
+                let old_session = self.session_id.take();
+                self.status = Status::Open;
+                self.updated_at = now;
+                Ok(old_session)
+            }
+
+            // Release: not claimed
+            (_, None, Transition::Release) => Err(BaError::InvalidTransition(format!(
+                "{} is not claimed",
+                self.id
+            ))),
+
+            // Release: not in progress (but claimed somehow - shouldn't happen)
+            (_, Some(_), Transition::Release) => Err(BaError::InvalidTransition(format!(
+                "{} is not in progress",
+                self.id
+            ))),
+
+            // Finish: InProgress + claimed → Closed
+            (Status::InProgress, Some(_), Transition::Finish) => {
+                let old_session = self.session_id.take();
+                self.status = Status::Closed;
+                self.closed_at = Some(now);
+                self.updated_at = now;
+                Ok(old_session)
+            }
+
+            // Finish: not claimed
+            (_, None, Transition::Finish) => Err(BaError::InvalidTransition(format!(
+                "{} is not claimed. Use 'close' for unclaimed issues.",
+                self.id
+            ))),
+
+            // Finish: already closed
+            (Status::Closed, _, Transition::Finish) => Err(BaError::InvalidTransition(format!(
+                "{} is already closed",
+                self.id
+            ))),
+
+            // Finish: open but not claimed (shouldn't have session)
+            (Status::Open, Some(_), Transition::Finish) => Err(BaError::InvalidTransition(
+                format!("{} is open, not in progress", self.id),
+            )),
+
+            // Close: Open + unclaimed → Closed (escape hatch)
+            (Status::Open, None, Transition::Close) => {
+                self.status = Status::Closed;
+                self.closed_at = Some(now);
+                self.updated_at = now;
+                Ok(None)
+            }
+
+            // Close: already closed
+            (Status::Closed, _, Transition::Close) => Err(BaError::InvalidTransition(format!(
+                "{} is already closed",
+                self.id
+            ))),
+
+            // Close: claimed - must release first or use finish
+            (_, Some(session), Transition::Close) => Err(BaError::AlreadyClaimed(format!(
+                "{} is claimed by session {}. Use 'release' first, or 'finish' to complete.",
+                self.id, session
+            ))),
+
+            // Invalid states (InProgress without session shouldn't exist)
+            (Status::InProgress, None, Transition::Claim { session }) => {
+                // Treat as claimable - fix the inconsistent state
+                self.session_id = Some(session);
+                self.updated_at = now;
+                Ok(None)
+            }
+
+            (Status::InProgress, None, Transition::Close) => {
+                // InProgress but no owner - treat as closeable
+                self.status = Status::Closed;
+                self.closed_at = Some(now);
+                self.updated_at = now;
+                Ok(None)
+            }
+
+            // Reopen: Closed → Open (unclaimed, unlike claim-to-reopen)
+            (Status::Closed, _, Transition::Reopen) => {
+                self.session_id = None;
+                self.status = Status::Open;
+                self.closed_at = None;
+                self.updated_at = now;
+                Ok(None)
+            }
+
+            // Reopen: only closed issues can be reopened
+            (_, _, Transition::Reopen) => Err(BaError::InvalidTransition(format!(
+                "{} is not closed",
+                self.id
+            ))),
+
+            // Reassign: InProgress + claimed by `from` → claimed by `to`
+            (Status::InProgress, Some(existing), Transition::Reassign { from, to })
+                if existing == &from =>
+            {
+                let old_session = self.session_id.replace(to);
+                self.updated_at = now;
+                Ok(old_session)
+            }
+
+            // Reassign: someone else holds it; refuse to take it over
+            (Status::InProgress, Some(existing), Transition::Reassign { from, .. }) => {
+                Err(BaError::AlreadyClaimed(format!(
+                    "{} is claimed by session {}, not {}",
+                    self.id, existing, from
+                )))
+            }
+
+            (_, _, Transition::Reassign { .. }) => Err(BaError::InvalidTransition(format!(
+                "{} is not in progress",
+                self.id
+            ))),
+        }
+    }
+}
+
+/// Open, or in progress under a lapsed lease (stale, so fair game).
+fn is_claimable(issue: &Issue) -> bool {
+    issue.status == Status::Open || issue.lease_expired(Utc::now())
+}
+
+pub fn default_priority() -> u8 {
+    2
+}
+
+// Beads import types - using Value for flexible parsing with clear errors
+#[derive(Debug, Deserialize)]
+pub struct BeadsDependency {
+    pub issue_id: String,
+    pub depends_on_id: String,
+    #[serde(rename = "type")]
+    pub dep_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BeadsIssue {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    pub status: String,
+    #[serde(default = "default_priority")]
+    pub priority: u8,
+    pub issue_type: String,
+    pub created_at: String,
+    pub updated_at: String,
+    #[serde(default)]
+    pub closed_at: Option<String>,
+    #[serde(default)]
+    pub dependencies: Vec<BeadsDependency>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub comments: Vec<BeadsComment>,
+}
+
+/// A comment in a beads export; its own `id` and `issue_id` are not kept.
+#[derive(Debug, Deserialize)]
+pub struct BeadsComment {
+    #[serde(default)]
+    pub author: String,
+    pub text: String,
+    pub created_at: String,
+}
+
+#[derive(Debug)]
+pub struct ImportError {
+    pub line_num: usize,
+    pub issue_id: Option<String>,
+    pub field: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.issue_id {
+            Some(id) => write!(
+                f,
+                "Line {}: Issue '{}' - {}: {}",
+                self.line_num, id, self.field, self.message
+            ),
+            None => write!(
+                f,
+                "Line {}: {}: {}",
+                self.line_num, self.field, self.message
+            ),
+        }
+    }
+}
+
+/// What an import did: issues added, issues skipped because their ID was
+/// taken, and the lines or links it rejected.
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub imported: usize,
+    pub skipped: usize,
+    pub errors: Vec<ImportError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    pub version: u8,
+    pub prefix: String,
+    /// Type for `create` without `-t`
+    #[serde(default = "default_issue_type")]
+    pub default_type: IssueType,
+    /// Priority for `create` without `-p`
+    #[serde(default = "default_priority")]
+    pub default_priority: u8,
+    /// Read-only web view of the issues; `ba link` appends `/<id>`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_base_url: Option<String>,
+    /// Priority an issue takes when `ba label` adds one of these labels
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub label_priority: BTreeMap<String, u8>,
+    /// Days idle before `ba groom` raises an open issue's priority
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub escalate_after_days: Option<u32>,
+    /// Days idle before `ba groom` lowers it instead; longer than escalation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decay_after_days: Option<u32>,
+}
+
+pub fn default_issue_type() -> IssueType {
+    IssueType::Task
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Errors
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Command failure. Each variant maps to its own exit code so scripts can tell
+/// "retry later" (someone else holds the claim) from "give up" (no such issue).
+/// Everything without a dedicated variant is `Other` and exits 1.
+#[derive(Debug)]
+pub enum BaError {
+    /// No issue matches the given ID (exit 3)
+    NotFound(String),
+    /// The ID prefix matches more than one issue (exit 4)
+    Ambiguous(String),
+    /// Another session (or this one) already holds the claim (exit 5)
+    AlreadyClaimed(String),
+    /// The issue's status doesn't allow this transition (exit 6)
+    InvalidTransition(String),
+    Other(String),
+}
+
+impl BaError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            BaError::Other(_) => 1,
+            BaError::NotFound(_) => 3,
+            BaError::Ambiguous(_) => 4,
+            BaError::AlreadyClaimed(_) => 5,
+            BaError::InvalidTransition(_) => 6,
+        }
+    }
+
+    /// Stable name for the kind of error, reported as `code` under `--json`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            BaError::Other(_) => "other",
+            BaError::NotFound(_) => "not_found",
+            BaError::Ambiguous(_) => "ambiguous",
+            BaError::AlreadyClaimed(_) => "already_claimed",
+            BaError::InvalidTransition(_) => "invalid_transition",
+        }
+    }
+
+    /// Same kind of error (and exit code), with `context` in front of the message.
+    pub fn context(self, context: &str) -> Self {
+        let wrap = |msg: String| format!("{}: {}", context, msg);
+        match self {
+            BaError::NotFound(msg) => BaError::NotFound(wrap(msg)),
+            BaError::Ambiguous(msg) => BaError::Ambiguous(wrap(msg)),
+            BaError::AlreadyClaimed(msg) => BaError::AlreadyClaimed(wrap(msg)),
+            BaError::InvalidTransition(msg) => BaError::InvalidTransition(wrap(msg)),
+            BaError::Other(msg) => BaError::Other(wrap(msg)),
+        }
+    }
+}
+
+impl std::fmt::Display for BaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BaError::NotFound(msg)
+            | BaError::Ambiguous(msg)
+            | BaError::AlreadyClaimed(msg)
+            | BaError::InvalidTransition(msg)
+            | BaError::Other(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl From<String> for BaError {
+    fn from(msg: String) -> Self {
+        BaError::Other(msg)
+    }
+}
+
+impl From<&str> for BaError {
+    fn from(msg: &str) -> Self {
+        BaError::Other(msg.to_string())
+    }
+}
+
+/// Look up a comment by its 0-based position as shown by `ba show`.
+fn comment_index(issue: &Issue, index: usize) -> Result<usize, String> {
+    match issue.comments.len() {
+        0 => Err(format!("{} has no comments", issue.id)),
+        len if index >= len => Err(format!(
+            "Comment index {} out of range for {} (valid: 0-{})",
+            index,
+            issue.id,
+            len - 1
+        )),
+        _ => Ok(index),
+    }
+}
+
+/// A cycle rotated to start at its smallest ID, so rotations compare equal.
+fn normalize_cycle(cycle: &[String]) -> Vec<String> {
+    if cycle.is_empty() {
+        return vec![];
+    }
+    // Rotate so smallest element is first
+    let min_pos = cycle
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, v)| *v)
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let mut normalized: Vec<String> = cycle[min_pos..].to_vec();
+    normalized.extend(cycle[..min_pos].to_vec());
+    normalized
+}
+
+/// Parse a JSONL issues file; a missing file is an empty side of a merge.
+pub fn read_issues_file(path: &Path) -> Result<HashMap<String, Issue>, BaError> {
+    let mut issues = HashMap::new();
+    if !path.exists() {
+        return Ok(issues);
+    }
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let issue: Issue = serde_json::from_str(line).map_err(|e| {
+            format!(
+                "Failed to parse {} at line {}: {}",
+                path.display(),
+                index + 1,
+                e
+            )
+        })?;
+        issues.insert(issue.id.clone(), issue);
+    }
+    Ok(issues)
+}
+
+/// Field-by-field equality via the serialized form (Issue has no PartialEq).
+fn same_issue(a: &Issue, b: &Issue) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// Three-way merge of a set-like list: keep what both sides kept, plus what
+/// either side added; an item removed on either side stays removed.
+fn merge_list<T: Clone + PartialEq>(base: &[T], ours: &[T], theirs: &[T]) -> Vec<T> {
+    let mut merged: Vec<T> = vec![];
+    for item in ours.iter().chain(theirs) {
+        let in_base = base.contains(item);
+        let kept = !in_base || (ours.contains(item) && theirs.contains(item));
+        if kept && !merged.contains(item) {
+            merged.push(item.clone());
+        }
+    }
+    merged
+}
+
+/// Merge one issue edited on both sides. The newer `updated_at` wins scalar
+/// fields; labels, comments and dependencies are merged as sets.
+fn merge_issue(base: Option<&Issue>, ours: &Issue, theirs: &Issue) -> Issue {
+    let (winner, loser) = if theirs.updated_at > ours.updated_at {
+        (theirs, ours)
+    } else {
+        (ours, theirs)
+    };
+    let empty = vec![];
+    let base_labels = base.map_or(&empty, |b| &b.labels);
+    let base_blocks = base.map_or(&empty, |b| &b.blocks);
+    let base_blocked_by = base.map_or(&empty, |b| &b.blocked_by);
+    let base_related = base.map_or(&empty, |b| &b.related);
+
+    let mut merged = winner.clone();
+    merged.labels = merge_list(base_labels, &winner.labels, &loser.labels);
+    merged.blocks = merge_list(base_blocks, &winner.blocks, &loser.blocks);
+    merged.blocked_by = merge_list(base_blocked_by, &winner.blocked_by, &loser.blocked_by);
+    merged.related = merge_list(base_related, &winner.related, &loser.related);
+    for (blocker, edge) in &loser.edges {
+        merged.edges.entry(blocker.clone()).or_insert(*edge);
+    }
+    let blocked_by = merged.blocked_by.clone();
+    merged
+        .edges
+        .retain(|blocker, _| blocked_by.contains(blocker));
+
+    // Comments are identified by author + creation time; edits follow the winner
+    let key = |c: &Comment| (c.author.clone(), c.created_at);
+    let base_keys: Vec<_> = base.map_or(vec![], |b| b.comments.iter().map(key).collect());
+    let winner_keys: Vec<_> = winner.comments.iter().map(key).collect();
+    let loser_keys: Vec<_> = loser.comments.iter().map(key).collect();
+    let kept = merge_list(&base_keys, &winner_keys, &loser_keys);
+    let mut comments: Vec<Comment> = winner
+        .comments
+        .iter()
+        .chain(&loser.comments)
+        .filter(|c| kept.contains(&key(c)))
+        .fold(vec![], |mut acc: Vec<Comment>, c| {
+            if !acc.iter().any(|seen| key(seen) == key(c)) {
+                acc.push(c.clone());
+            }
+            acc
+        });
+    comments.sort_by_key(|c| c.created_at);
+    merged.comments = comments;
+
+    merged
+}
+
+/// Merge whole stores keyed by issue ID. Returns the result and how many
+/// issues were edited on both sides.
+pub fn merge_stores(
+    base: &HashMap<String, Issue>,
+    ours: &HashMap<String, Issue>,
+    theirs: &HashMap<String, Issue>,
+) -> (HashMap<String, Issue>, usize) {
+    let mut merged = HashMap::new();
+    let mut conflicts = 0;
+
+    let mut ids: HashSet<&String> = ours.keys().collect();
+    ids.extend(theirs.keys());
+    ids.extend(base.keys());
+
+    for id in ids {
+        let b = base.get(id);
+        let resolved = match (ours.get(id), theirs.get(id)) {
+            (Some(o), Some(t)) if same_issue(o, t) => Some(o.clone()),
+            (Some(o), Some(t)) => match b {
+                Some(b) if same_issue(b, o) => Some(t.clone()),
+                Some(b) if same_issue(b, t) => Some(o.clone()),
+                _ => {
+                    conflicts += 1;
+                    Some(merge_issue(b, o, t))
+                }
+            },
+            // Deleted on one side: honor it only if the other side didn't edit
+            (Some(kept), None) | (None, Some(kept)) => match b {
+                Some(b) if same_issue(b, kept) => None,
+                _ => Some(kept.clone()),
+            },
+            (None, None) => None,
+        };
+        if let Some(issue) = resolved {
+            merged.insert(id.clone(), issue);
+        }
+    }
+
+    (merged, conflicts)
+}
+
+/// Parse an audit log (`.ba/audit.jsonl` or a copy of one), oldest first.
+pub fn read_audit_log(path: &Path) -> Result<Vec<AuditEntry>, BaError> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read audit log: {}", e))?;
+
+    let mut entries = vec![];
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: AuditEntry = serde_json::from_str(line)
+            .map_err(|e| format!("Failed to parse audit log at line {}: {}", index + 1, e))?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Store (in-memory + file operations)
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// One line of `.ba/audit.jsonl`: what a command did to one issue.
+/// A missing `from_status` means the issue was created, a missing
+/// `to_status` that it was deleted. Every entry written by one save shares
+/// its timestamp, which is how `ba undo` reverses a command as a whole.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub command: String,
+    pub issue_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    pub from_status: Option<Status>,
+    pub to_status: Option<Status>,
+}
+
+/// A line of issues.jsonl that didn't parse. Kept verbatim and written back
+/// on save so a bad line never silently costs data.
+struct CorruptLine {
+    line: usize,
+    raw: String,
+    error: String,
+}
+
+/// Per-issue state captured at load, diffed on save to write the audit log.
+pub struct Loaded {
+    pub status: Status,
+    pub updated_at: DateTime<Utc>,
+    pub session_id: Option<String>,
+}
+
+/// One integrity problem found by `ba doctor` or `ba replay`.
+#[derive(Debug, Serialize)]
+pub struct Problem {
+    pub id: String,
+    pub kind: &'static str,
+    pub detail: String,
+    /// Whether `--fix` repairs it
+    pub fixable: bool,
+}
+
+/// What [`Store::close_cascade`] or [`Store::close_unclaimed`] did: every
+/// issue it closed (a cascade's requested one first) and the claimed issues
+/// it left open, with their sessions.
+#[derive(Debug, Clone)]
+pub struct Cascade {
+    pub closed: Vec<String>,
+    pub skipped: Vec<(String, String)>,
+}
+
+/// What a label change across many issues did (or would do, in a dry run):
+/// the issues whose labels changed, and (id, old, new) for each issue a
+/// `label-priority` rule moved.
+#[derive(Debug, Clone)]
+pub struct Relabel {
+    pub affected: Vec<String>,
+    pub reprioritized: Vec<(String, u8, u8)>,
+}
+
+/// What [`Store::undo`] did: the audit entries it reversed, the issues it
+/// moved back, and the IDs of the issues it removed because the undone save
+/// created them.
+#[derive(Debug, Clone)]
+pub struct Undo {
+    pub undone: Vec<AuditEntry>,
+    pub reversed: Vec<Issue>,
+    pub deleted: Vec<String>,
+}
+
+/// What [`Store::claim`] did: the claimed issue, the session whose lapsed
+/// lease it took over, and when the issue had been closed if the claim
+/// reopened it.
+#[derive(Debug, Clone)]
+pub struct Claim {
+    pub issue: Issue,
+    pub taken_from: Option<String>,
+    pub reopened_from: Option<DateTime<Utc>>,
+}
+
+/// What [`Store::set_priority`] did: the issue as changed, its old
+/// priority, and each blocker it bumped with that blocker's old priority.
+#[derive(Debug, Clone)]
+pub struct PriorityChange {
+    pub issue: Issue,
+    pub from: u8,
+    pub bumped: Vec<(String, u8)>,
+}
+
+/// One priority change made by [`Store::groom`].
+#[derive(Debug, Clone, Serialize)]
+pub struct Groomed {
+    pub id: String,
+    pub title: String,
+    pub from: u8,
+    pub to: u8,
+    pub idle_days: i64,
+}
+
+/// How a command holds the store lock from `Store::load` until it exits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LockMode {
+    /// Read-only commands: any number of readers at once, never saves.
+    Shared,
+    /// Mutating commands: sole access across load → mutate → save.
+    Exclusive,
+}
+
+pub struct Store {
+    pub config: Config,
+    pub issues: HashMap<String, Issue>,
+    pub ba_dir: PathBuf,
+    pub lock_mode: LockMode,
+    /// Subcommand name recorded in audit entries
+    pub command: String,
+    /// Who ran the command, recorded in audit entries
+    pub author: Option<String>,
+    /// Set by `ba batch`: `save` is a no-op until the whole batch succeeds
+    pub defer_save: bool,
+    pub loaded: HashMap<String, Loaded>,
+    corrupt: Vec<CorruptLine>,
+    _lock_file: File,
+}
+
+impl Store {
+    pub fn load(ba_dir: &Path, lock_mode: LockMode) -> Result<Self, BaError> {
+        let lock_path = ba_dir.join(LOCK_FILE);
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(&lock_path)
+            .map_err(|e| format!("Failed to open lock file '{}': {}", lock_path.display(), e))?;
+        let locked = match lock_mode {
+            LockMode::Shared => lock_file.lock_shared(),
+            LockMode::Exclusive => lock_file.lock_exclusive(),
+        };
+        locked.map_err(|e| {
+            format!(
+                "Failed to acquire store lock '{}': {}",
+                lock_path.display(),
+                e
+            )
+        })?;
+
+        let config_path = ba_dir.join(CONFIG_FILE);
+        let config: Config = if config_path.exists() {
+            let content = fs::read_to_string(&config_path)
+                .map_err(|e| format!("Failed to read config: {}", e))?;
+            serde_json::from_str(&content).map_err(|e| format!("Failed to parse config: {}", e))?
+        } else {
+            return Err("Not initialized. Run 'ba init' first.".into());
+        };
+
+        let issues_path = ba_dir.join(ISSUES_FILE);
+        let mut issues = HashMap::new();
+        let mut corrupt = vec![];
+        if issues_path.exists() {
+            let file = File::open(&issues_path)
+                .map_err(|e| format!("Failed to open issues file: {}", e))?;
+            let reader = BufReader::new(file);
+            for (index, line) in reader.lines().enumerate() {
+                let line_num = index + 1;
+                let line =
+                    line.map_err(|e| format!("Failed to read issues line {}: {}", line_num, e))?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<Issue>(&line) {
+                    Ok(issue) => {
+                        issues.insert(issue.id.clone(), issue);
+                    }
+                    Err(e) => corrupt.push(CorruptLine {
+                        line: line_num,
+                        raw: line,
+                        error: e.to_string(),
+                    }),
+                }
+            }
+        }
+
+        let loaded = issues
+            .values()
+            .map(|issue| {
+                let state = Loaded {
+                    status: issue.status.clone(),
+                    updated_at: issue.updated_at,
+                    session_id: issue.session_id.clone(),
+                };
+                (issue.id.clone(), state)
+            })
+            .collect();
+
+        Ok(Store {
+            config,
+            issues,
+            ba_dir: ba_dir.to_path_buf(),
+            lock_mode,
+            command: String::new(),
+            author: None,
+            defer_save: false,
+            loaded,
+            corrupt,
+            _lock_file: lock_file,
+        })
+    }
+
+    pub fn save(&self) -> Result<(), BaError> {
+        // Other readers may hold the shared lock too; writing now could race
+        if self.lock_mode == LockMode::Shared {
+            return Err("Internal error: store loaded read-only cannot be saved".into());
+        }
+        if self.defer_save {
+            return Ok(());
+        }
+        let mut content = issues_jsonl(self.issues.values())?;
+        for corrupt in &self.corrupt {
+            content.push_str(&corrupt.raw);
+            content.push('\n');
+        }
+        write_atomic(&self.ba_dir.join(ISSUES_FILE), &content)?;
+        if dry_run() {
+            return Ok(());
+        }
+        self.append_audit()
+    }
+
+    /// Validate the fields of a new open issue, give it an ID, and insert it.
+    /// `blockers` may be ID prefixes; an unknown one fails before anything
+    /// is inserted. Their `blocks` side is wired up here.
+    pub fn create_issue(
+        &mut self,
+        title: String,
+        issue_type: &str,
+        priority: u8,
+        description: String,
+        labels: &[String],
+        blockers: &[String],
+    ) -> Result<Issue, BaError> {
+        let issue_type: IssueType = issue_type.parse()?;
+
+        if priority > 4 {
+            return Err("Priority must be 0-4".into());
+        }
+        if title.trim().is_empty() {
+            return Err("Title must not be empty".into());
+        }
+
+        let mut blocked_by: Vec<String> = vec![];
+        for blocker in blockers {
+            let blocker = self.resolve_id(blocker)?;
+            if !blocked_by.contains(&blocker) {
+                blocked_by.push(blocker);
+            }
+        }
+
+        let mut labels = labels.to_vec();
+        labels.sort();
+        labels.dedup();
+
+        let now = Utc::now();
+        let id = self.generate_id(&title, &now);
+
+        let mut issue = Issue {
+            id: id.clone(),
+            title,
+            description,
+            status: Status::Open,
+            priority,
+            issue_type,
+            session_id: None,
+            claim_expires_at: None,
+            assignee: None,
+            labels,
+            comments: vec![],
+            created_at: now,
+            updated_at: now,
+            closed_at: None,
+            blocks: vec![],
+            blocked_by: blocked_by.clone(),
+            edges: BTreeMap::new(),
+            related: vec![],
+            parent: None,
+            due: None,
+            estimate: None,
+            rank: None,
+            close_reason: None,
+            duplicate_of: None,
+            archived: false,
+            time_entries: vec![],
+            groomed_at: None,
+            idle_since: None,
+        };
+        let labels = issue.labels.clone();
+        issue.apply_label_priority(&self.config.label_priority, &labels);
+
+        for blocker in &blocked_by {
+            let blocker_issue = self.issues.get_mut(blocker).expect("resolved ID exists");
+            blocker_issue.blocks.push(id.clone());
+            blocker_issue.updated_at = now;
+        }
+        self.issues.insert(id, issue.clone());
+        Ok(issue)
+    }
+
+    /// Resolve `id` and apply `transition` to it, returning the updated issue.
+    /// Nothing is written until `save()`.
+    pub fn apply_transition(&mut self, id: &str, transition: Transition) -> Result<Issue, BaError> {
+        let id = self.resolve_id(id)?;
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        issue.apply(transition)?;
+        Ok(issue.clone())
+    }
+
+    /// Claim `id` for `session`, with a lease of `ttl` minutes if given.
+    /// Claiming a closed issue reopens it.
+    pub fn claim(&mut self, id: &str, session: &str, ttl: Option<u32>) -> Result<Claim, BaError> {
+        let id = self.resolve_id(id)?;
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+
+        // Remember when a reopened issue was closed, so the caller can flag
+        // downstream work that may have started on the strength of that close
+        let reopened_from = match issue.status {
+            Status::Closed => issue.closed_at,
+            _ => None,
+        };
+        let taken_from = issue.apply(Transition::Claim {
+            session: session.to_string(),
+        })?;
+        issue.claim_expires_at =
+            ttl.map(|minutes| issue.updated_at + chrono::Duration::minutes(minutes.into()));
+
+        Ok(Claim {
+            issue: issue.clone(),
+            taken_from,
+            reopened_from,
+        })
+    }
+
+    /// Hand the claim on `id` from session `from` to `to`.
+    pub fn reassign(&mut self, id: &str, from: &str, to: &str) -> Result<Issue, BaError> {
+        self.apply_transition(
+            id,
+            Transition::Reassign {
+                from: from.to_string(),
+                to: to.to_string(),
+            },
+        )
+    }
+
+    /// Put `id` back in the pool. Returns the issue and the session that held it.
+    pub fn release(&mut self, id: &str) -> Result<(Issue, String), BaError> {
+        let id = self.resolve_id(id)?;
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        let old_session = issue.apply(Transition::Release)?.unwrap_or_default();
+        Ok((issue.clone(), old_session))
+    }
+
+    /// Finish claimed work on `id`. Returns the issue and the session that held it.
+    pub fn finish(&mut self, id: &str, reason: Option<String>) -> Result<(Issue, String), BaError> {
+        let id = self.resolve_id(id)?;
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        let old_session = issue.apply(Transition::Finish)?.unwrap_or_default();
+        issue.close_reason = reason;
+        Ok((issue.clone(), old_session))
+    }
+
+    /// Issues `issue` blocks that are now ready, most urgent first.
+    pub fn newly_ready(&self, issue: &Issue) -> Vec<&Issue> {
+        let mut ready: Vec<&Issue> = issue
+            .blocks
+            .iter()
+            .filter_map(|dep| self.issues.get(dep))
+            .filter(|dep| self.is_ready(dep))
+            .collect();
+        ready.sort_by(|a, b| a.cmp_priority(b));
+        ready
+    }
+
+    /// Dependents of `issue` that were started or finished after `closed_at`.
+    /// Reopening `issue` may invalidate that work, so the operator should review it.
+    pub fn acted_on_dependents(&self, issue: &Issue, closed_at: DateTime<Utc>) -> Vec<&Issue> {
+        let mut acted: Vec<&Issue> = issue
+            .blocks
+            .iter()
+            .filter_map(|id| self.issues.get(id))
+            .filter(|dep| dep.status != Status::Open && dep.updated_at > closed_at)
+            .collect();
+        acted.sort_by(|a, b| a.id.cmp(&b.id));
+        acted
+    }
+
+    /// Set or clear `archived`. Only closed issues can be archived.
+    pub fn set_archived(&mut self, id: &str, archive: bool) -> Result<Issue, BaError> {
+        let id = self.resolve_id(id)?;
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        if archive && issue.status != Status::Closed {
+            return Err(BaError::InvalidTransition(format!(
+                "{} is {}; only closed issues can be archived",
+                id, issue.status
+            )));
+        }
+        if issue.archived == archive {
+            return Err(format!(
+                "{} is {} archived",
+                id,
+                if archive { "already" } else { "not" }
+            )
+            .into());
+        }
+        issue.archived = archive;
+        issue.updated_at = Utc::now();
+        Ok(issue.clone())
+    }
+
+    /// Release every in-progress issue whose lease has lapsed back to the
+    /// pool. Returns (id, session that held it), sorted by ID.
+    pub fn reap(&mut self) -> Result<Vec<(String, String)>, BaError> {
+        let now = Utc::now();
+        let mut reaped: Vec<(String, String)> = vec![];
+        for issue in self.issues.values_mut() {
+            if issue.lease_expired(now) {
+                let old_session = issue.apply(Transition::Release)?;
+                reaped.push((issue.id.clone(), old_session.unwrap_or_default()));
+            }
+        }
+        reaped.sort();
+        Ok(reaped)
+    }
+
+    /// Close each of `ids` that isn't already closed. Claimed issues are
+    /// someone's active work, so they are skipped rather than closed.
+    pub fn close_unclaimed(&mut self, ids: &[String]) -> Result<Cascade, BaError> {
+        let mut ids: Vec<String> = ids
+            .iter()
+            .map(|id| self.resolve_id(id))
+            .collect::<Result<_, _>>()?;
+        ids.sort();
+        ids.dedup();
+
+        let mut result = Cascade {
+            closed: vec![],
+            skipped: vec![],
+        };
+        for id in ids {
+            let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+            if issue.status == Status::Closed {
+                continue;
+            }
+            if let Some(session) = &issue.session_id {
+                result.skipped.push((id, session.clone()));
+                continue;
+            }
+            issue.apply(Transition::Close)?;
+            result.closed.push(id);
+        }
+        Ok(result)
+    }
+
+    /// Description template for `issue_type`, if the project has one.
+    pub fn template(&self, issue_type: &str) -> Option<String> {
+        let issue_type: IssueType = issue_type.parse().ok()?;
+        fs::read_to_string(template_path(&self.ba_dir, &issue_type)).ok()
+    }
+
+    /// Write `config` back to config.json (under the same exclusive lock).
+    pub fn save_config(&self) -> Result<(), BaError> {
+        let config_json = serde_json::to_string_pretty(&self.config)
+            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        Ok(write_atomic(&self.ba_dir.join(CONFIG_FILE), &config_json)?)
+    }
+
+    /// Warn about lines skipped at load, or fail on the first one in strict mode.
+    pub fn check_corrupt(&self, strict: bool) -> Result<(), BaError> {
+        if let Some(first) = self.corrupt.first()
+            && strict
+        {
+            return Err(format!(
+                "Failed to parse issue at line {}: {}",
+                first.line, first.error
+            )
+            .into());
+        }
+        for corrupt in &self.corrupt {
+            eprintln!(
+                "Warning: skipped corrupt line {} in {}: {} (see 'ba doctor')",
+                corrupt.line, ISSUES_FILE, corrupt.error
+            );
+        }
+        Ok(())
+    }
+
+    /// Every entry in the audit log, oldest first.
+    pub fn audit_entries(&self) -> Result<Vec<AuditEntry>, BaError> {
+        let path = self.ba_dir.join(AUDIT_FILE);
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        read_audit_log(&path)
+    }
+
+    /// Fold `entries` into each issue's last logged status and compare that
+    /// with the store. Entries carry only statuses, so this checks the log
+    /// against issues.jsonl; it can't rebuild the other fields.
+    pub fn audit_problems(&self, entries: &[AuditEntry]) -> Vec<Problem> {
+        let mut problems: Vec<Problem> = vec![];
+        let mut report = |id: &str, kind, detail: String| {
+            problems.push(Problem {
+                id: id.to_string(),
+                kind,
+                detail,
+                fixable: false,
+            });
+        };
+
+        // None once the log deletes the issue
+        let mut replayed: BTreeMap<&str, Option<&Status>> = BTreeMap::new();
+        for entry in entries {
+            let id = entry.issue_id.as_str();
+            match (replayed.get(id), &entry.from_status) {
+                (Some(Some(_)), None) => report(
+                    id,
+                    "audit-gap",
+                    format!("'{}' creates it again", entry.command),
+                ),
+                (Some(Some(last)), Some(from)) if *last != from => report(
+                    id,
+                    "audit-gap",
+                    format!(
+                        "'{}' starts from {}, but the log last left it {}",
+                        entry.command, from, last
+                    ),
+                ),
+                _ => {}
+            }
+            replayed.insert(id, entry.to_status.as_ref());
+        }
+
+        let mut ids: Vec<&String> = self.issues.keys().collect();
+        ids.sort();
+        for id in ids {
+            let status = &self.issues[id].status;
+            match replayed.get(id.as_str()) {
+                None => report(id, "unlogged", "no audit entries".to_string()),
+                Some(None) => report(id, "unlogged", "the log deleted it".to_string()),
+                Some(Some(logged)) if *logged != status => report(
+                    id,
+                    "status-drift",
+                    format!("log says {}, issues.jsonl says {}", logged, status),
+                ),
+                Some(Some(_)) => {}
+            }
+        }
+        for (id, logged) in &replayed {
+            if let Some(status) = logged
+                && !self.issues.contains_key(*id)
+            {
+                report(
+                    id,
+                    "missing",
+                    format!("log says {}, not in issues.jsonl", status),
+                );
+            }
+        }
+        problems
+    }
+
+    /// Append one audit entry per issue created, changed or deleted since load.
+    /// Runs under the exclusive lock, so entries from racing agents never interleave.
+    pub fn append_audit(&self) -> Result<(), BaError> {
+        let now = Utc::now();
+        let mut entries: Vec<AuditEntry> = vec![];
+
+        for issue in self.issues.values() {
+            let before = self.loaded.get(&issue.id);
+            if before.is_some_and(|b| b.status == issue.status && b.updated_at == issue.updated_at)
+            {
+                continue;
+            }
+            entries.push(AuditEntry {
+                timestamp: now,
+                command: self.command.clone(),
+                issue_id: issue.id.clone(),
+                // A release clears the session; keep the one that let go
+                session: issue
+                    .session_id
+                    .clone()
+                    .or_else(|| before.and_then(|b| b.session_id.clone())),
+                author: self.author.clone(),
+                from_status: before.map(|b| b.status.clone()),
+                to_status: Some(issue.status.clone()),
+            });
+        }
+        for (id, before) in &self.loaded {
+            if !self.issues.contains_key(id) {
+                entries.push(AuditEntry {
+                    timestamp: now,
+                    command: self.command.clone(),
+                    issue_id: id.clone(),
+                    session: before.session_id.clone(),
+                    author: self.author.clone(),
+                    from_status: Some(before.status.clone()),
+                    to_status: None,
+                });
+            }
+        }
+
+        if entries.is_empty() {
+            return Ok(());
+        }
+        entries.sort_by(|a, b| a.issue_id.cmp(&b.issue_id));
+
+        let path = self.ba_dir.join(AUDIT_FILE);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open audit log: {}", e))?;
+        let mut buffer = String::new();
+        for entry in &entries {
+            buffer.push_str(&serde_json::to_string(entry).unwrap());
+            buffer.push('\n');
+        }
+        file.write_all(buffer.as_bytes())
+            .map_err(|e| format!("Failed to write audit log: {}", e))?;
+        Ok(())
+    }
+
+    /// Remove `ids` and every reference to them (dependencies, related links,
+    /// parent, duplicate_of), bumping `updated_at` on the issues that lost one.
+    pub fn remove_issues(&mut self, ids: &[String], now: DateTime<Utc>) -> Vec<Issue> {
+        let delete_set: HashSet<&String> = ids.iter().collect();
+        for issue in self.issues.values_mut() {
+            let blocks_before = issue.blocks.len();
+            let blocked_by_before = issue.blocked_by.len();
+
+            issue.blocks.retain(|dep| !delete_set.contains(dep));
+            issue.blocked_by.retain(|dep| !delete_set.contains(dep));
+            issue.edges.retain(|dep, _| !delete_set.contains(dep));
+            let related_before = issue.related.len();
+            issue.related.retain(|other| !delete_set.contains(other));
+            let orphaned = issue
+                .parent
+                .take_if(|parent| delete_set.contains(parent))
+                .is_some()
+                | issue
+                    .duplicate_of
+                    .take_if(|canonical| delete_set.contains(canonical))
+                    .is_some();
+
+            if issue.blocks.len() != blocks_before
+                || issue.blocked_by.len() != blocked_by_before
+                || issue.related.len() != related_before
+                || orphaned
+            {
+                issue.updated_at = now;
+            }
+        }
+
+        ids.iter().filter_map(|id| self.issues.remove(id)).collect()
+    }
+
+    /// Hard blockers of `issue` that aren't closed yet. Missing blockers and
+    /// soft edges don't block.
+    pub fn open_blockers<'a>(&self, issue: &'a Issue) -> Vec<&'a String> {
+        issue
+            .blocked_by
+            .iter()
+            .filter(|blocker_id| {
+                issue.edge(blocker_id).hard
+                    && self
+                        .issues
+                        .get(*blocker_id)
+                        .is_some_and(|b| b.status != Status::Closed)
+            })
+            .collect()
+    }
+
+    /// Every non-closed issue upstream of `id` via `blocked_by`, in DFS order.
+    /// Closed blockers end the walk: whatever blocks them no longer matters.
+    pub fn transitive_open_blockers(&self, id: &str) -> Vec<String> {
+        let mut found: Vec<String> = vec![];
+        let mut stack: Vec<&str> = vec![id];
+        while let Some(current) = stack.pop() {
+            let Some(issue) = self.issues.get(current) else {
+                continue;
+            };
+            for blocker in self.open_blockers(issue).into_iter().rev() {
+                if blocker != id && !found.contains(blocker) {
+                    found.push(blocker.clone());
+                    stack.push(blocker);
+                }
+            }
+        }
+        found
+    }
+
+    /// Ready = open (or held under an expired lease) and all blockers closed.
+    pub fn is_ready(&self, issue: &Issue) -> bool {
+        is_claimable(issue) && self.open_blockers(issue).is_empty()
+    }
+
+    /// Stricter ready: claimable, and every issue upstream via hard edges is
+    /// closed, including whatever still blocks an already-closed blocker.
+    pub fn is_ready_transitive(&self, issue: &Issue, memo: &mut HashMap<String, bool>) -> bool {
+        is_claimable(issue) && self.upstream_closed(&issue.id, memo, &mut HashSet::new()).0
+    }
+
+    /// Whether everything upstream of `id` is closed, plus whether that answer
+    /// is final. A walk that hits an issue already on the DFS path (a cycle)
+    /// is provisional, so only final answers go into `memo`.
+    pub fn upstream_closed(
+        &self,
+        id: &str,
+        memo: &mut HashMap<String, bool>,
+        visiting: &mut HashSet<String>,
+    ) -> (bool, bool) {
+        if let Some(&closed) = memo.get(id) {
+            return (closed, true);
+        }
+        let Some(issue) = self.issues.get(id) else {
+            return (true, true);
+        };
+        if !visiting.insert(id.to_string()) {
+            return (true, false);
+        }
+
+        let mut closed = true;
+        let mut complete = true;
+        for blocker_id in &issue.blocked_by {
+            if !issue.edge(blocker_id).hard {
+                continue;
+            }
+            let Some(blocker) = self.issues.get(blocker_id) else {
+                continue;
+            };
+            if blocker.status != Status::Closed {
+                closed = false;
+                break;
+            }
+            let (upstream, final_answer) = self.upstream_closed(blocker_id, memo, visiting);
+            complete &= final_answer;
+            if !upstream {
+                closed = false;
+                break;
+            }
+        }
+
+        visiting.remove(id);
+        // An open ancestor is conclusive even mid-cycle
+        if !closed || complete {
+            memo.insert(id.to_string(), closed);
+            return (closed, true);
+        }
+        (closed, false)
+    }
+
+    /// Expand a possibly abbreviated ID to the one issue it names.
+    /// `partial` may prefix the full ID (`ab-x7`) or just its hash (`x7`).
+    pub fn resolve_id(&self, partial: &str) -> Result<String, BaError> {
+        if self.issues.contains_key(partial) {
+            return Ok(partial.to_string());
+        }
+
+        let mut candidates: Vec<&String> = self
+            .issues
+            .keys()
+            .filter(|id| {
+                id.starts_with(partial)
+                    || id
+                        .split_once('-')
+                        .is_some_and(|(_, hash)| hash.starts_with(partial))
+            })
+            .collect();
+        candidates.sort();
+
+        match candidates.as_slice() {
+            [] => Err(BaError::NotFound(format!("Issue not found: {}", partial))),
+            [id] => Ok(id.to_string()),
+            _ => {
+                let list: Vec<&str> = candidates.iter().map(|id| id.as_str()).collect();
+                Err(BaError::Ambiguous(format!(
+                    "Ambiguous ID '{}' matches: {}",
+                    partial,
+                    list.join(", ")
+                )))
+            }
+        }
+    }
+
+    /// Every distinct cycle in the `blocked_by` graph, each listed once.
+    pub fn detect_cycles(&self, open_only: bool) -> Vec<Vec<String>> {
+        let mut cycles: Vec<Vec<String>> = vec![];
+
+        // Sorted start points keep the report (and what --break cuts) stable
+        let mut ids: Vec<&String> = self.issues.keys().collect();
+        ids.sort();
+        for id in ids {
+            let mut visited = vec![];
+            let mut path = vec![];
+            self.find_cycles(id, open_only, &mut visited, &mut path, &mut cycles);
+        }
+
+        // Deduplicate cycles (same cycle can be found from different starting points)
+        let mut unbaue_cycles: Vec<Vec<String>> = vec![];
+        for cycle in cycles {
+            let normalized = normalize_cycle(&cycle);
+            if !unbaue_cycles
+                .iter()
+                .any(|c| normalize_cycle(c) == normalized)
+            {
+                unbaue_cycles.push(cycle);
+            }
+        }
+        unbaue_cycles
+    }
+
+    fn find_cycles(
+        &self,
+        id: &str,
+        open_only: bool,
+        visited: &mut Vec<String>,
+        path: &mut Vec<String>,
+        cycles: &mut Vec<Vec<String>>,
+    ) {
+        if path.contains(&id.to_string()) {
+            // Found a cycle
+            let cycle_start = path.iter().position(|x| x == id).unwrap();
+            let cycle: Vec<String> = path[cycle_start..].to_vec();
+            cycles.push(cycle);
+            return;
+        }
+
+        if visited.contains(&id.to_string()) {
+            return;
+        }
+
+        // A closed issue can't take part in an active cycle
+        if open_only
+            && self
+                .issues
+                .get(id)
+                .is_some_and(|issue| issue.status == Status::Closed)
+        {
+            return;
+        }
+
+        visited.push(id.to_string());
+        path.push(id.to_string());
+
+        if let Some(issue) = self.issues.get(id) {
+            for blocker in &issue.blocked_by {
+                self.find_cycles(blocker, open_only, visited, path, cycles);
+            }
+        }
+
+        path.pop();
+    }
+
+    /// Shortest chain of `blocked_by` edges leading from `from` to `to`, both
+    /// ends included, or None when `from` doesn't (transitively) wait on `to`.
+    pub fn blocker_path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        let mut came_from: HashMap<&str, &str> = HashMap::new();
+        let mut queue = std::collections::VecDeque::from([from]);
+        while let Some(current) = queue.pop_front() {
+            if current == to {
+                let mut path = vec![to.to_string()];
+                let mut step = to;
+                while let Some(prev) = came_from.get(step) {
+                    path.push(prev.to_string());
+                    step = prev;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            let Some(issue) = self.issues.get(current) else {
+                continue;
+            };
+            for blocker in &issue.blocked_by {
+                if blocker != from && !came_from.contains_key(blocker.as_str()) {
+                    came_from.insert(blocker, current);
+                    queue.push_back(blocker);
+                }
+            }
+        }
+        None
+    }
+
+    /// Make `id` wait on `blocker` through `edge`, on both ends. Refuses a
+    /// self-block, an existing edge, and (unless `force`) an edge that closes
+    /// a cycle. Returns the resolved `(id, blocker)`.
+    pub fn add_block(
+        &mut self,
+        id: &str,
+        blocker: &str,
+        edge: Edge,
+        force: bool,
+    ) -> Result<(String, String), BaError> {
+        let id = self.resolve_id(id)?;
+        let blocker = self.resolve_id(blocker)?;
+
+        if id == blocker {
+            return Err("Issue cannot block itself".into());
+        }
+        if self.issues[&id].blocked_by.contains(&blocker) {
+            return Err(format!("{} already blocked by {}", id, blocker).into());
+        }
+        // The new edge closes a cycle if the blocker already waits on `id`
+        if !force && let Some(path) = self.blocker_path(&blocker, &id) {
+            return Err(format!(
+                "would create a cycle: {} -> {} (use --force to add it anyway)",
+                id,
+                path.join(" -> ")
+            )
+            .into());
+        }
+
+        let now = Utc::now();
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        issue.blocked_by.push(blocker.clone());
+        issue.set_edge(&blocker, edge);
+        issue.updated_at = now;
+        let blocker_issue = self.issues.get_mut(&blocker).expect("resolved ID exists");
+        blocker_issue.blocks.push(id.clone());
+        blocker_issue.updated_at = now;
+        Ok((id, blocker))
+    }
+
+    /// Undo [`Store::add_block`]: `id` no longer waits on `blocker`.
+    /// Returns the resolved `(id, blocker)`.
+    pub fn unblock(&mut self, id: &str, blocker: &str) -> Result<(String, String), BaError> {
+        let id = self.resolve_id(id)?;
+        let blocker = self.resolve_id(blocker)?;
+        if !self.issues[&id].blocked_by.contains(&blocker) {
+            return Err(format!("{} is not blocked by {}", id, blocker).into());
+        }
+        self.remove_block(&id, &blocker, Utc::now());
+        Ok((id, blocker))
+    }
+
+    /// Add (`link`) or remove the symmetric related link between `id` and
+    /// `other`. Returns the resolved `(id, other)`.
+    pub fn set_related(
+        &mut self,
+        id: &str,
+        other: &str,
+        link: bool,
+    ) -> Result<(String, String), BaError> {
+        let id = self.resolve_id(id)?;
+        let other = self.resolve_id(other)?;
+
+        if id == other {
+            return Err("Issue cannot be related to itself".into());
+        }
+        let linked = self.issues[&id].related.contains(&other);
+        if link && linked {
+            return Err(format!("{} already related to {}", id, other).into());
+        }
+        if !link && !linked {
+            return Err(format!("{} is not related to {}", id, other).into());
+        }
+
+        let now = Utc::now();
+        for (this, that) in [(&id, &other), (&other, &id)] {
+            let issue = self.issues.get_mut(this).expect("resolved ID exists");
+            if link {
+                issue.related.push(that.clone());
+            } else {
+                issue.related.retain(|r| r != that);
+            }
+            issue.updated_at = now;
+        }
+        Ok((id, other))
+    }
+
+    /// Put `id` inside `epic`. Refuses a non-epic parent and a parent that
+    /// `id` already (transitively) contains.
+    pub fn set_parent(&mut self, id: &str, epic: &str) -> Result<Issue, BaError> {
+        let id = self.resolve_id(id)?;
+        let epic = self.resolve_id(epic)?;
+
+        if id == epic {
+            return Err("Issue cannot be its own parent".into());
+        }
+        if !matches!(self.issues[&epic].issue_type, IssueType::Epic) {
+            return Err(format!(
+                "{} is a {}, not an epic",
+                epic, self.issues[&epic].issue_type
+            )
+            .into());
+        }
+        if self.is_ancestor(&id, &epic) {
+            return Err(format!(
+                "Setting parent would create a cycle: {} is an ancestor of {}",
+                id, epic
+            )
+            .into());
+        }
+
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        issue.parent = Some(epic);
+        issue.updated_at = Utc::now();
+        Ok(issue.clone())
+    }
+
+    /// Take `id` out of `epic`; the epic must be its current parent.
+    pub fn unparent(&mut self, id: &str, epic: &str) -> Result<Issue, BaError> {
+        let id = self.resolve_id(id)?;
+        let epic = self.resolve_id(epic)?;
+
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        if issue.parent.as_ref() != Some(&epic) {
+            return Err(format!("{} is not a child of {}", id, epic).into());
+        }
+        issue.parent = None;
+        issue.updated_at = Utc::now();
+        Ok(issue.clone())
+    }
+
+    /// Whether `ancestor` is `id` itself or contains it, walking up parents.
+    fn is_ancestor(&self, ancestor: &str, id: &str) -> bool {
+        let mut current = Some(id.to_string());
+        let mut seen: Vec<String> = vec![];
+        while let Some(step) = current {
+            if step == ancestor {
+                return true;
+            }
+            if seen.contains(&step) {
+                return false;
+            }
+            current = self.issues.get(&step).and_then(|i| i.parent.clone());
+            seen.push(step);
+        }
+        false
+    }
+
+    /// Close an unclaimed issue, keeping `reason`.
+    pub fn close(&mut self, id: &str, reason: Option<String>) -> Result<Issue, BaError> {
+        let id = self.resolve_id(id)?;
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        issue.apply(Transition::Close)?;
+        issue.close_reason = reason;
+        Ok(issue.clone())
+    }
+
+    /// Close `id`, then every blocker up its `blocked_by` chain that no open
+    /// issue outside the cascade still waits on. Claimed blockers are skipped,
+    /// and so is whatever only they still need.
+    pub fn close_cascade(&mut self, id: &str, reason: Option<String>) -> Result<Cascade, BaError> {
+        let id = self.resolve_id(id)?;
+        self.issues
+            .get_mut(&id)
+            .expect("resolved ID exists")
+            .apply(Transition::Close)?;
+
+        // Every unfinished issue upstream of `id`; the set guards against cycles
+        let mut upstream: BTreeSet<String> = BTreeSet::new();
+        let mut pending = self.issues[&id].blocked_by.clone();
+        while let Some(blocker_id) = pending.pop() {
+            let Some(blocker) = self.issues.get(&blocker_id) else {
+                continue;
+            };
+            if blocker.status != Status::Closed && upstream.insert(blocker_id) {
+                pending.extend(blocker.blocked_by.iter().cloned());
+            }
+        }
+
+        // Drop blockers that something open outside the set depends on, until
+        // nothing changes; a cycle entirely inside the set closes as a whole
+        let prune = |store: &Store, set: &mut BTreeSet<String>| loop {
+            let needed: Vec<String> = set
+                .iter()
+                .filter(|candidate| {
+                    store.issues[*candidate].blocks.iter().any(|dependent| {
+                        !set.contains(dependent)
+                            && store
+                                .issues
+                                .get(dependent)
+                                .is_some_and(|d| d.status != Status::Closed)
+                    })
+                })
+                .cloned()
+                .collect();
+            if needed.is_empty() {
+                break;
+            }
+            for candidate in needed {
+                set.remove(&candidate);
+            }
+        };
+        prune(self, &mut upstream);
+
+        let skipped: Vec<(String, String)> = upstream
+            .iter()
+            .filter_map(|candidate| {
+                let session = self.issues[candidate].session_id.clone()?;
+                Some((candidate.clone(), session))
+            })
+            .collect();
+        for (skipped_id, _) in &skipped {
+            upstream.remove(skipped_id);
+        }
+        prune(self, &mut upstream);
+
+        let mut closed = vec![id.clone()];
+        closed.extend(upstream);
+        for closed_id in &closed {
+            let issue = self.issues.get_mut(closed_id).expect("collected ID exists");
+            if closed_id != &id {
+                issue.apply(Transition::Close)?;
+            }
+            issue.close_reason = reason.clone();
+        }
+        Ok(Cascade { closed, skipped })
+    }
+
+    /// Close `id` with reason "duplicate", point it at `canonical`, and leave
+    /// a comment by `author` saying so. Duplicates can't chain: the canonical
+    /// issue must not be a duplicate itself, and `id` must not have
+    /// duplicates of its own.
+    pub fn close_as_duplicate(
+        &mut self,
+        id: &str,
+        canonical: &str,
+        author: &str,
+    ) -> Result<Issue, BaError> {
+        let id = self.resolve_id(id)?;
+        let canonical = self.resolve_id(canonical)?;
+        if id == canonical {
+            return Err("An issue can't be a duplicate of itself".into());
+        }
+        if let Some(original) = &self.issues[&canonical].duplicate_of {
+            return Err(format!(
+                "{} is itself a duplicate of {}; mark {} as a duplicate of {} instead",
+                canonical, original, id, original
+            )
+            .into());
+        }
+        let mut dups: Vec<&str> = self
+            .issues
+            .values()
+            .filter(|i| i.duplicate_of.as_ref() == Some(&id))
+            .map(|i| i.id.as_str())
+            .collect();
+        if !dups.is_empty() {
+            dups.sort();
+            return Err(format!(
+                "{} is the canonical issue for {}; it can't become a duplicate itself",
+                id,
+                dups.join(", ")
+            )
+            .into());
+        }
+
+        let text = format!(
+            "Duplicate of {}: {}",
+            canonical, self.issues[&canonical].title
+        );
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        issue.apply(Transition::Close)?;
+        issue.close_reason = Some("duplicate".to_string());
+        issue.duplicate_of = Some(canonical);
+        let now = Utc::now();
+        issue.comments.push(Comment {
+            author: author.to_string(),
+            text,
+            created_at: now,
+            edited_at: None,
+        });
+        issue.updated_at = now;
+        Ok(issue.clone())
+    }
+
+    /// Merge `b` into `a`: text, labels, comments and time are appended to
+    /// `a`, every link to or from `b` is moved onto `a`, and `b` is deleted.
+    /// `author` signs the merge note. Refuses a closed `a`, a claimed `b`, and
+    /// `b` with children when `a` isn't an epic.
+    pub fn merge_issues(&mut self, a: &str, b: &str, author: &str) -> Result<Issue, BaError> {
+        let a = self.resolve_id(a)?;
+        let b = self.resolve_id(b)?;
+        if a == b {
+            return Err("An issue can't be merged into itself".into());
+        }
+        let (into, from) = (&self.issues[&a], &self.issues[&b]);
+        if into.status == Status::Closed {
+            return Err(format!("{} is closed; reopen it before merging into it", a).into());
+        }
+        if let Some(session) = &from.session_id {
+            return Err(format!(
+                "{} is claimed by session {}; release it before merging",
+                b, session
+            )
+            .into());
+        }
+        let has_children = self.issues.values().any(|i| i.parent.as_ref() == Some(&b));
+        if has_children && !matches!(into.issue_type, IssueType::Epic) {
+            return Err(format!(
+                "{} has children but {} is a {}, not an epic",
+                b, a, into.issue_type
+            )
+            .into());
+        }
+
+        let now = Utc::now();
+        let from = self.issues[&b].clone();
+
+        // Links pointing at `b` from elsewhere now point at `a`; `remove_issues`
+        // below scrubs whatever is left (including links between `a` and `b`)
+        for issue in self.issues.values_mut() {
+            if issue.id == a || issue.id == b {
+                continue;
+            }
+            let mut touched = false;
+            if issue.blocks.contains(&b) {
+                issue.blocks.retain(|x| x != &b);
+                if !issue.blocks.contains(&a) {
+                    issue.blocks.push(a.clone());
+                }
+                touched = true;
+            }
+            if issue.blocked_by.contains(&b) {
+                let edge = issue.edge(&b);
+                issue.blocked_by.retain(|x| x != &b);
+                issue.edges.remove(&b);
+                if !issue.blocked_by.contains(&a) {
+                    issue.blocked_by.push(a.clone());
+                    issue.set_edge(&a, edge);
+                }
+                touched = true;
+            }
+            if issue.related.contains(&b) {
+                issue.related.retain(|x| x != &b);
+                if !issue.related.contains(&a) {
+                    issue.related.push(a.clone());
+                }
+                touched = true;
+            }
+            if issue.parent.as_ref() == Some(&b) {
+                issue.parent = Some(a.clone());
+                touched = true;
+            }
+            if issue.duplicate_of.as_ref() == Some(&b) {
+                issue.duplicate_of = Some(a.clone());
+                touched = true;
+            }
+            if touched {
+                issue.updated_at = now;
+            }
+        }
+
+        // Adopt `b`'s parent only if `a` has none and it wouldn't make `a` its own ancestor
+        let mut parent = from.parent.clone().filter(|p| p != &a);
+        let mut ancestor = parent.clone();
+        let mut seen = vec![];
+        while let Some(current) = ancestor {
+            if current == a || seen.contains(&current) {
+                parent = None;
+                break;
+            }
+            ancestor = self.issues.get(&current).and_then(|i| i.parent.clone());
+            seen.push(current);
+        }
+
+        let into = self.issues.get_mut(&a).expect("resolved ID exists");
+        for blocker in from.blocked_by.iter().filter(|x| **x != a) {
+            if !into.blocked_by.contains(blocker) {
+                into.blocked_by.push(blocker.clone());
+                into.set_edge(blocker, from.edge(blocker));
+            }
+        }
+        for dependent in from.blocks.iter().filter(|x| **x != a) {
+            if !into.blocks.contains(dependent) {
+                into.blocks.push(dependent.clone());
+            }
+        }
+        for other in from.related.iter().filter(|x| **x != a) {
+            if !into.related.contains(other) {
+                into.related.push(other.clone());
+            }
+        }
+        if into.parent.is_none() {
+            into.parent = parent;
+        }
+
+        if !from.description.is_empty() {
+            if !into.description.is_empty() {
+                into.description.push_str("\n\n");
+            }
+            into.description.push_str(&format!(
+                "Merged from {} ({}):\n{}",
+                b, from.title, from.description
+            ));
+        }
+        let gained: Vec<String> = from
+            .labels
+            .iter()
+            .filter(|l| !into.labels.contains(l))
+            .cloned()
+            .collect();
+        into.labels.extend(gained.iter().cloned());
+        into.labels.sort();
+        into.apply_label_priority(&self.config.label_priority, &gained);
+        into.comments
+            .extend(from.comments.iter().cloned().map(|mut comment| {
+                comment.text = format!("[from {}] {}", b, comment.text);
+                comment
+            }));
+        into.comments.push(Comment {
+            author: author.to_string(),
+            text: format!("Merged {}: {}", b, from.title),
+            created_at: now,
+            edited_at: None,
+        });
+        into.time_entries.extend(from.time_entries.iter().copied());
+        into.time_entries.sort_by_key(|(start, _)| *start);
+        into.updated_at = now;
+
+        self.remove_issues(std::slice::from_ref(&b), now);
+        Ok(self.issues[&a].clone())
+    }
+
+    /// Append a comment by `author` to `id`.
+    pub fn add_comment(&mut self, id: &str, author: &str, text: &str) -> Result<Issue, BaError> {
+        let id = self.resolve_id(id)?;
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        let now = Utc::now();
+        issue.comments.push(Comment {
+            author: author.to_string(),
+            text: text.to_string(),
+            created_at: now,
+            edited_at: None,
+        });
+        issue.updated_at = now;
+        Ok(issue.clone())
+    }
+
+    /// Add `label` to `id`, applying any `label-priority` rule. Returns the
+    /// issue and, if a rule moved it, its old priority.
+    pub fn add_label(&mut self, id: &str, label: &str) -> Result<(Issue, Option<u8>), BaError> {
+        let id = self.resolve_id(id)?;
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        if issue.labels.iter().any(|l| l == label) {
+            return Err(format!("Label '{}' already exists on {}", label, id).into());
+        }
+        issue.labels.push(label.to_string());
+        issue.labels.sort();
+        let reprioritized =
+            issue.apply_label_priority(&self.config.label_priority, &[label.to_string()]);
+        issue.updated_at = Utc::now();
+        Ok((issue.clone(), reprioritized))
+    }
+
+    /// Remove `label` from `id`. A priority a label rule set stays.
+    pub fn remove_label(&mut self, id: &str, label: &str) -> Result<Issue, BaError> {
+        let id = self.resolve_id(id)?;
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        if !issue.labels.iter().any(|l| l == label) {
+            return Err(format!("Label '{}' not found on {}", label, id).into());
+        }
+        issue.labels.retain(|l| l != label);
+        issue.updated_at = Utc::now();
+        Ok(issue.clone())
+    }
+
+    /// Set the priority of `id` (0-4). With `bump_blockers`, every transitive
+    /// open blocker less urgent than `value` is raised to it too: an urgent
+    /// issue is only as urgent as its slowest blocker.
+    pub fn set_priority(
+        &mut self,
+        id: &str,
+        value: u8,
+        bump_blockers: bool,
+    ) -> Result<PriorityChange, BaError> {
+        if value > 4 {
+            return Err("Priority must be 0-4".into());
+        }
+
+        let now = Utc::now();
+        let id = self.resolve_id(id)?;
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        let from = issue.priority;
+        issue.priority = value;
+        issue.updated_at = now;
+        let issue = issue.clone();
+
+        let mut bumped: Vec<(String, u8)> = vec![];
+        if bump_blockers {
+            for blocker_id in self.transitive_open_blockers(&id) {
+                let blocker = self.issues.get_mut(&blocker_id).expect("blocker exists");
+                if blocker.priority > value {
+                    bumped.push((blocker_id, blocker.priority));
+                    blocker.priority = value;
+                    blocker.updated_at = now;
+                }
+            }
+        }
+        Ok(PriorityChange {
+            issue,
+            from,
+            bumped,
+        })
+    }
+
+    /// Move open issues one priority level by idle time: up once idle for
+    /// `escalate_after_days`, down instead once idle for `decay_after_days`.
+    /// Claimed work is left alone. A change counts as an update, but idle time
+    /// keeps counting from the last other change, and an issue moves at most
+    /// one level per escalation period so repeated runs don't race it to P0.
+    pub fn groom(&mut self) -> Result<Vec<Groomed>, BaError> {
+        let (escalate, decay) = (
+            self.config.escalate_after_days,
+            self.config.decay_after_days,
+        );
+        let Some(step) = escalate.or(decay) else {
+            return Err("No grooming rules configured (ba config set escalate-after <days>, or decay-after <days>)".into());
+        };
+        let idle_for = |days: Option<u32>, idle: i64| days.is_some_and(|d| idle >= i64::from(d));
+
+        let now = Utc::now();
+        let mut changes: Vec<Groomed> = vec![];
+        for issue in self.issues.values_mut() {
+            if issue.status != Status::Open {
+                continue;
+            }
+            // Only a groom since the last real change moves the idle origin
+            let groomed_last = issue.groomed_at == Some(issue.updated_at);
+            let since = match issue.idle_since {
+                Some(since) if groomed_last => since,
+                _ => issue.updated_at,
+            };
+            let idle = (now - since).num_days();
+            if groomed_last && (now - issue.updated_at).num_days() < i64::from(step) {
+                continue;
+            }
+            let to = if idle_for(decay, idle) {
+                if issue.priority == 4 {
+                    continue;
+                }
+                issue.priority + 1
+            } else if idle_for(escalate, idle) && issue.priority > 0 {
+                issue.priority - 1
+            } else {
+                continue;
+            };
+            changes.push(Groomed {
+                id: issue.id.clone(),
+                title: issue.title.clone(),
+                from: issue.priority,
+                to,
+                idle_days: idle,
+            });
+            issue.priority = to;
+            issue.updated_at = now;
+            issue.groomed_at = Some(now);
+            issue.idle_since = Some(since);
+        }
+        changes.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(changes)
+    }
+
+    /// Add issues from a beads JSONL export, minting ba IDs unless `keep_ids`.
+    /// A line that can't be read or parsed is reported and skipped; an ID
+    /// the store already has is skipped.
+    pub fn import_beads(
+        &mut self,
+        reader: impl BufRead,
+        keep_ids: bool,
+    ) -> Result<ImportReport, BaError> {
+        let mut imported = 0;
+        let mut skipped = 0;
+        let mut errors: Vec<ImportError> = vec![];
+        let mut id_map: HashMap<String, String> = HashMap::new(); // old_id -> new_id
+
+        // First pass: parse all issues and build ID map
+        let mut beads_issues: Vec<(usize, BeadsIssue)> = vec![];
+
+        for (line_num, line) in reader.lines().enumerate() {
+            let line_num = line_num + 1; // 1-indexed for user display
+            let line = match line {
+                Ok(l) => l,
+                Err(e) => {
+                    errors.push(ImportError {
+                        line_num,
+                        issue_id: None,
+                        field: "line".to_string(),
+                        message: format!("Failed to read: {}", e),
+                    });
+                    continue;
+                }
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // First try to get the ID for better error messages
+            let raw: serde_json::Value = match serde_json::from_str(&line) {
+                Ok(v) => v,
+                Err(e) => {
+                    errors.push(ImportError {
+                        line_num,
+                        issue_id: None,
+                        field: "json".to_string(),
+                        message: format!("Invalid JSON: {}", e),
+                    });
+                    continue;
+                }
+            };
+
+            let issue_id = raw
+                .get("id")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+
+            // Now parse as BeadsIssue
+            let beads_issue: BeadsIssue = match serde_json::from_value(raw.clone()) {
+                Ok(i) => i,
+                Err(e) => {
+                    // Try to identify which field failed
+                    let field = if raw.get("title").is_none() {
+                        "title (missing)"
+                    } else if raw.get("status").is_none() {
+                        "status (missing)"
+                    } else if raw.get("issue_type").is_none() {
+                        "issue_type (missing)"
+                    } else if raw.get("created_at").is_none() {
+                        "created_at (missing)"
+                    } else if raw.get("updated_at").is_none() {
+                        "updated_at (missing)"
+                    } else {
+                        "parsing"
+                    };
+                    errors.push(ImportError {
+                        line_num,
+                        issue_id,
+                        field: field.to_string(),
+                        message: format!("{}", e),
+                    });
+                    continue;
+                }
+            };
+
+            beads_issues.push((line_num, beads_issue));
+        }
+
+        // Build ID map (before creating issues, so we can resolve dependencies)
+        for (_, beads) in &beads_issues {
+            let new_id = if keep_ids {
+                beads.id.clone()
+            } else {
+                // Parse timestamp for ID generation
+                let ts = DateTime::parse_from_rfc3339(&beads.created_at)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now());
+                self.generate_id(&beads.title, &ts)
+            };
+            id_map.insert(beads.id.clone(), new_id);
+        }
+
+        // Second pass: create issues with resolved dependencies
+        for (line_num, beads) in beads_issues {
+            let new_id = id_map.get(&beads.id).unwrap().clone();
+
+            // Check for duplicate
+            if self.issues.contains_key(&new_id) {
+                skipped += 1;
+                continue;
+            }
+
+            // Parse status
+            let status = match beads.status.as_str() {
+                "open" => Status::Open,
+                "in_progress" => Status::InProgress,
+                "closed" => Status::Closed,
+                other => {
+                    errors.push(ImportError {
+                        line_num,
+                        issue_id: Some(beads.id.clone()),
+                        field: "status".to_string(),
+                        message: format!(
+                            "Unknown status '{}', expected open/in_progress/closed",
+                            other
+                        ),
+                    });
+                    continue;
+                }
+            };
+
+            // Parse issue_type (unknown types map to task)
+            let issue_type: IssueType = match beads.issue_type.parse() {
+                Ok(t) => t,
+                Err(_) => {
+                    errors.push(ImportError {
+                        line_num,
+                        issue_id: Some(beads.id.clone()),
+                        field: "issue_type".to_string(),
+                        message: format!("Unknown type '{}' mapped to 'task'", beads.issue_type),
+                    });
+                    IssueType::Task
+                }
+            };
+
+            // Parse timestamps
+            let created_at = match DateTime::parse_from_rfc3339(&beads.created_at) {
+                Ok(dt) => dt.with_timezone(&Utc),
+                Err(e) => {
+                    errors.push(ImportError {
+                        line_num,
+                        issue_id: Some(beads.id.clone()),
+                        field: "created_at".to_string(),
+                        message: format!("Invalid timestamp '{}': {}", beads.created_at, e),
+                    });
+                    continue;
+                }
+            };
+
+            let updated_at = match DateTime::parse_from_rfc3339(&beads.updated_at) {
+                Ok(dt) => dt.with_timezone(&Utc),
+                Err(e) => {
+                    errors.push(ImportError {
+                        line_num,
+                        issue_id: Some(beads.id.clone()),
+                        field: "updated_at".to_string(),
+                        message: format!("Invalid timestamp '{}': {}", beads.updated_at, e),
+                    });
+                    continue;
+                }
+            };
+
+            let closed_at = if let Some(ref ca) = beads.closed_at {
+                match DateTime::parse_from_rfc3339(ca) {
+                    Ok(dt) => Some(dt.with_timezone(&Utc)),
+                    Err(e) => {
+                        errors.push(ImportError {
+                            line_num,
+                            issue_id: Some(beads.id.clone()),
+                            field: "closed_at".to_string(),
+                            message: format!("Invalid timestamp '{}': {}", ca, e),
+                        });
+                        continue;
+                    }
+                }
+            } else {
+                None
+            };
+
+            // Map dependencies where this issue depends on another. Beads only gates
+            // on `blocks`: the first `parent-child` becomes the parent epic, `related`
+            // becomes a cross-reference, and the rest are soft edges keeping their type
+            let mut blocked_by: Vec<String> = vec![];
+            let mut edges: BTreeMap<String, Edge> = BTreeMap::new();
+            let mut related: Vec<String> = vec![];
+            let mut parent: Option<String> = None;
+            for dep in beads.dependencies.iter().filter(|d| d.issue_id == beads.id) {
+                if dep.dep_type.eq_ignore_ascii_case("related") {
+                    if let Some(other) = id_map.get(&dep.depends_on_id)
+                        && !related.contains(other)
+                    {
+                        related.push(other.clone());
+                    }
+                    continue;
+                }
+                let dep_type: DepType = match dep.dep_type.parse() {
+                    Ok(t) => t,
+                    Err(_) => {
+                        errors.push(ImportError {
+                            line_num,
+                            issue_id: Some(beads.id.clone()),
+                            field: "dependencies".to_string(),
+                            message: format!(
+                                "Unsupported dependency type '{}' on {} skipped",
+                                dep.dep_type, dep.depends_on_id
+                            ),
+                        });
+                        continue;
+                    }
+                };
+                let Some(new_blocker_id) = id_map.get(&dep.depends_on_id) else {
+                    continue;
+                };
+                if dep_type == DepType::ParentChild && parent.is_none() {
+                    parent = Some(new_blocker_id.clone());
+                    continue;
+                }
+                blocked_by.push(new_blocker_id.clone());
+                if dep_type != DepType::Blocks {
+                    edges.insert(
+                        new_blocker_id.clone(),
+                        Edge {
+                            dep_type,
+                            hard: false,
+                        },
+                    );
+                }
+            }
+
+            // A comment with a bad timestamp is reported and dropped; the issue
+            // still goes in
+            let mut comments: Vec<Comment> = vec![];
+            for comment in beads.comments {
+                match DateTime::parse_from_rfc3339(&comment.created_at) {
+                    Ok(dt) => comments.push(Comment {
+                        author: if comment.author.is_empty() {
+                            "anonymous".to_string()
+                        } else {
+                            comment.author
+                        },
+                        text: comment.text,
+                        created_at: dt.with_timezone(&Utc),
+                        edited_at: None,
+                    }),
+                    Err(e) => errors.push(ImportError {
+                        line_num,
+                        issue_id: Some(beads.id.clone()),
+                        field: "comments".to_string(),
+                        message: format!(
+                            "Invalid comment timestamp '{}': {} (comment skipped)",
+                            comment.created_at, e
+                        ),
+                    }),
+                }
+            }
+            comments.sort_by_key(|c| c.created_at);
+
+            let mut labels = beads.labels;
+            labels.sort();
+            labels.dedup();
+
+            let issue = Issue {
+                id: new_id.clone(),
+                title: beads.title,
+                description: beads.description,
+                status,
+                priority: beads.priority.min(4),
+                issue_type,
+                session_id: None,
+                claim_expires_at: None,
+                assignee: None,
+                labels,
+                comments,
+                created_at,
+                updated_at,
+                closed_at,
+                blocks: vec![], // Will be filled in next pass
+                blocked_by,
+                edges,
+                related,
+                parent,
+                due: None,
+                estimate: None,
+                rank: None,
+                close_reason: None,
+                duplicate_of: None,
+                archived: false,
+                time_entries: vec![],
+                groomed_at: None,
+                idle_since: None,
+            };
+
+            self.issues.insert(new_id, issue);
+            imported += 1;
+        }
+
+        // Third pass: populate `blocks` field (reverse of blocked_by) and mirror
+        // related links, which beads records on one side only
+        let ids: Vec<String> = self.issues.keys().cloned().collect();
+        for id in ids {
+            let issue = self.issues.get(&id).unwrap();
+            let (blocked_by, related) = (issue.blocked_by.clone(), issue.related.clone());
+            for blocker_id in blocked_by {
+                if let Some(blocker) = self.issues.get_mut(&blocker_id)
+                    && !blocker.blocks.contains(&id)
+                {
+                    blocker.blocks.push(id.clone());
+                }
+            }
+            for other_id in related {
+                if let Some(other) = self.issues.get_mut(&other_id)
+                    && !other.related.contains(&id)
+                {
+                    other.related.push(id.clone());
+                }
+            }
+        }
+
+        Ok(ImportReport {
+            imported,
+            skipped,
+            errors,
+        })
+    }
+
+    /// Add issues from the native `issues.jsonl` format. An ID the store
+    /// already has is skipped. Links are kept only between issues this import
+    /// added: one naming a skipped or unknown ID would attach to whatever
+    /// local issue has that ID, so it is dropped and reported instead.
+    pub fn import_native(&mut self, reader: impl BufRead) -> Result<ImportReport, BaError> {
+        let mut report = ImportReport::default();
+        let mut added: Vec<(usize, String)> = vec![];
+
+        for (line_num, line) in reader.lines().enumerate() {
+            let line_num = line_num + 1; // 1-indexed for user display
+            let line = match line {
+                Ok(l) => l,
+                Err(e) => {
+                    report.errors.push(ImportError {
+                        line_num,
+                        issue_id: None,
+                        field: "line".to_string(),
+                        message: format!("Failed to read: {}", e),
+                    });
+                    continue;
+                }
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let issue: Issue = match serde_json::from_str(&line) {
+                Ok(i) => i,
+                Err(e) => {
+                    report.errors.push(ImportError {
+                        line_num,
+                        issue_id: None,
+                        field: "json".to_string(),
+                        message: format!("Invalid issue: {}", e),
+                    });
+                    continue;
+                }
+            };
+
+            if self.issues.contains_key(&issue.id) {
+                report.skipped += 1;
+                continue;
+            }
+
+            added.push((line_num, issue.id.clone()));
+            self.issues.insert(issue.id.clone(), issue);
+            report.imported += 1;
+        }
+
+        let ids: HashSet<String> = added.iter().map(|(_, id)| id.clone()).collect();
+        for (line_num, id) in &added {
+            let issue = self.issues.get_mut(id).expect("just imported");
+            let mut dropped: Vec<(&str, String)> = vec![];
+            for (field, links) in [
+                ("blocks", &mut issue.blocks),
+                ("blocked_by", &mut issue.blocked_by),
+                ("related", &mut issue.related),
+            ] {
+                links.retain(|target| {
+                    let keep = ids.contains(target);
+                    if !keep {
+                        dropped.push((field, target.clone()));
+                    }
+                    keep
+                });
+            }
+            issue.edges.retain(|target, _| {
+                let keep = ids.contains(target);
+                if !keep {
+                    dropped.push(("edges", target.clone()));
+                }
+                keep
+            });
+            for (field, link) in [
+                ("parent", &mut issue.parent),
+                ("duplicate_of", &mut issue.duplicate_of),
+            ] {
+                if let Some(target) = link.take_if(|target| !ids.contains(target)) {
+                    dropped.push((field, target));
+                }
+            }
+
+            report
+                .errors
+                .extend(dropped.into_iter().map(|(field, target)| ImportError {
+                    line_num: *line_num,
+                    issue_id: Some(id.clone()),
+                    field: field.to_string(),
+                    message: format!("Dropped link to {}, which this import did not add", target),
+                }));
+        }
+        Ok(report)
+    }
+
+    /// Add `label` to (`adding`) or remove it from every issue `query`
+    /// matches. Returns how many matched and what changed; a dry run reports
+    /// the same without touching the store.
+    pub fn label_query(
+        &mut self,
+        query: &Query,
+        label: &str,
+        adding: bool,
+        dry_run: bool,
+    ) -> Result<(usize, Relabel), BaError> {
+        let mut matched = 0;
+        let mut affected: Vec<String> = self
+            .issues
+            .values()
+            .filter(|issue| query.matches(issue))
+            .inspect(|_| matched += 1)
+            .filter(|issue| issue.labels.iter().any(|l| l == label) != adding)
+            .map(|issue| issue.id.clone())
+            .collect();
+        affected.sort();
+
+        let mut reprioritized: Vec<(String, u8, u8)> = vec![];
+        let rules = &self.config.label_priority;
+        let now = Utc::now();
+        for id in &affected {
+            let issue = self.issues.get_mut(id).expect("matched ID exists");
+            if dry_run {
+                let mut preview = issue.clone();
+                preview.labels.push(label.to_string());
+                if adding
+                    && let Some(old) = preview.apply_label_priority(rules, &[label.to_string()])
+                {
+                    reprioritized.push((id.clone(), old, preview.priority));
+                }
+                continue;
+            }
+            if adding {
+                issue.labels.push(label.to_string());
+                issue.labels.sort();
+                if let Some(old) = issue.apply_label_priority(rules, &[label.to_string()]) {
+                    reprioritized.push((id.clone(), old, issue.priority));
+                }
+            } else {
+                issue.labels.retain(|l| l != label);
+            }
+            issue.updated_at = now;
+        }
+
+        Ok((
+            matched,
+            Relabel {
+                affected,
+                reprioritized,
+            },
+        ))
+    }
+
+    /// Rename label `old` to `new` on every issue that has it.
+    pub fn rename_label(&mut self, old: &str, new: &str) -> Result<Relabel, BaError> {
+        if new.trim().is_empty() {
+            return Err("Label must not be empty".into());
+        }
+        if old == new {
+            return Err(format!("Label is already '{}'", new).into());
+        }
+
+        let now = Utc::now();
+        let mut affected: Vec<String> = vec![];
+        let mut reprioritized: Vec<(String, u8, u8)> = vec![];
+        for issue in self.issues.values_mut() {
+            if !issue.labels.iter().any(|l| l == old) {
+                continue;
+            }
+            issue.labels.retain(|l| l != old);
+            // Issues that already carry `new` just lose `old`
+            if !issue.labels.iter().any(|l| l == new) {
+                issue.labels.push(new.to_string());
+                issue.labels.sort();
+                if let Some(from) =
+                    issue.apply_label_priority(&self.config.label_priority, &[new.to_string()])
+                {
+                    reprioritized.push((issue.id.clone(), from, issue.priority));
+                }
+            }
+            issue.updated_at = now;
+            affected.push(issue.id.clone());
+        }
+        affected.sort();
+        reprioritized.sort();
+        Ok(Relabel {
+            affected,
+            reprioritized,
+        })
+    }
+
+    /// Give `id` a rank between `other` and its neighbor on the chosen side.
+    /// Ranks are fractional, so usually only `id` changes; when a neighbor is
+    /// unranked or the gap is too narrow for an f64, the level is renumbered
+    /// 1..n in its current order first.
+    pub fn rank(&mut self, id: &str, other: &str, before: bool) -> Result<Issue, BaError> {
+        let id = self.resolve_id(id)?;
+        let other = self.resolve_id(other)?;
+        if id == other {
+            return Err("An issue can't be ranked relative to itself".into());
+        }
+        let priority = self.issues[&id].priority;
+        if self.issues[&other].priority != priority {
+            return Err(format!(
+                "{} is P{} but {} is P{}; rank only orders issues within one priority",
+                id, priority, other, self.issues[&other].priority
+            )
+            .into());
+        }
+
+        // The level as `list` shows it, without the issue being moved
+        let mut level: Vec<&Issue> = self
+            .issues
+            .values()
+            .filter(|i| i.priority == priority && i.id != id && !i.archived)
+            .collect();
+        level.sort_by(|a, b| a.cmp_priority(b));
+        let mut level: Vec<(String, Option<f64>)> =
+            level.iter().map(|i| (i.id.clone(), i.rank)).collect();
+        let pos = level
+            .iter()
+            .position(|(i, _)| *i == other)
+            .expect("other is in the level");
+        let (lo, hi) = if before {
+            (pos.checked_sub(1), Some(pos))
+        } else {
+            (Some(pos), Some(pos + 1).filter(|&next| next < level.len()))
+        };
+
+        let between = |level: &[(String, Option<f64>)]| -> Option<f64> {
+            let rank = |index: Option<usize>| index.map(|i| level[i].1);
+            match (rank(lo), rank(hi)) {
+                (Some(Some(lo)), Some(Some(hi))) => {
+                    let mid = lo + (hi - lo) / 2.0;
+                    (lo < mid && mid < hi).then_some(mid)
+                }
+                (None, Some(Some(hi))) => Some(hi - 1.0),
+                (Some(Some(lo)), None) => Some(lo + 1.0),
+                _ => None,
+            }
+        };
+
+        let now = Utc::now();
+        let rank = match between(&level) {
+            Some(rank) => rank,
+            None => {
+                for (index, (issue_id, rank)) in level.iter_mut().enumerate() {
+                    let renumbered = Some((index + 1) as f64);
+                    if *rank != renumbered {
+                        *rank = renumbered;
+                        let issue = self
+                            .issues
+                            .get_mut(issue_id.as_str())
+                            .expect("level ID exists");
+                        issue.rank = renumbered;
+                        issue.updated_at = now;
+                    }
+                }
+                between(&level).expect("whole-number ranks leave room")
+            }
+        };
+
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        issue.rank = Some(rank);
+        issue.updated_at = now;
+        Ok(issue.clone())
+    }
+
+    /// Set or clear the due date of `id`.
+    pub fn set_due(&mut self, id: &str, due: Option<DateTime<Utc>>) -> Result<Issue, BaError> {
+        let id = self.resolve_id(id)?;
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        issue.due = due;
+        issue.updated_at = Utc::now();
+        Ok(issue.clone())
+    }
+
+    /// Set or clear the assignee of `id`. Assignment is bookkeeping only: it
+    /// never touches the claim state machine.
+    pub fn assign(&mut self, id: &str, name: Option<String>) -> Result<Issue, BaError> {
+        if name.as_ref().is_some_and(|n| n.trim().is_empty()) {
+            return Err("Assignee must not be empty (use --clear to remove)".into());
+        }
+        let id = self.resolve_id(id)?;
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        issue.assignee = name;
+        issue.updated_at = Utc::now();
+        Ok(issue.clone())
+    }
+
+    pub fn set_estimate(&mut self, id: &str, value: u32) -> Result<Issue, BaError> {
+        let id = self.resolve_id(id)?;
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        issue.estimate = Some(value);
+        issue.updated_at = Utc::now();
+        Ok(issue.clone())
+    }
+
+    /// Open a tracked time interval on `id`.
+    pub fn start_timer(&mut self, id: &str) -> Result<Issue, BaError> {
+        let id = self.resolve_id(id)?;
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        if issue.time_entries.iter().any(|(_, end)| end.is_none()) {
+            return Err(format!("Timer already running for {}", id).into());
+        }
+        let now = Utc::now();
+        issue.time_entries.push((now, None));
+        issue.updated_at = now;
+        Ok(issue.clone())
+    }
+
+    /// Close the running interval on `id`. Returns the issue and how long
+    /// that interval ran.
+    pub fn stop_timer(&mut self, id: &str) -> Result<(Issue, chrono::Duration), BaError> {
+        let id = self.resolve_id(id)?;
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        let now = Utc::now();
+        let Some(entry) = issue
+            .time_entries
+            .iter_mut()
+            .rev()
+            .find(|(_, end)| end.is_none())
+        else {
+            return Err(format!("No timer running for {}", id).into());
+        };
+        entry.1 = Some(now);
+        let interval = now - entry.0;
+        issue.updated_at = now;
+        Ok((issue.clone(), interval))
+    }
+
+    /// Replace the text of comment `index` (0-based, as `ba show` numbers
+    /// them) on `id`. Returns the issue and the edited comment.
+    pub fn edit_comment(
+        &mut self,
+        id: &str,
+        index: usize,
+        text: &str,
+    ) -> Result<(Issue, Comment), BaError> {
+        let id = self.resolve_id(id)?;
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        let index = comment_index(issue, index)?;
+        let now = Utc::now();
+        let comment = &mut issue.comments[index];
+        comment.text = text.to_string();
+        comment.edited_at = Some(now);
+        let comment = comment.clone();
+        issue.updated_at = now;
+        Ok((issue.clone(), comment))
+    }
+
+    /// Remove comment `index` from `id`. Returns the issue and the comment.
+    pub fn delete_comment(&mut self, id: &str, index: usize) -> Result<(Issue, Comment), BaError> {
+        let id = self.resolve_id(id)?;
+        let issue = self.issues.get_mut(&id).expect("resolved ID exists");
+        let index = comment_index(issue, index)?;
+        let removed = issue.comments.remove(index);
+        issue.updated_at = Utc::now();
+        Ok((issue.clone(), removed))
+    }
+
+    /// Move `id` (and with `with_deps`, everything connected to it through
+    /// blocks/blocked_by) into `dest`, minting new IDs there unless `keep_id`.
+    /// Returns (old ID, new ID) pairs, sorted. Neither store is saved; save
+    /// `dest` first, so a crash in between duplicates issues rather than
+    /// losing them.
+    pub fn move_to(
+        &mut self,
+        dest: &mut Store,
+        id: &str,
+        keep_id: bool,
+        with_deps: bool,
+    ) -> Result<Vec<(String, String)>, BaError> {
+        let id = self.resolve_id(id)?;
+
+        let linked = |issue: &Issue| -> Vec<String> {
+            issue
+                .blocked_by
+                .iter()
+                .chain(&issue.blocks)
+                .filter(|other| self.issues.contains_key(*other))
+                .cloned()
+                .collect()
+        };
+
+        let mut moving: Vec<String> = vec![id.clone()];
+        if with_deps {
+            let mut next = 0;
+            while next < moving.len() {
+                for other in linked(&self.issues[&moving[next]]) {
+                    if !moving.contains(&other) {
+                        moving.push(other);
+                    }
+                }
+                next += 1;
+            }
+        } else {
+            let deps = linked(&self.issues[&id]);
+            if !deps.is_empty() {
+                return Err(format!(
+                    "{} has dependencies that would dangle: {} (use --with-deps to move them too)",
+                    id,
+                    deps.join(", ")
+                )
+                .into());
+            }
+        }
+        moving.sort();
+
+        for old_id in &moving {
+            if let Some(session) = &self.issues[old_id].session_id {
+                return Err(BaError::AlreadyClaimed(format!(
+                    "{} is claimed by session {}. Release it before moving.",
+                    old_id, session
+                )));
+            }
+        }
+
+        // Pick every new ID before inserting so collisions are caught up front
+        let mut renames: HashMap<String, String> = HashMap::new();
+        for old_id in &moving {
+            let new_id = if keep_id {
+                if dest.issues.contains_key(old_id) {
+                    return Err(
+                        format!("{} already exists in {}", old_id, dest.ba_dir.display()).into(),
+                    );
+                }
+                old_id.clone()
+            } else {
+                let issue = &self.issues[old_id];
+                dest.generate_id(&issue.title, &issue.created_at)
+            };
+            // Reserve the ID so the next generate_id can't hand it out again
+            dest.issues
+                .insert(new_id.clone(), self.issues[old_id].clone());
+            renames.insert(old_id.clone(), new_id);
+        }
+
+        // References into the moved set follow it; anything pointing back into the
+        // source store (related links, parent epics) can't, so it's dropped
+        let rename_all = |ids: &[String]| -> Vec<String> {
+            ids.iter().filter_map(|r| renames.get(r).cloned()).collect()
+        };
+        let now = Utc::now();
+        for old_id in &moving {
+            let mut issue = self.issues.remove(old_id).expect("moving IDs exist");
+            issue.id = renames[old_id].clone();
+            issue.blocks = rename_all(&issue.blocks);
+            issue.blocked_by = rename_all(&issue.blocked_by);
+            issue.edges = issue
+                .edges
+                .iter()
+                .filter_map(|(blocker, edge)| Some((renames.get(blocker)?.clone(), *edge)))
+                .collect();
+            issue.related = rename_all(&issue.related);
+            issue.parent = issue.parent.and_then(|p| renames.get(&p).cloned());
+            issue.duplicate_of = issue.duplicate_of.and_then(|c| renames.get(&c).cloned());
+            issue.updated_at = now;
+            dest.issues.insert(issue.id.clone(), issue);
+        }
+
+        for issue in self.issues.values_mut() {
+            let related_before = issue.related.len();
+            issue.related.retain(|r| !renames.contains_key(r));
+            let orphaned = issue
+                .parent
+                .take_if(|parent| renames.contains_key(parent))
+                .is_some()
+                | issue
+                    .duplicate_of
+                    .take_if(|canonical| renames.contains_key(canonical))
+                    .is_some();
+            if issue.related.len() != related_before || orphaned {
+                issue.updated_at = now;
+            }
+        }
+
+        Ok(moving
+            .into_iter()
+            .map(|old_id| {
+                let new_id = renames[&old_id].clone();
+                (old_id, new_id)
+            })
+            .collect())
+    }
+
+    /// Rename every issue minted under the current prefix to `new_prefix`,
+    /// rewriting links to match, and make it the prefix for new issues.
+    /// Returns the old prefix and how many issues were renamed.
+    pub fn reprefix(&mut self, new_prefix: &str) -> Result<(String, usize), BaError> {
+        let valid = (1..=4).contains(&new_prefix.len())
+            && new_prefix
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit());
+        if !valid {
+            return Err(format!(
+                "Invalid prefix '{}': use 1-4 lowercase letters or digits",
+                new_prefix
+            )
+            .into());
+        }
+        let old_prefix = self.config.prefix.clone();
+        if new_prefix == old_prefix {
+            return Err(format!("Prefix is already '{}'", old_prefix).into());
+        }
+
+        // Only IDs minted under the old prefix move; imported IDs keep theirs
+        let old_head = format!("{}-", old_prefix);
+        let renames: HashMap<String, String> = self
+            .issues
+            .keys()
+            .filter_map(|id| {
+                let hash = id.strip_prefix(&old_head)?;
+                Some((id.clone(), format!("{}-{}", new_prefix, hash)))
+            })
+            .collect();
+        let mut collisions: Vec<&String> = renames
+            .values()
+            .filter(|new_id| self.issues.contains_key(*new_id) && !renames.contains_key(*new_id))
+            .collect();
+        if !collisions.is_empty() {
+            collisions.sort();
+            let list: Vec<&str> = collisions.iter().map(|id| id.as_str()).collect();
+            return Err(format!(
+                "Refusing to reprefix: new IDs collide with existing issues: {}",
+                list.join(", ")
+            )
+            .into());
+        }
+
+        let rename = |id: &String| renames.get(id).cloned().unwrap_or_else(|| id.clone());
+        let issues: HashMap<String, Issue> = self
+            .issues
+            .drain()
+            .map(|(_, mut issue)| {
+                issue.id = rename(&issue.id);
+                issue.blocks = issue.blocks.iter().map(rename).collect();
+                issue.blocked_by = issue.blocked_by.iter().map(rename).collect();
+                issue.edges = issue
+                    .edges
+                    .iter()
+                    .map(|(blocker, edge)| (rename(blocker), *edge))
+                    .collect();
+                issue.related = issue.related.iter().map(rename).collect();
+                issue.parent = issue.parent.as_ref().map(rename);
+                issue.duplicate_of = issue.duplicate_of.as_ref().map(rename);
+                (issue.id.clone(), issue)
+            })
+            .collect();
+        self.issues = issues;
+        // A rename isn't a change to the issue; keep it out of the audit log
+        self.loaded = self
+            .loaded
+            .drain()
+            .map(|(id, state)| (rename(&id), state))
+            .collect();
+        self.config.prefix = new_prefix.to_string();
+
+        Ok((old_prefix, renames.len()))
+    }
+
+    /// Reverse the latest save that is still live: every status change it
+    /// made is inverted and every issue it created is removed. A conflict (an
+    /// issue that has moved on since) is an error that may leave earlier
+    /// issues already reverted, so drop the store rather than saving it.
+    pub fn undo(&mut self) -> Result<Undo, BaError> {
+        // One save writes its entries with a shared timestamp; undo reverses that
+        // whole group and writes one group itself, cancelling the latest save that
+        // is still live, so repeated undos walk further back
+        let entries = self.audit_entries()?;
+        let mut live: Vec<&[AuditEntry]> = vec![];
+        for group in entries.chunk_by(|a, b| a.timestamp == b.timestamp) {
+            if group[0].command == "undo" {
+                live.pop();
+            } else {
+                live.push(group);
+            }
+        }
+        let Some(group) = live.pop() else {
+            return Err("Nothing to undo".into());
+        };
+        let command = &group[0].command;
+
+        if let Some(deleted) = group.iter().find(|e| e.to_status.is_none()) {
+            return Err(format!(
+                "Cannot undo '{}' of {}: the issue is gone (restore a snapshot instead)",
+                command, deleted.issue_id
+            )
+            .into());
+        }
+        // Entries that kept their status were side effects (a blocker gaining a
+        // `blocks` link, say); only creates and transitions are reversed
+        let undone: Vec<AuditEntry> = group
+            .iter()
+            .filter(|e| e.from_status != e.to_status)
+            .cloned()
+            .collect();
+        if undone.is_empty() {
+            return Err(format!(
+                "Last change ('{}' on {}) didn't change status; undo only reverses transitions",
+                command,
+                group
+                    .iter()
+                    .map(|e| e.issue_id.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+            .into());
+        }
+
+        let mut created: Vec<String> = vec![];
+        let mut reversed: Vec<Issue> = vec![];
+        for entry in &undone {
+            let id = &entry.issue_id;
+            let issue = self
+                .issues
+                .get_mut(id)
+                .ok_or_else(|| format!("Conflict: {} no longer exists; not undoing", id))?;
+            let to = entry.to_status.as_ref().expect("deletions refused above");
+            if &issue.status != to {
+                return Err(format!(
+                    "Conflict: {} is now {}, not {}; not undoing",
+                    id, issue.status, to
+                )
+                .into());
+            }
+            let Some(from) = &entry.from_status else {
+                created.push(id.clone());
+                continue;
+            };
+
+            let claim = || {
+                entry
+                    .session
+                    .clone()
+                    .map(|session| Transition::Claim { session })
+                    .ok_or_else(|| {
+                        format!(
+                            "Cannot undo '{}' of {}: no session recorded",
+                            entry.command, id
+                        )
+                    })
+            };
+            let inverse = match (to, from) {
+                (Status::InProgress, Status::Open) => vec![Transition::Release],
+                (Status::Closed, Status::Open) => vec![Transition::Reopen],
+                (Status::Open | Status::Closed, Status::InProgress) => vec![claim()?],
+                (Status::Open, Status::Closed) => vec![Transition::Close],
+                (Status::InProgress, Status::Closed) => {
+                    vec![Transition::Release, Transition::Close]
+                }
+                _ => unreachable!("from != to"),
+            };
+            for transition in inverse {
+                issue.apply(transition)?;
+            }
+            reversed.push(issue.clone());
+        }
+        // Removing a created issue also strips the links other issues gained to it
+        self.remove_issues(&created, Utc::now());
+
+        Ok(Undo {
+            undone,
+            reversed,
+            deleted: created,
+        })
+    }
+
+    /// Drop the `id` blocked by `blocker` edge from both ends.
+    pub fn remove_block(&mut self, id: &str, blocker: &str, now: DateTime<Utc>) {
+        if let Some(issue) = self.issues.get_mut(id) {
+            issue.blocked_by.retain(|b| b != blocker);
+            issue.edges.remove(blocker);
+            issue.updated_at = now;
+        }
+        if let Some(blocker_issue) = self.issues.get_mut(blocker) {
+            blocker_issue.blocks.retain(|b| b != id);
+            blocker_issue.updated_at = now;
+        }
+    }
+
+    /// Scan for drift that the commands themselves never produce.
+    pub fn integrity_problems(&self) -> Vec<Problem> {
+        let mut problems: Vec<Problem> = self
+            .corrupt
+            .iter()
+            .map(|corrupt| Problem {
+                id: format!("line {}", corrupt.line),
+                kind: "corrupt-line",
+                detail: format!("{} (kept as-is; fix it by hand)", corrupt.error),
+                fixable: false,
+            })
+            .collect();
+        let mut ids: Vec<&String> = self.issues.keys().collect();
+        ids.sort();
+
+        for id in ids {
+            let issue = &self.issues[id];
+            let mut report = |kind, detail: String, fixable| {
+                problems.push(Problem {
+                    id: id.clone(),
+                    kind,
+                    detail,
+                    fixable,
+                });
+            };
+
+            for blocker in &issue.blocked_by {
+                match self.issues.get(blocker) {
+                    None => report(
+                        "dangling-ref",
+                        format!("blocked_by {} which doesn't exist", blocker),
+                        true,
+                    ),
+                    Some(other) if !other.blocks.contains(id) => report(
+                        "asymmetric-block",
+                        format!("blocked_by {} but {}.blocks lacks it", blocker, blocker),
+                        true,
+                    ),
+                    Some(_) => {}
+                }
+            }
+            for blocked in &issue.blocks {
+                match self.issues.get(blocked) {
+                    None => report(
+                        "dangling-ref",
+                        format!("blocks {} which doesn't exist", blocked),
+                        true,
+                    ),
+                    Some(other) if !other.blocked_by.contains(id) => report(
+                        "asymmetric-block",
+                        format!("blocks {} but {}.blocked_by lacks it", blocked, blocked),
+                        true,
+                    ),
+                    Some(_) => {}
+                }
+            }
+            for other_id in &issue.related {
+                match self.issues.get(other_id) {
+                    None => report(
+                        "dangling-ref",
+                        format!("related to {} which doesn't exist", other_id),
+                        true,
+                    ),
+                    Some(other) if !other.related.contains(id) => report(
+                        "asymmetric-related",
+                        format!("related to {} but {}.related lacks it", other_id, other_id),
+                        true,
+                    ),
+                    Some(_) => {}
+                }
+            }
+            for blocker in issue.edges.keys() {
+                if !issue.blocked_by.contains(blocker) {
+                    report(
+                        "stale-edge",
+                        format!("edge metadata for {} without a blocked_by entry", blocker),
+                        true,
+                    );
+                }
+            }
+            if let Some(parent) = &issue.parent
+                && !self.issues.contains_key(parent)
+            {
+                report(
+                    "dangling-ref",
+                    format!("parent {} doesn't exist", parent),
+                    true,
+                );
+            }
+            if let Some(canonical) = &issue.duplicate_of
+                && !self.issues.contains_key(canonical)
+            {
+                report(
+                    "dangling-ref",
+                    format!("duplicate_of {} doesn't exist", canonical),
+                    true,
+                );
+            }
+
+            match (&issue.status, &issue.session_id) {
+                (Status::InProgress, None) => report(
+                    "unowned-in-progress",
+                    "in_progress without a session_id (claim it to assign an owner)".to_string(),
+                    false,
+                ),
+                (Status::Open | Status::Closed, Some(session)) => report(
+                    "orphaned-session",
+                    format!("{} but still has session {}", issue.status, session),
+                    true,
+                ),
+                _ => {}
+            }
+        }
+
+        problems
+    }
+
+    /// Repair every fixable problem: drop dangling references, mirror
+    /// one-sided blocks and related links, and clear sessions left on issues
+    /// nobody works on.
+    pub fn repair_integrity(&mut self) {
+        let now = Utc::now();
+        let ids: HashSet<String> = self.issues.keys().cloned().collect();
+        let mut mirrors: Vec<(String, String)> = vec![];
+        let mut related_mirrors: Vec<(String, String)> = vec![];
+
+        for issue in self.issues.values_mut() {
+            let before = (
+                issue.blocks.len(),
+                issue.blocked_by.len(),
+                issue.edges.len(),
+                issue.related.len(),
+                issue.parent.is_some(),
+                issue.duplicate_of.is_some(),
+                issue.session_id.is_some(),
+            );
+
+            issue.blocked_by.retain(|b| ids.contains(b));
+            issue.related.retain(|r| ids.contains(r));
+            issue.blocks.retain(|b| ids.contains(b));
+            let blocked_by = issue.blocked_by.clone();
+            issue.edges.retain(|b, _| blocked_by.contains(b));
+            if issue.parent.as_ref().is_some_and(|p| !ids.contains(p)) {
+                issue.parent = None;
+            }
+            if issue
+                .duplicate_of
+                .as_ref()
+                .is_some_and(|c| !ids.contains(c))
+            {
+                issue.duplicate_of = None;
+            }
+            if issue.status != Status::InProgress {
+                issue.session_id = None;
+            }
+
+            for blocker in &issue.blocked_by {
+                mirrors.push((blocker.clone(), issue.id.clone()));
+            }
+            for blocked in &issue.blocks {
+                mirrors.push((issue.id.clone(), blocked.clone()));
+            }
+            for other in &issue.related {
+                related_mirrors.push((other.clone(), issue.id.clone()));
+            }
+
+            let after = (
+                issue.blocks.len(),
+                issue.blocked_by.len(),
+                issue.edges.len(),
+                issue.related.len(),
+                issue.parent.is_some(),
+                issue.duplicate_of.is_some(),
+                issue.session_id.is_some(),
+            );
+            if before != after {
+                issue.updated_at = now;
+            }
+        }
+
+        // Every surviving edge exists on both ends
+        for (blocker, blocked) in mirrors {
+            let blocker_issue = self.issues.get_mut(&blocker).expect("kept IDs exist");
+            if !blocker_issue.blocks.contains(&blocked) {
+                blocker_issue.blocks.push(blocked.clone());
+                blocker_issue.updated_at = now;
+            }
+            let blocked_issue = self.issues.get_mut(&blocked).expect("kept IDs exist");
+            if !blocked_issue.blocked_by.contains(&blocker) {
+                blocked_issue.blocked_by.push(blocker);
+                blocked_issue.updated_at = now;
+            }
+        }
+        for (id, other) in related_mirrors {
+            let issue = self.issues.get_mut(&id).expect("kept IDs exist");
+            if !issue.related.contains(&other) {
+                issue.related.push(other);
+                issue.updated_at = now;
+            }
+        }
+    }
+
+    pub fn generate_id(&self, title: &str, timestamp: &DateTime<Utc>) -> String {
+        let input = format!("{}{}", title, timestamp.to_rfc3339());
+        let mut hasher = Sha256::new();
+        hasher.update(input.as_bytes());
+        let hash = hasher.finalize();
+
+        // Try sliding window: bytes 0-3, then 1-4, then 2-5, etc.
+        // SHA256 gives 32 bytes, so we can slide up to 28 times
+        for offset in 0..28 {
+            let suffix: String = hash[offset..offset + 4]
+                .iter()
+                .map(|b| {
+                    let idx = (b % 36) as usize;
+                    if idx < 10 {
+                        (b'0' + idx as u8) as char
+                    } else {
+                        (b'a' + (idx - 10) as u8) as char
+                    }
+                })
+                .collect();
+
+            let id = format!("{}-{}", self.config.prefix, suffix);
+            if !self.issues.contains_key(&id) {
+                return id;
+            }
+        }
+
+        // Extremely unlikely fallback: append counter
+        let mut counter = 0u32;
+        loop {
+            let id = format!("{}-{:04x}", self.config.prefix, counter);
+            if !self.issues.contains_key(&id) {
+                return id;
+            }
+            counter += 1;
+        }
+    }
+}
+
+/// Where `ba template edit` keeps the description template for `issue_type`.
+pub fn template_path(ba_dir: &Path, issue_type: &IssueType) -> PathBuf {
+    ba_dir
+        .join(TEMPLATES_DIR)
+        .join(format!("{}.md", issue_type))
+}
+
+/// Write issues in the native storage format (one compact issue per line,
+/// sorted by ID) via temp file + rename so readers never see a partial file.
+pub fn write_issues_atomic<'a>(
+    path: &Path,
+    issues: impl IntoIterator<Item = &'a Issue>,
+) -> Result<(), BaError> {
+    Ok(write_atomic(path, &issues_jsonl(issues)?)?)
+}
+
+/// Issues as JSONL text, one per line.
+pub fn issues_jsonl<'a>(issues: impl IntoIterator<Item = &'a Issue>) -> Result<String, BaError> {
+    // Sort issues by ID for consistent output
+    let mut sorted: Vec<_> = issues.into_iter().collect();
+    sorted.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let mut content = String::new();
+    for issue in sorted {
+        let line = serde_json::to_string(issue)
+            .map_err(|e| format!("Failed to serialize issue: {}", e))?;
+        content.push_str(&line);
+        content.push('\n');
+    }
+
+    Ok(content)
+}
+
+/// Set once in `main`: `--dry-run`. Writes to the store, and to any file a
+/// command would produce, are skipped; output still shows the result.
+pub static DRY_RUN: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+pub fn dry_run() -> bool {
+    DRY_RUN.load(std::sync::atomic::Ordering::Relaxed)
+}
+
+/// Replace `path` with `content` via a temp file + rename, so readers see
+/// either the old file or the new one, never a partial write.
+fn write_atomic(path: &Path, content: &str) -> Result<(), String> {
+    if dry_run() {
+        return Ok(());
+    }
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| ISSUES_FILE.to_string());
+    let nonce = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("Failed to read system clock: {}", e))?
+        .as_nanos();
+    let tmp_path = dir.join(format!(
+        "{}.tmp.{}.{}",
+        file_name,
+        std::process::id(),
+        nonce
+    ));
+
+    let mut file =
+        File::create(&tmp_path).map_err(|e| format!("Failed to create temp file: {}", e))?;
+    file.write_all(content.as_bytes())
+        .map_err(|e| format!("Failed to write temp file: {}", e))?;
+
+    file.flush()
+        .map_err(|e| format!("Failed to flush temp file: {}", e))?;
+    file.sync_all()
+        .map_err(|e| format!("Failed to sync temp file: {}", e))?;
+
+    fs::rename(&tmp_path, path).map_err(|e| format!("Failed to rename temp file: {}", e))?;
+
+    Ok(())
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Query (filter expressions for bulk operations)
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CmpOp {
+    pub fn compare<T: PartialOrd>(self, left: T, right: T) -> bool {
+        match self {
+            CmpOp::Eq => left == right,
+            CmpOp::Ne => left != right,
+            CmpOp::Lt => left < right,
+            CmpOp::Le => left <= right,
+            CmpOp::Gt => left > right,
+            CmpOp::Ge => left >= right,
+        }
+    }
+}
+
+/// A single `field <op> value` test. Values are validated at parse time so a
+/// typo'd status or type is an error rather than a silent empty match.
+#[derive(Debug, Clone)]
+pub enum Condition {
+    Status(CmpOp, Status),
+    Type(CmpOp, String),
+    Priority(CmpOp, u8),
+    Label(CmpOp, String),
+    Session(CmpOp, String),
+    Title(CmpOp, String),
+    Id(CmpOp, String),
+}
+
+impl Condition {
+    pub fn parse(field: &str, op: CmpOp, value: &str) -> Result<Self, String> {
+        let equality_only = |name: &str| -> Result<(), String> {
+            if matches!(op, CmpOp::Eq | CmpOp::Ne) {
+                Ok(())
+            } else {
+                Err(format!("Field '{}' only supports ':', '=' and '!='", name))
+            }
+        };
+
+        match field.to_lowercase().as_str() {
+            "status" => {
+                equality_only("status")?;
+                Ok(Condition::Status(op, value.parse()?))
+            }
+            "type" => {
+                equality_only("type")?;
+                let issue_type: IssueType = value.parse()?;
+                Ok(Condition::Type(op, issue_type.to_string()))
+            }
+            "priority" => {
+                let priority: u8 = value
+                    .parse()
+                    .map_err(|_| format!("Invalid priority in query: {}", value))?;
+                if priority > 4 {
+                    return Err("Priority must be 0-4".to_string());
+                }
+                Ok(Condition::Priority(op, priority))
+            }
+            "label" => {
+                equality_only("label")?;
+                Ok(Condition::Label(op, value.to_string()))
+            }
+            "session" => {
+                equality_only("session")?;
+                Ok(Condition::Session(op, value.to_string()))
+            }
+            "title" => {
+                equality_only("title")?;
+                Ok(Condition::Title(op, value.to_lowercase()))
+            }
+            "id" => {
+                equality_only("id")?;
+                Ok(Condition::Id(op, value.to_string()))
+            }
+            _ => Err(format!(
+                "Unknown query field: {} (valid: status, type, priority, label, session, title, id)",
+                field
+            )),
+        }
+    }
+
+    pub fn matches(&self, issue: &Issue) -> bool {
+        match self {
+            Condition::Status(op, status) => op.compare(&issue.status, status),
+            Condition::Type(op, issue_type) => {
+                op.compare(&issue.issue_type.to_string(), issue_type)
+            }
+            Condition::Priority(op, priority) => op.compare(issue.priority, *priority),
+            Condition::Label(op, label) => op.compare(issue.labels.contains(label), true),
+            Condition::Session(op, session) => {
+                op.compare(issue.session_id.as_deref(), Some(session.as_str()))
+            }
+            Condition::Title(op, needle) => {
+                op.compare(issue.title.to_lowercase().contains(needle.as_str()), true)
+            }
+            Condition::Id(op, id) => op.compare(&issue.id, id),
+        }
+    }
+}
+
+/// Boolean combination of conditions, e.g.
+/// `status:open AND (label:backend OR NOT priority>1)`. NOT binds tightest,
+/// then AND, then OR; parentheses group.
+#[derive(Debug, Clone)]
+pub enum Query {
+    Condition(Condition),
+    Not(Box<Query>),
+    All(Vec<Query>),
+    Any(Vec<Query>),
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let tokens = tokenize_query(input)?;
+        if tokens.is_empty() {
+            return Err("Empty query".to_string());
+        }
+        let mut parser = QueryParser {
+            tokens: tokens.into_iter().peekable(),
+        };
+        let query = parser.any()?;
+        match parser.tokens.next() {
+            None => Ok(query),
+            Some(Token::Close) => Err("Unmatched ')' in query".to_string()),
+            Some(other) => Err(format!("Expected AND or OR, found '{}'", other)),
+        }
+    }
+
+    pub fn matches(&self, issue: &Issue) -> bool {
+        match self {
+            Query::Condition(condition) => condition.matches(issue),
+            Query::Not(query) => !query.matches(issue),
+            Query::All(queries) => queries.iter().all(|q| q.matches(issue)),
+            Query::Any(queries) => queries.iter().any(|q| q.matches(issue)),
+        }
+    }
+}
+
+/// Recursive-descent parser over query tokens, one method per precedence level.
+struct QueryParser {
+    tokens: std::iter::Peekable<std::vec::IntoIter<Token>>,
+}
+
+impl QueryParser {
+    /// Consume `keyword` if it's next, erroring when nothing follows it.
+    fn keyword(&mut self, keyword: &str) -> Result<bool, String> {
+        match self.tokens.peek() {
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword) => {
+                self.tokens.next();
+                if self.tokens.peek().is_none() {
+                    return Err(format!(
+                        "Query ends with a dangling {}",
+                        keyword.to_uppercase()
+                    ));
+                }
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn any(&mut self) -> Result<Query, String> {
+        let mut terms = vec![self.all()?];
+        while self.keyword("or")? {
+            terms.push(self.all()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.pop().unwrap()
+        } else {
+            Query::Any(terms)
+        })
+    }
+
+    fn all(&mut self) -> Result<Query, String> {
+        let mut terms = vec![self.unary()?];
+        while self.keyword("and")? {
+            terms.push(self.unary()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.pop().unwrap()
+        } else {
+            Query::All(terms)
+        })
+    }
+
+    fn unary(&mut self) -> Result<Query, String> {
+        if self.keyword("not")? {
+            return Ok(Query::Not(Box::new(self.unary()?)));
+        }
+        if self.tokens.peek() == Some(&Token::Open) {
+            self.tokens.next();
+            let query = self.any()?;
+            return match self.tokens.next() {
+                Some(Token::Close) => Ok(query),
+                Some(other) => Err(format!("Expected ')', found '{}'", other)),
+                None => Err("Missing ')' in query".to_string()),
+            };
+        }
+        self.condition().map(Query::Condition)
+    }
+
+    fn condition(&mut self) -> Result<Condition, String> {
+        let field = match self.tokens.next() {
+            Some(Token::Word(field)) => field,
+            Some(other) => return Err(format!("Expected a field name, found '{}'", other)),
+            None => return Err("Expected a condition".to_string()),
+        };
+        let op = match self.tokens.next() {
+            Some(Token::Op(op)) => match op.as_str() {
+                ":" | "=" | "==" => CmpOp::Eq,
+                "!=" => CmpOp::Ne,
+                "<" => CmpOp::Lt,
+                "<=" => CmpOp::Le,
+                ">" => CmpOp::Gt,
+                _ => CmpOp::Ge,
+            },
+            Some(other) => {
+                return Err(format!(
+                    "Expected operator after '{}', found '{}'",
+                    field, other
+                ));
+            }
+            None => return Err(format!("Expected operator after '{}'", field)),
+        };
+        let value = match self.tokens.next() {
+            Some(Token::Word(value) | Token::Quoted(value)) => value,
+            _ => return Err(format!("Expected value after '{}'", field)),
+        };
+        Condition::parse(&field, op, &value)
+    }
+}
+
+/// A lexical piece of a query. Quoted strings are kept apart from bare words
+/// so `label:"or"` is a value, never a keyword.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(String),
+    Open,
+    Close,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Word(s) | Token::Quoted(s) | Token::Op(s) => write!(f, "{}", s),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+        }
+    }
+}
+
+/// Split a query into words, operators, parentheses, and quoted strings.
+fn tokenize_query(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' || c == ')' {
+            chars.next();
+            tokens.push(if c == '(' { Token::Open } else { Token::Close });
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some(ch) if ch == c => break,
+                    Some(ch) => value.push(ch),
+                    None => return Err("Unterminated quote in query".to_string()),
+                }
+            }
+            tokens.push(Token::Quoted(value));
+        } else if matches!(c, ':' | '=' | '!' | '<' | '>') {
+            chars.next();
+            let mut op = c.to_string();
+            if chars.peek() == Some(&'=') && c != ':' {
+                chars.next();
+                op.push('=');
+            }
+            if op == "!" {
+                return Err("Expected '!=' in query".to_string());
+            }
+            tokens.push(Token::Op(op));
+        } else {
+            let mut word = String::new();
+            while let Some(&ch) = chars.peek() {
+                if ch.is_whitespace()
+                    || matches!(ch, ':' | '=' | '!' | '<' | '>' | '"' | '\'' | '(' | ')')
+                {
+                    break;
+                }
+                word.push(ch);
+                chars.next();
+            }
+            tokens.push(Token::Word(word));
+        }
+    }
+
+    Ok(tokens)
+}
@@ -3,462 +3,20 @@
 //! A spiritual fork of beads (bd), keeping the simplicity of v0.9.6
 //! with added session-based claiming for multi-agent coordination.
 
-use chrono::{DateTime, Utc};
-use clap::{Parser, Subcommand};
-use fs2::FileExt;
+// The issue schema's json! literal outgrows the default macro recursion limit
+#![recursion_limit = "256"]
+
+use ba::*;
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, Utc};
+use clap::builder::NonEmptyStringValueParser;
+use clap::parser::ValueSource;
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs::{self, File, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
+use std::io::{BufRead, BufReader, IsTerminal, Write};
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
-
-const ISSUES_FILE: &str = "issues.jsonl";
-const CONFIG_FILE: &str = "config.json";
-const LOCK_FILE: &str = "issues.lock";
-
-// ─────────────────────────────────────────────────────────────────────────────
-// Data Types
-// ─────────────────────────────────────────────────────────────────────────────
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-#[serde(rename_all = "snake_case")]
-enum Status {
-    Open,
-    InProgress,
-    Closed,
-}
-
-impl std::fmt::Display for Status {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Status::Open => write!(f, "open"),
-            Status::InProgress => write!(f, "in_progress"),
-            Status::Closed => write!(f, "closed"),
-        }
-    }
-}
-
-// AIDEV-NOTE: Issue types are minimal by design. Only types that signal
-// different work patterns exist - priority handles urgency, title describes
-// the work. Types:
-// - task: default, general work
-// - epic: container for grouping related issues
-// - refactor: improving existing code (no new behavior)
-// - spike: research/investigation (may not produce code)
-// Legacy types (bug, feature, chore) deserialize to Task for backwards compat.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-enum IssueType {
-    Epic,
-    Refactor,
-    Spike,
-    #[serde(other)]
-    Task,
-}
-
-impl std::fmt::Display for IssueType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            IssueType::Task => write!(f, "task"),
-            IssueType::Epic => write!(f, "epic"),
-            IssueType::Refactor => write!(f, "refactor"),
-            IssueType::Spike => write!(f, "spike"),
-        }
-    }
-}
-
-impl std::str::FromStr for IssueType {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "task" => Ok(IssueType::Task),
-            "epic" => Ok(IssueType::Epic),
-            "refactor" => Ok(IssueType::Refactor),
-            "spike" => Ok(IssueType::Spike),
-            _ => Err(format!(
-                "Unknown issue type: {} (valid: task, epic, refactor, spike)",
-                s
-            )),
-        }
-    }
-}
-
-// ─────────────────────────────────────────────────────────────────────────────
-// State Machine
-// ─────────────────────────────────────────────────────────────────────────────
-
-/// Transitions that can be applied to an issue.
-/// Status is a side-effect of ownership transitions, not set directly.
-#[derive(Debug, Clone)]
-enum Transition {
-    /// Take ownership: (Open|Closed) → InProgress
-    Claim { session: String },
-    /// Abandon work: InProgress → Open
-    Release,
-    /// Complete work: InProgress → Closed
-    Finish,
-    /// Close unclaimed issue: Open → Closed (escape hatch)
-    Close,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Comment {
-    author: String,
-    text: String,
-    created_at: DateTime<Utc>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Issue {
-    id: String,
-    title: String,
-    #[serde(default)]
-    description: String,
-    status: Status,
-    #[serde(default = "default_priority")]
-    priority: u8,
-    issue_type: IssueType,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    session_id: Option<String>,
-    #[serde(default)]
-    labels: Vec<String>,
-    #[serde(default)]
-    comments: Vec<Comment>,
-    created_at: DateTime<Utc>,
-    updated_at: DateTime<Utc>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    closed_at: Option<DateTime<Utc>>,
-    #[serde(default)]
-    blocks: Vec<String>,
-    #[serde(default)]
-    blocked_by: Vec<String>,
-}
-
-impl Issue {
-    /// Apply a state transition to this issue.
-    /// Returns the previous session_id if relevant (for release/finish messages).
-    fn apply(&mut self, transition: Transition) -> Result<Option<String>, String> {
-        let now = Utc::now();
-
-        match (&self.status, &self.session_id, transition) {
-            // Claim: Open + unclaimed → InProgress
-            (Status::Open, None, Transition::Claim { session }) => {
-                self.session_id = Some(session);
-                self.status = Status::InProgress;
-                self.updated_at = now;
-                Ok(None)
-            }
-
-            // Claim: Open + already claimed by same session
-            (Status::Open, Some(existing), Transition::Claim { session })
-                if existing == &session =>
-            {
-                Err(format!("{} already claimed by this session", self.id))
-            }
-
-            // Claim: Open + already claimed by different session
-            (Status::Open, Some(existing), Transition::Claim { .. }) => Err(format!(
-                "{} already claimed by session {}",
-                self.id, existing
-            )),
-
-            // Claim: Closed → InProgress (reopen)
-            (Status::Closed, _, Transition::Claim { session }) => {
-                self.session_id = Some(session);
-                self.status = Status::InProgress;
-                self.closed_at = None;
-                self.updated_at = now;
-                Ok(None)
-            }
-
-            // Claim: InProgress + already claimed
-            (Status::InProgress, Some(existing), Transition::Claim { session })
-                if existing == &session =>
-            {
-                Err(format!("{} already claimed by this session", self.id))
-            }
-
-            (Status::InProgress, Some(existing), Transition::Claim { .. }) => Err(format!(
-                "{} already claimed by session {}",
-                self.id, existing
-            )),
-
-            // Release: InProgress + claimed → Open
-            (Status::InProgress, Some(_), Transition::Release) => {
-                let old_session = self.session_id.take();
-                self.status = Status::Open;
-                self.updated_at = now;
-                Ok(old_session)
-            }
-
-            // Release: not claimed
-            (_, None, Transition::Release) => Err(format!("{} is not claimed", self.id)),
-
-            // Release: not in progress (but claimed somehow - shouldn't happen)
-            (_, Some(_), Transition::Release) => Err(format!("{} is not in progress", self.id)),
-
-            // Finish: InProgress + claimed → Closed
-            (Status::InProgress, Some(_), Transition::Finish) => {
-                let old_session = self.session_id.take();
-                self.status = Status::Closed;
-                self.closed_at = Some(now);
-                self.updated_at = now;
-                Ok(old_session)
-            }
-
-            // Finish: not claimed
-            (_, None, Transition::Finish) => Err(format!(
-                "{} is not claimed. Use 'close' for unclaimed issues.",
-                self.id
-            )),
-
-            // Finish: already closed
-            (Status::Closed, _, Transition::Finish) => {
-                Err(format!("{} is already closed", self.id))
-            }
-
-            // Finish: open but not claimed (shouldn't have session)
-            (Status::Open, Some(_), Transition::Finish) => {
-                Err(format!("{} is open, not in progress", self.id))
-            }
-
-            // Close: Open + unclaimed → Closed (escape hatch)
-            (Status::Open, None, Transition::Close) => {
-                self.status = Status::Closed;
-                self.closed_at = Some(now);
-                self.updated_at = now;
-                Ok(None)
-            }
-
-            // Close: already closed
-            (Status::Closed, _, Transition::Close) => Err(format!("{} is already closed", self.id)),
-
-            // Close: claimed - must release first or use finish
-            (_, Some(session), Transition::Close) => Err(format!(
-                "{} is claimed by session {}. Use 'release' first, or 'finish' to complete.",
-                self.id, session
-            )),
-
-            // Invalid states (InProgress without session shouldn't exist)
-            (Status::InProgress, None, Transition::Claim { session }) => {
-                // Treat as claimable - fix the inconsistent state
-                self.session_id = Some(session);
-                self.updated_at = now;
-                Ok(None)
-            }
-
-            (Status::InProgress, None, Transition::Close) => {
-                // InProgress but no owner - treat as closeable
-                self.status = Status::Closed;
-                self.closed_at = Some(now);
-                self.updated_at = now;
-                Ok(None)
-            }
-        }
-    }
-}
-
-fn default_priority() -> u8 {
-    2
-}
-
-// Beads import types - using Value for flexible parsing with clear errors
-#[derive(Debug, Deserialize)]
-struct BeadsDependency {
-    issue_id: String,
-    depends_on_id: String,
-    #[serde(rename = "type")]
-    dep_type: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct BeadsIssue {
-    id: String,
-    title: String,
-    #[serde(default)]
-    description: String,
-    status: String,
-    #[serde(default = "default_priority")]
-    priority: u8,
-    issue_type: String,
-    created_at: String,
-    updated_at: String,
-    #[serde(default)]
-    closed_at: Option<String>,
-    #[serde(default)]
-    dependencies: Vec<BeadsDependency>,
-}
-
-#[derive(Debug)]
-struct ImportError {
-    line_num: usize,
-    issue_id: Option<String>,
-    field: String,
-    message: String,
-}
-
-impl std::fmt::Display for ImportError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match &self.issue_id {
-            Some(id) => write!(
-                f,
-                "Line {}: Issue '{}' - {}: {}",
-                self.line_num, id, self.field, self.message
-            ),
-            None => write!(
-                f,
-                "Line {}: {}: {}",
-                self.line_num, self.field, self.message
-            ),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Config {
-    version: u8,
-    prefix: String,
-}
-
-// ─────────────────────────────────────────────────────────────────────────────
-// Store (in-memory + file operations)
-// ─────────────────────────────────────────────────────────────────────────────
-
-struct Store {
-    config: Config,
-    issues: HashMap<String, Issue>,
-    ba_dir: PathBuf,
-    _lock_file: File,
-}
-
-impl Store {
-    fn load(ba_dir: &Path) -> Result<Self, String> {
-        let lock_path = ba_dir.join(LOCK_FILE);
-        let lock_file = OpenOptions::new()
-            .create(true)
-            .read(true)
-            .write(true)
-            .open(&lock_path)
-            .map_err(|e| format!("Failed to open lock file '{}': {}", lock_path.display(), e))?;
-        lock_file.lock_exclusive().map_err(|e| {
-            format!(
-                "Failed to acquire store lock '{}': {}",
-                lock_path.display(),
-                e
-            )
-        })?;
-
-        let config_path = ba_dir.join(CONFIG_FILE);
-        let config: Config = if config_path.exists() {
-            let content = fs::read_to_string(&config_path)
-                .map_err(|e| format!("Failed to read config: {}", e))?;
-            serde_json::from_str(&content).map_err(|e| format!("Failed to parse config: {}", e))?
-        } else {
-            return Err("Not initialized. Run 'ba init' first.".to_string());
-        };
-
-        let issues_path = ba_dir.join(ISSUES_FILE);
-        let mut issues = HashMap::new();
-        if issues_path.exists() {
-            let file = File::open(&issues_path)
-                .map_err(|e| format!("Failed to open issues file: {}", e))?;
-            let reader = BufReader::new(file);
-            for (index, line) in reader.lines().enumerate() {
-                let line_num = index + 1;
-                let line =
-                    line.map_err(|e| format!("Failed to read issues line {}: {}", line_num, e))?;
-                if line.trim().is_empty() {
-                    continue;
-                }
-                let issue: Issue = serde_json::from_str(&line)
-                    .map_err(|e| format!("Failed to parse issue at line {}: {}", line_num, e))?;
-                issues.insert(issue.id.clone(), issue);
-            }
-        }
-
-        Ok(Store {
-            config,
-            issues,
-            ba_dir: ba_dir.to_path_buf(),
-            _lock_file: lock_file,
-        })
-    }
-
-    fn save(&self) -> Result<(), String> {
-        // Sort issues by ID for consistent output
-        let mut sorted: Vec<_> = self.issues.values().collect();
-        sorted.sort_by(|a, b| a.id.cmp(&b.id));
-
-        let issues_path = self.ba_dir.join(ISSUES_FILE);
-        let nonce = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map_err(|e| format!("Failed to read system clock: {}", e))?
-            .as_nanos();
-        let tmp_path =
-            self.ba_dir
-                .join(format!("issues.jsonl.tmp.{}.{}", std::process::id(), nonce));
-
-        let mut file =
-            File::create(&tmp_path).map_err(|e| format!("Failed to create temp file: {}", e))?;
-
-        for issue in sorted {
-            let line = serde_json::to_string(issue)
-                .map_err(|e| format!("Failed to serialize issue: {}", e))?;
-            writeln!(file, "{}", line).map_err(|e| format!("Failed to write issue: {}", e))?;
-        }
-
-        file.flush()
-            .map_err(|e| format!("Failed to flush temp file: {}", e))?;
-        file.sync_all()
-            .map_err(|e| format!("Failed to sync temp file: {}", e))?;
-
-        fs::rename(&tmp_path, &issues_path)
-            .map_err(|e| format!("Failed to rename temp file: {}", e))?;
-
-        Ok(())
-    }
-
-    fn generate_id(&self, title: &str, timestamp: &DateTime<Utc>) -> String {
-        let input = format!("{}{}", title, timestamp.to_rfc3339());
-        let mut hasher = Sha256::new();
-        hasher.update(input.as_bytes());
-        let hash = hasher.finalize();
-
-        // Try sliding window: bytes 0-3, then 1-4, then 2-5, etc.
-        // SHA256 gives 32 bytes, so we can slide up to 28 times
-        for offset in 0..28 {
-            let suffix: String = hash[offset..offset + 4]
-                .iter()
-                .map(|b| {
-                    let idx = (b % 36) as usize;
-                    if idx < 10 {
-                        (b'0' + idx as u8) as char
-                    } else {
-                        (b'a' + (idx - 10) as u8) as char
-                    }
-                })
-                .collect();
-
-            let id = format!("{}-{}", self.config.prefix, suffix);
-            if !self.issues.contains_key(&id) {
-                return id;
-            }
-        }
-
-        // Extremely unlikely fallback: append counter
-        let mut counter = 0u32;
-        loop {
-            let id = format!("{}-{:04x}", self.config.prefix, counter);
-            if !self.issues.contains_key(&id) {
-                return id;
-            }
-            counter += 1;
-        }
-    }
-}
-
 // ─────────────────────────────────────────────────────────────────────────────
 // CLI
 // ─────────────────────────────────────────────────────────────────────────────
@@ -468,7 +26,7 @@ impl Store {
 #[command(about = "Simple task tracking for LLM sessions")]
 #[command(version)]
 struct Cli {
-    /// Data directory (default: .ba/)
+    /// Data directory (default: nearest .ba/ in this or a parent directory)
     #[arg(long, default_value = ".ba")]
     dir: PathBuf,
 
@@ -476,6 +34,22 @@ struct Cli {
     #[arg(long)]
     json: bool,
 
+    /// Fail on any corrupt line in issues.jsonl instead of skipping it
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// Plain output even on a terminal (also honors NO_COLOR)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Suppress success messages from mutating commands (errors and --json still print)
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Run the command and print the result, but write nothing to disk
+    #[arg(long, global = true)]
+    dry_run: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -489,36 +63,117 @@ enum Commands {
     #[command(visible_alias = "add", visible_alias = "new")]
     Create {
         /// Issue title
-        title: String,
+        #[arg(required_unless_present = "batch", conflicts_with = "batch")]
+        title: Option<String>,
 
-        /// Issue type (bug, feature, task, epic, chore, refactor, spike)
-        #[arg(short = 't', long, default_value = "task")]
-        issue_type: String,
+        /// Read one issue per stdin line: a plain title, or a JSON object
+        /// with title/type/priority/description (flags supply the defaults)
+        #[arg(long)]
+        batch: bool,
+
+        /// Issue type (bug, feature, task, epic, chore, refactor, spike);
+        /// defaults to the project's default-type (task)
+        #[arg(short = 't', long)]
+        issue_type: Option<String>,
+
+        /// Priority (0-4, 0 = highest); defaults to the project's
+        /// default-priority (2)
+        #[arg(short, long)]
+        priority: Option<u8>,
 
-        /// Priority (0-4, 0 = highest)
-        #[arg(short, long, default_value = "2")]
-        priority: u8,
+        /// Description; defaults to .ba/templates/<type>.md if it exists
+        #[arg(short, long)]
+        description: Option<String>,
 
-        /// Description
-        #[arg(short, long, default_value = "")]
-        description: String,
+        /// Read the description from a file (`-` for stdin)
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["description", "batch"])]
+        description_file: Option<PathBuf>,
+
+        #[command(flatten)]
+        links: CreateLinks,
+    },
+
+    /// Create a new open issue with the type, priority, labels and
+    /// description of an existing one
+    Clone {
+        /// Issue ID to copy
+        id: String,
+        /// Title for the copy; defaults to "Copy of <title>"
+        #[arg(long)]
+        title: Option<String>,
     },
 
     /// List issues
     List {
-        /// Filter by status (open, in_progress, closed)
-        #[arg(long)]
-        status: Option<String>,
+        #[command(flatten)]
+        filter: ListFilter,
+
+        #[command(flatten)]
+        view: ListView,
+    },
+
+    /// Print how many issues match the list filters
+    Count {
+        #[command(flatten)]
+        filter: ListFilter,
+    },
+
+    /// Search titles, descriptions and comments (case-insensitive)
+    Search {
+        /// Text to look for
+        query: String,
 
         /// Include closed issues
         #[arg(long)]
         all: bool,
+
+        /// Include archived issues (implies --all)
+        #[arg(long)]
+        archived: bool,
+    },
+
+    /// List issues matching a filter expression, e.g.
+    /// "status:open AND (label:backend OR priority<=1) AND NOT type:spike"
+    Query {
+        /// Conditions on status, type, priority, label, session, title, id,
+        /// combined with AND, OR, NOT and parentheses
+        expr: String,
+
+        /// Also match archived issues
+        #[arg(long)]
+        archived: bool,
+
+        #[command(flatten)]
+        view: ListView,
+    },
+
+    /// Show the most recently updated issues, whatever their status
+    Recent {
+        /// How many issues to show
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
     },
 
     /// Show issue details
     Show {
         /// Issue ID
         id: String,
+        /// Print only the comment thread (just the `comments` array with --json)
+        #[arg(long)]
+        comments_only: bool,
+        /// Also print the issue's web link (needs web-base-url in config)
+        #[arg(long, conflicts_with = "comments_only")]
+        web: bool,
+        /// One line: id, status, priority, type, title and labels (--json
+        /// prints the full issue as usual)
+        #[arg(long, conflicts_with_all = ["comments_only", "web"])]
+        oneline: bool,
+    },
+
+    /// Print the web viewer URL of an issue (needs web-base-url in config)
+    Link {
+        /// Issue ID
+        id: String,
     },
 
     /// Close an issue
@@ -526,29 +181,59 @@ enum Commands {
         /// Issue ID
         id: String,
 
-        /// Reason for closing
+        /// Reason for closing (free text, e.g. wontfix or duplicate), kept on
+        /// the issue
         #[arg(long)]
         reason: Option<String>,
+
+        /// Also close blockers (recursively) that no other open issue waits on
+        #[arg(long)]
+        cascade: bool,
+    },
+
+    /// Close every unclaimed issue matching the list filters
+    BulkClose {
+        #[command(flatten)]
+        filter: ListFilter,
     },
 
-    /// Delete issues by label selector
+    /// Permanently delete an issue, or issues by label selector
     Delete {
+        /// Issue ID (alternative to --label/--label-prefix)
+        id: Option<String>,
         /// Exact label match (e.g. factory:bloodwork:skeleton)
         #[arg(long)]
         label: Option<String>,
         /// Label prefix match (e.g. factory:bloodwork:)
         #[arg(long = "label-prefix")]
         label_prefix: Option<String>,
-        /// Also delete open/in_progress issues (default: only closed)
+        /// By id: delete even if claimed. By label: also delete open/in_progress issues
         #[arg(long)]
         force: bool,
     },
+
+    /// Delete closed issues closed before a cutoff, saving them to .ba/archive.jsonl
+    Purge {
+        /// Cutoff: RFC 3339, YYYY-MM-DD, or an age like 90d, 12w
+        #[arg(long)]
+        before: String,
+    },
+
     /// Add a blocking dependency (blocker blocks id)
     Block {
         /// Issue that is blocked
         id: String,
         /// Issue that blocks it
         blocker: String,
+        /// Dependency type (blocks, discovered-from, parent-child)
+        #[arg(short = 't', long = "type", default_value = "blocks")]
+        dep_type: String,
+        /// Advisory edge: shown in show/tree but doesn't gate ready
+        #[arg(long)]
+        soft: bool,
+        /// Add the edge even if it closes a dependency cycle
+        #[arg(long)]
+        force: bool,
     },
 
     /// Remove a blocking dependency
@@ -559,1221 +244,5612 @@ enum Commands {
         blocker: String,
     },
 
+    /// Link two issues as related (non-blocking, both directions)
+    Relate {
+        /// Issue ID
+        id: String,
+        /// Issue to link it with
+        other: String,
+    },
+
+    /// Remove a related link
+    Unrelate {
+        /// Issue ID
+        id: String,
+        /// Issue it was linked with
+        other: String,
+    },
+
+    /// Add or remove any link between two issues: blocking, related or
+    /// parent (block, unblock, relate, unrelate and parent are shorthands)
+    Deps {
+        /// Issue ID
+        id: String,
+        #[command(subcommand)]
+        action: DepsAction,
+    },
+
     /// Show dependency tree
     Tree {
         /// Root issue ID
         id: String,
+        /// Hide closed blockers
+        #[arg(long)]
+        open_only: bool,
+        /// Walk downstream: what this issue blocks, recursively
+        #[arg(long)]
+        blocks: bool,
+        /// Stop after this many levels below the root
+        #[arg(long)]
+        depth: Option<usize>,
+        /// Only descend into issues with these statuses; repeat or comma-separate
+        #[arg(long, value_delimiter = ',')]
+        status: Vec<String>,
+        /// Add each issue's priority and the session holding it
+        #[arg(short, long)]
+        verbose: bool,
+    },
+
+    /// Put an issue inside an epic
+    Parent {
+        /// Child issue ID
+        id: String,
+        /// Epic ID
+        epic: String,
+    },
+
+    /// List the issues inside an epic
+    Children {
+        /// Epic ID
+        epic: String,
+    },
+
+    /// Show how much of an epic is done
+    Progress {
+        /// Epic ID
+        epic: String,
     },
 
     /// Detect circular dependencies
-    Cycles,
+    Cycles {
+        /// Ignore closed issues (cycles among closed issues are history)
+        #[arg(long)]
+        open_only: bool,
+        /// Remove one edge per cycle (the least recently touched) until none remain
+        #[arg(long = "break")]
+        break_cycles: bool,
+    },
+
+    /// Run subcommands from stdin, one per line, saving once at the end;
+    /// if any line fails, nothing is saved
+    Batch,
 
     /// Show issues ready to work on (open, not blocked)
-    Ready,
+    Ready {
+        /// Also require every indirect blocker upstream to be closed
+        #[arg(long)]
+        transitive: bool,
 
-    /// Claim an issue for a session
-    Claim {
-        /// Issue ID
-        id: String,
-        /// Session ID (caller provides their own)
+        /// Only issues of this type (task, epic, refactor, spike)
+        #[arg(long = "type")]
+        issue_type: Option<String>,
+
+        /// Only issues with this label
         #[arg(long)]
-        session: String,
+        label: Option<String>,
+
+        /// Only estimated issues at or above this estimate (chunky work)
+        #[arg(long)]
+        min_estimate: Option<u32>,
+
+        /// Only estimated issues at or below this estimate (quick wins)
+        #[arg(long)]
+        max_estimate: Option<u32>,
+
+        /// Add the session or assignee already eyeing each issue
+        #[arg(short, long)]
+        verbose: bool,
+
+        #[command(flatten)]
+        page: Page,
     },
 
-    /// Release a claimed issue (back to open)
-    Release {
-        /// Issue ID
-        id: String,
+    /// Show unfinished issues waiting on open blockers, and which ones
+    Blocked,
+
+    /// Keep the ready list on screen, redrawing when issues change
+    Watch {
+        /// Poll interval in milliseconds
+        #[arg(long, default_value_t = 1000)]
+        interval: u64,
     },
 
-    /// Finish a claimed issue (release + close)
-    Finish {
-        /// Issue ID
-        id: String,
+    /// Show the single top ready issue (optionally claiming it)
+    Next {
+        /// Session ID to claim for
+        #[arg(long, env = "BA_SESSION", value_parser = NonEmptyStringValueParser::new())]
+        session: Option<String>,
+        /// Claim the issue for --session in the same step
+        #[arg(long, requires = "session")]
+        claim: bool,
     },
 
-    /// Show issues claimed by a session
-    Mine {
-        /// Session ID
+    /// Summarize the backlog: counts, blocked/ready totals, oldest open issue
+    Stats {
+        /// Instead, group unfinished issues by time since their last update
         #[arg(long)]
-        session: String,
+        aging: bool,
     },
 
-    /// Add or remove a label
-    Label {
-        /// Issue ID
-        id: String,
-        /// Action: add or remove
-        action: String,
-        /// Label name
-        label: String,
+    /// Show the audit log of changes
+    Log {
+        /// Only entries for this issue
+        #[arg(long)]
+        id: Option<String>,
     },
 
-    /// Set priority of an issue
-    Priority {
+    /// Show when an issue's status and priority changed, from git history
+    History {
         /// Issue ID
         id: String,
-        /// New priority (0-4, 0 = highest)
-        value: u8,
     },
 
-    /// Add a comment to an issue
-    Comment {
-        /// Issue ID
-        id: String,
-        /// Comment text
-        text: String,
-        /// Author name
-        #[arg(long, default_value = "anonymous")]
-        author: String,
+    /// Field-level changes since the issues file was last committed (git HEAD)
+    Diff {
+        /// Only this issue (default: every changed issue)
+        id: Option<String>,
     },
 
-    /// Import issues from beads (bd) export
-    Import {
-        /// Input file (beads JSONL export)
-        file: PathBuf,
-        /// Keep original IDs (default: generate new with ba prefix)
-        #[arg(long)]
-        keep_ids: bool,
+    /// Manage description templates in .ba/templates/<type>.md
+    Template {
+        #[command(subcommand)]
+        action: TemplateAction,
     },
 
-    /// Quick start guide for LLMs
-    Quickstart,
-}
+    /// Show or change project settings in .ba/config.json
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Change the project ID prefix, renaming existing issues to match
+    Reprefix {
+        /// New prefix (1-4 lowercase letters or digits)
+        prefix: String,
+    },
+
+    /// Move an issue into another .ba store
+    Move {
+        /// Issue ID
+        id: String,
+        /// Destination .ba directory (or the project root containing it)
+        #[arg(long)]
+        to: PathBuf,
+        /// Keep the ID instead of minting one under the destination prefix
+        #[arg(long)]
+        keep_id: bool,
+        /// Also move every issue linked through blocks/blocked_by
+        #[arg(long)]
+        with_deps: bool,
+    },
+
+    /// Reverse the status changes of the most recent command in the audit log
+    Undo,
+
+    /// Sum estimates of in-progress work per session
+    Load,
+
+    /// Start tracking time on an issue
+    Start {
+        /// Issue ID
+        id: String,
+    },
+
+    /// Stop the running timer on an issue
+    Stop {
+        /// Issue ID
+        id: String,
+    },
+
+    /// Claim an issue for a session
+    Claim {
+        /// Issue ID
+        id: String,
+        /// Session ID (caller provides their own)
+        #[arg(long, env = "BA_SESSION", value_parser = NonEmptyStringValueParser::new())]
+        session: String,
+        /// Lease in minutes; once it lapses `reap` releases the issue
+        #[arg(long)]
+        ttl: Option<u32>,
+    },
+
+    /// Release in-progress issues whose claim lease has expired
+    Reap,
+
+    /// Claim an issue and show its full details
+    Work {
+        /// Issue ID
+        id: String,
+        /// Session ID
+        #[arg(long, env = "BA_SESSION", value_parser = NonEmptyStringValueParser::new())]
+        session: String,
+    },
+
+    /// Hand an in-progress issue from one session to another
+    Reassign {
+        /// Issue ID
+        id: String,
+        /// Session currently holding the claim
+        #[arg(long)]
+        from: String,
+        /// Session taking it over
+        #[arg(long)]
+        to: String,
+    },
+
+    /// Release a claimed issue (back to open)
+    Release {
+        /// Issue ID
+        id: String,
+    },
+
+    /// Close an issue as a duplicate of another, linking the two
+    Dup {
+        /// Issue ID of the duplicate
+        id: String,
+        /// Canonical issue it duplicates
+        #[arg(long)]
+        of: String,
+        /// Author of the linking comment (default: $BA_AUTHOR, then git user.name)
+        #[arg(long)]
+        author: Option<String>,
+    },
+
+    /// Fold a duplicate into the issue that stays, then delete the duplicate
+    MergeIssues {
+        /// Issue ID that stays
+        a: String,
+        /// Issue ID merged into it and deleted
+        b: String,
+        /// Author of the merge note (default: $BA_AUTHOR, then git user.name)
+        #[arg(long)]
+        author: Option<String>,
+    },
+
+    /// Reopen a closed issue without claiming it (back to open)
+    Reopen {
+        /// Issue ID
+        id: String,
+    },
+
+    /// Hide a closed issue from every listing, even --all
+    Archive {
+        /// Issue ID
+        id: String,
+    },
+
+    /// Bring an archived issue back into --all listings
+    Unarchive {
+        /// Issue ID
+        id: String,
+    },
+
+    /// Finish a claimed issue (release + close)
+    Finish {
+        /// Issue ID
+        id: String,
+        /// Reason for closing, kept on the issue
+        #[arg(long)]
+        reason: Option<String>,
+        /// List the issues this one blocked that are now ready
+        #[arg(long)]
+        show_unblocked: bool,
+    },
+
+    /// Show issues claimed by a session
+    Mine {
+        /// Session ID
+        #[arg(long, env = "BA_SESSION", value_parser = NonEmptyStringValueParser::new())]
+        session: String,
+
+        #[command(flatten)]
+        page: Page,
+    },
+
+    /// Summarize what a session holds and what to do next
+    Whoami {
+        /// Session ID
+        #[arg(long, env = "BA_SESSION", value_parser = NonEmptyStringValueParser::new())]
+        session: String,
+    },
+
+    /// Add or remove a label
+    Label {
+        /// <ID> add|remove <LABEL>, add|remove <LABEL> with --query, or
+        /// rename <OLD> <NEW> across every issue
+        #[arg(required = true, num_args = 2..=3, value_names = ["ID", "ACTION", "LABEL"])]
+        args: Vec<String>,
+        /// Apply to every issue matching a query (e.g. "status = closed")
+        #[arg(long)]
+        query: Option<String>,
+    },
+
+    /// List every label in use with how many issues carry it
+    Labels,
+
+    /// Set priority of an issue
+    Priority {
+        /// Issue ID
+        id: String,
+        /// New priority (0-4, 0 = highest)
+        value: u8,
+        /// Also raise every transitive open blocker to at least this priority
+        #[arg(long)]
+        bump_blockers: bool,
+    },
+
+    /// Raise the priority of stale open issues and lower it on ones idle even
+    /// longer (rules: `ba config set escalate-after|decay-after <days>`)
+    Groom,
+
+    /// Order an issue relative to another of the same priority
+    Rank {
+        /// Issue ID
+        id: String,
+        /// Place it just before this issue
+        #[arg(long, required_unless_present = "after", conflicts_with = "after")]
+        before: Option<String>,
+        /// Place it just after this issue
+        #[arg(long)]
+        after: Option<String>,
+    },
+
+    /// Set or clear an issue's due date
+    Due {
+        /// Issue ID
+        id: String,
+        /// Due date (YYYY-MM-DD, midnight UTC)
+        #[arg(required_unless_present = "clear")]
+        date: Option<String>,
+        /// Remove the due date
+        #[arg(long, conflicts_with = "date")]
+        clear: bool,
+    },
+
+    /// Set or clear the person responsible for an issue (not a claim)
+    Assign {
+        /// Issue ID
+        id: String,
+        /// Assignee name
+        #[arg(required_unless_present = "clear")]
+        name: Option<String>,
+        /// Remove the assignee
+        #[arg(long, conflicts_with = "name")]
+        clear: bool,
+    },
+
+    /// Set an issue's effort estimate
+    Estimate {
+        /// Issue ID
+        id: String,
+        /// Estimate (story points or hours)
+        value: u32,
+    },
+
+    /// Add a comment to an issue
+    Comment {
+        /// Issue ID
+        id: String,
+        /// Comment text (new text when used with --edit)
+        #[arg(required_unless_present = "delete")]
+        text: Option<String>,
+        /// Author name (default: $BA_AUTHOR, then git user.name)
+        #[arg(long)]
+        author: Option<String>,
+        /// Replace the text of the comment at this index (as shown by `ba show`)
+        #[arg(long, value_name = "INDEX", conflicts_with = "delete")]
+        edit: Option<usize>,
+        /// Remove the comment at this index (as shown by `ba show`)
+        #[arg(long, value_name = "INDEX", conflicts_with = "text")]
+        delete: Option<usize>,
+    },
+
+    /// Import issues from beads (bd) export
+    Import {
+        /// Input file (beads JSONL export, or ba's own issues.jsonl with --format native)
+        file: PathBuf,
+        /// Keep original IDs (default: generate new with ba prefix)
+        #[arg(long)]
+        keep_ids: bool,
+        /// Input format (beads, native)
+        #[arg(long, default_value = "beads")]
+        format: String,
+    },
+
+    /// Export issues
+    Export {
+        /// Output format (native-jsonl, markdown, csv)
+        #[arg(long, default_value = "native-jsonl")]
+        format: String,
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Include closed issues in report formats (native-jsonl always has everything)
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Checkpoint the store under a name (`ba snapshot list` shows checkpoints)
+    Snapshot {
+        /// Snapshot name, or `list`
+        name: String,
+        /// Overwrite an existing snapshot with the same name
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Replace all issues with a named snapshot
+    Restore {
+        /// Snapshot name
+        name: String,
+        /// Skip the confirmation prompt
+        #[arg(long)]
+        yes: bool,
+    },
+
+    /// Check issues.jsonl for broken references and inconsistent ownership
+    Doctor {
+        /// Repair what can be repaired safely, then save
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Replay the audit log's status changes and check them against issues.jsonl
+    Replay {
+        /// Audit log to replay (default: .ba/audit.jsonl)
+        log: Option<PathBuf>,
+    },
+
+    /// Three-way merge of issues.jsonl versions (git merge driver)
+    Merge {
+        /// Our version; the merged result is written here
+        ours: PathBuf,
+        /// Their version
+        theirs: PathBuf,
+        /// Common ancestor
+        base: PathBuf,
+    },
+
+    /// Print the JSON Schema of the issue format
+    Schema,
+
+    /// Print the .ba/ directory commands use from here
+    Root,
+
+    /// Quick start guide for LLMs
+    Quickstart,
+}
+
+impl Commands {
+    /// Commands that never save, so they can share the store lock.
+    fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            Commands::List { .. }
+                | Commands::Count { .. }
+                | Commands::Search { .. }
+                | Commands::Query { .. }
+                | Commands::Recent { .. }
+                | Commands::Blocked
+                | Commands::Show { .. }
+                | Commands::Link { .. }
+                | Commands::Tree { .. }
+                | Commands::Children { .. }
+                | Commands::Progress { .. }
+                | Commands::Cycles {
+                    break_cycles: false,
+                    ..
+                }
+                | Commands::Ready { .. }
+                | Commands::Next { claim: false, .. }
+                | Commands::Stats { .. }
+                | Commands::Load
+                | Commands::Log { .. }
+                | Commands::History { .. }
+                | Commands::Diff { .. }
+                | Commands::Labels
+                | Commands::Config {
+                    action: ConfigAction::List | ConfigAction::Get { .. }
+                }
+                | Commands::Doctor { fix: false }
+                | Commands::Replay { .. }
+                | Commands::Mine { .. }
+                | Commands::Whoami { .. }
+                | Commands::Export { .. }
+                | Commands::Root
+        )
+    }
+}
+
+#[derive(Subcommand)]
+enum TemplateAction {
+    /// Create the template for a type if needed and open it in $VISUAL/$EDITOR
+    Edit {
+        /// Issue type (task, epic, refactor, spike)
+        issue_type: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum DepsAction {
+    /// Add a link
+    Add {
+        #[command(flatten)]
+        target: DepsTarget,
+        /// Dependency type for blocking links (blocks, discovered-from, parent-child)
+        #[arg(short = 't', long = "type", default_value = "blocks", conflicts_with_all = ["related", "parent"])]
+        dep_type: String,
+        /// Advisory blocking link: shown in show/tree but doesn't gate ready
+        #[arg(long, conflicts_with_all = ["related", "parent"])]
+        soft: bool,
+        /// Add a blocking link even if it closes a dependency cycle
+        #[arg(long, conflicts_with_all = ["related", "parent"])]
+        force: bool,
+    },
+    /// Remove a link
+    Rm {
+        #[command(flatten)]
+        target: DepsTarget,
+    },
+}
+
+/// The other end of a `ba deps` link; exactly one is given.
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+struct DepsTarget {
+    /// Issue that blocks this one
+    #[arg(long, value_name = "ID")]
+    blocked_by: Option<String>,
+    /// Issue this one blocks
+    #[arg(long, value_name = "ID")]
+    blocks: Option<String>,
+    /// Issue to relate this one with (non-blocking, both directions)
+    #[arg(long, value_name = "ID")]
+    related: Option<String>,
+    /// Epic containing this issue
+    #[arg(long, value_name = "ID")]
+    parent: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print every setting
+    List,
+    /// Print one setting
+    Get { key: String },
+    /// Set a key: prefix (renames issues, like reprefix), default-type
+    /// (task, epic, refactor, spike) or default-priority (0-4)
+    Set { key: String, value: String },
+}
+
+/// Labels and blockers a new issue starts with (`create`, each `--batch` line).
+#[derive(Args)]
+struct CreateLinks {
+    /// Label to add (repeatable)
+    #[arg(long = "label")]
+    labels: Vec<String>,
+
+    /// Issue that blocks the new one (repeatable)
+    #[arg(long = "blocked-by")]
+    blocked_by: Vec<String>,
+}
+
+impl CreateLinks {
+    /// Resolve every blocker up front so a bad ID fails before any line is read.
+    fn resolve_blockers(&self, store: &Store) -> Result<Vec<String>, BaError> {
+        let mut blockers: Vec<String> = vec![];
+        for id in &self.blocked_by {
+            let id = store.resolve_id(id)?;
+            if !blockers.contains(&id) {
+                blockers.push(id);
+            }
+        }
+        Ok(blockers)
+    }
+}
+
+/// How `list` orders and prints the issues its filter selects.
+#[derive(Args, Debug)]
+struct ListView {
+    /// Comma-separated columns (id, priority, type, status, title, labels,
+    /// session, assignee, created, updated, closed)
+    #[arg(long)]
+    columns: Option<String>,
+
+    /// Annotate each JSON issue with a computed `ready` boolean
+    #[arg(long)]
+    with_ready: bool,
+
+    /// Sort by priority, created, updated, id, or status
+    #[arg(long, default_value = "priority")]
+    sort: String,
+
+    /// Reverse the sort order
+    #[arg(long)]
+    reverse: bool,
+
+    /// One compact JSON issue per line, shaped like issues.jsonl
+    #[arg(long)]
+    ndjson: bool,
+
+    #[command(flatten)]
+    page: Page,
+}
+
+/// `--limit`/`--offset` for listings, applied after sorting.
+#[derive(Args, Debug, Default)]
+struct Page {
+    /// Show at most this many issues
+    #[arg(long)]
+    limit: Option<usize>,
+
+    /// Skip this many issues first
+    #[arg(long, default_value_t = 0)]
+    offset: usize,
+
+    /// With --json, wrap the page as {total, offset, limit, issues}
+    #[arg(long)]
+    paginate: bool,
+}
+
+impl Page {
+    /// Cut `issues` down to the requested page; returns how many there were.
+    fn apply<T>(&self, issues: &mut Vec<T>) -> usize {
+        let total = issues.len();
+        issues.drain(..self.offset.min(total));
+        if let Some(limit) = self.limit {
+            issues.truncate(limit);
+        }
+        total
+    }
+
+    /// Print a page as a JSON array, or in the `--paginate` envelope.
+    fn print_json<T: Serialize>(&self, total: usize, issues: &T) {
+        if self.paginate {
+            println!(
+                "{}",
+                serde_json::json!({
+                    "total": total,
+                    "offset": self.offset,
+                    "limit": self.limit,
+                    "issues": issues,
+                })
+            );
+        } else {
+            println!("{}", serde_json::to_string(issues).unwrap());
+        }
+    }
+
+    /// Footer line when the page doesn't show everything.
+    fn note(&self, shown: usize, total: usize) -> Option<String> {
+        if shown == total {
+            None
+        } else if shown == 0 {
+            Some(format!(
+                "Nothing at offset {} ({} total)",
+                self.offset, total
+            ))
+        } else {
+            Some(format!(
+                "Showing {}-{} of {} (--offset/--limit to page)",
+                self.offset + 1,
+                self.offset + shown,
+                total
+            ))
+        }
+    }
+}
+
+/// Issue filters shared by `list` and the commands that mirror it.
+#[derive(Args, Debug, Default)]
+struct ListFilter {
+    /// Filter by status (open, in_progress, closed); repeat or comma-separate
+    /// to match any of several
+    #[arg(long, value_delimiter = ',')]
+    status: Vec<String>,
+
+    /// Include closed issues
+    #[arg(long)]
+    all: bool,
+
+    /// Only this priority (0-4)
+    #[arg(long)]
+    priority: Option<u8>,
+
+    /// Only priorities at or above this urgency (e.g. 1 = P0 and P1)
+    #[arg(long)]
+    max_priority: Option<u8>,
+
+    /// Only non-closed issues whose due date has passed
+    #[arg(long)]
+    overdue: bool,
+
+    /// Only issues of this type (task, epic, refactor, spike)
+    #[arg(long = "type")]
+    issue_type: Option<String>,
+
+    /// Only issues with this label
+    #[arg(long)]
+    label: Option<String>,
+
+    /// Only issues assigned to this person
+    #[arg(long)]
+    assignee: Option<String>,
+
+    /// Only estimated issues at or above this estimate
+    #[arg(long)]
+    min_estimate: Option<u32>,
+
+    /// Only estimated issues at or below this estimate
+    #[arg(long)]
+    max_estimate: Option<u32>,
+
+    /// Only issues updated after this time: RFC 3339, YYYY-MM-DD, or relative
+    /// like 30m, 1h, 2d, 1w
+    #[arg(long)]
+    since: Option<String>,
+
+    /// Include archived issues (they're all closed, so this implies --all)
+    #[arg(long)]
+    archived: bool,
+}
+
+impl ListFilter {
+    /// Whether any flag selects a subset (beyond hiding closed issues).
+    fn narrows(&self) -> bool {
+        !self.status.is_empty()
+            || self.priority.is_some()
+            || self.max_priority.is_some()
+            || self.overdue
+            || self.issue_type.is_some()
+            || self.label.is_some()
+            || self.assignee.is_some()
+            || self.min_estimate.is_some()
+            || self.max_estimate.is_some()
+            || self.since.is_some()
+    }
+
+    /// Validate the flags, then keep only matching issues.
+    fn apply(&self, issues: &mut Vec<&Issue>) -> Result<(), String> {
+        for value in [self.priority, self.max_priority].into_iter().flatten() {
+            if value > 4 {
+                return Err("Priority must be 0-4".to_string());
+            }
+        }
+        let since = self
+            .since
+            .as_deref()
+            .map(|expr| parse_since(expr, Utc::now()))
+            .transpose()?;
+        let issue_type: Option<IssueType> =
+            self.issue_type.as_deref().map(str::parse).transpose()?;
+
+        if !self.archived {
+            issues.retain(|i| !i.archived);
+        }
+
+        if !self.status.is_empty() {
+            let statuses = self
+                .status
+                .iter()
+                .map(|s| s.trim().parse())
+                .collect::<Result<Vec<Status>, String>>()?;
+            issues.retain(|i| statuses.contains(&i.status));
+        } else if !self.all && !self.archived {
+            issues.retain(|i| i.status != Status::Closed);
+        }
+        if let Some(priority) = self.priority {
+            issues.retain(|i| i.priority == priority);
+        }
+        if let Some(max) = self.max_priority {
+            issues.retain(|i| i.priority <= max);
+        }
+        if let Some(issue_type) = issue_type {
+            issues.retain(|i| i.issue_type == issue_type);
+        }
+        if let Some(label) = &self.label {
+            issues.retain(|i| i.labels.contains(label));
+        }
+        if let Some(assignee) = &self.assignee {
+            issues.retain(|i| i.assignee.as_ref() == Some(assignee));
+        }
+        // Unestimated issues can't be sized, so either bound drops them
+        if let Some(min) = self.min_estimate {
+            issues.retain(|i| i.estimate.is_some_and(|e| e >= min));
+        }
+        if let Some(max) = self.max_estimate {
+            issues.retain(|i| i.estimate.is_some_and(|e| e <= max));
+        }
+        if let Some(since) = since {
+            issues.retain(|i| i.updated_at > since);
+        }
+        if self.overdue {
+            let now = Utc::now();
+            issues.retain(|i| i.status != Status::Closed && i.due.is_some_and(|due| due < now));
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse a `--since` expression into an instant: an RFC 3339 timestamp, a
+/// date (midnight UTC), or an age like `90m`, `1h`, `2d`, `1w` before `now`.
+fn parse_since(expr: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    let invalid = || {
+        format!(
+            "Invalid time: {} (expected RFC 3339, YYYY-MM-DD, or an age like 30m, 1h, 2d, 1w)",
+            expr
+        )
+    };
+
+    if let Ok(at) = DateTime::parse_from_rfc3339(expr) {
+        return Ok(at.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(expr, "%Y-%m-%d") {
+        return Ok(date.and_time(NaiveTime::MIN).and_utc());
+    }
+
+    let (split, _) = expr.char_indices().last().ok_or_else(invalid)?;
+    let (amount, unit) = expr.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let age = match unit {
+        "s" => chrono::Duration::try_seconds(amount),
+        "m" => chrono::Duration::try_minutes(amount),
+        "h" => chrono::Duration::try_hours(amount),
+        "d" => chrono::Duration::try_days(amount),
+        "w" => chrono::Duration::try_weeks(amount),
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+    now.checked_sub_signed(age).ok_or_else(invalid)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Output (color, quiet mode)
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Set once in `main`: `--quiet` without `--json`.
+static QUIET: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// `println!` for success chatter that `--quiet` silences. Data output
+/// (listings, reports, `--json`) and errors keep using `println!`/`eprintln!`.
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if !QUIET.load(std::sync::atomic::Ordering::Relaxed) {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Decided once in `main`: a TTY, no `--no-color`/`NO_COLOR`, and not `--json`.
+static COLOR: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Wrap already-padded `text` in an SGR code when color is on.
+fn paint(text: &str, code: Option<&str>) -> String {
+    match code {
+        Some(code) if COLOR.load(std::sync::atomic::Ordering::Relaxed) => {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        }
+        _ => text.to_string(),
+    }
+}
+
+fn status_color(status: &Status) -> Option<&'static str> {
+    match status {
+        Status::Open => None,
+        Status::InProgress => Some("33"),
+        Status::Closed => Some("2"),
+    }
+}
+
+fn priority_color(priority: u8) -> Option<&'static str> {
+    match priority {
+        0 => Some("1;91"),
+        1 => Some("31"),
+        _ => None,
+    }
+}
 
 // ─────────────────────────────────────────────────────────────────────────────
 // Command Implementations
 // ─────────────────────────────────────────────────────────────────────────────
 
-fn cmd_init(ac_dir: &Path) -> Result<(), String> {
-    if ac_dir.exists() {
-        return Err(format!("{} already exists", ac_dir.display()));
+fn cmd_init(ac_dir: &Path) -> Result<(), BaError> {
+    if ac_dir.exists() {
+        return Err(format!("{} already exists", ac_dir.display()).into());
+    }
+    if dry_run() {
+        say!("Would initialize {}", ac_dir.display());
+        return Ok(());
+    }
+
+    fs::create_dir_all(ac_dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+
+    // Generate prefix from current directory hash
+    let cwd =
+        std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
+    let cwd_str = cwd.to_string_lossy();
+    let mut hasher = Sha256::new();
+    hasher.update(cwd_str.as_bytes());
+    let hash = hasher.finalize();
+
+    // Take first 2 chars as base36
+    let prefix: String = hash[0..2]
+        .iter()
+        .map(|b| {
+            let idx = (b % 36) as usize;
+            if idx < 10 {
+                (b'0' + idx as u8) as char
+            } else {
+                (b'a' + (idx - 10) as u8) as char
+            }
+        })
+        .collect();
+
+    let config = Config {
+        version: 1,
+        prefix,
+        default_type: default_issue_type(),
+        default_priority: default_priority(),
+        web_base_url: None,
+        label_priority: BTreeMap::new(),
+        escalate_after_days: None,
+        decay_after_days: None,
+    };
+    let config_path = ac_dir.join(CONFIG_FILE);
+    let config_json = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    fs::write(&config_path, config_json).map_err(|e| format!("Failed to write config: {}", e))?;
+
+    // Create empty issues file
+    let issues_path = ac_dir.join(ISSUES_FILE);
+    File::create(&issues_path).map_err(|e| format!("Failed to create issues file: {}", e))?;
+
+    say!(
+        "Initialized {} with prefix '{}'",
+        ac_dir.display(),
+        config.prefix
+    );
+    Ok(())
+}
+
+/// Contents of `--description-file`; `-` reads stdin.
+fn read_description_file(path: &Path) -> Result<String, BaError> {
+    let read = if path == Path::new("-") {
+        std::io::read_to_string(std::io::stdin())
+    } else {
+        fs::read_to_string(path)
+    };
+    read.map_err(|e| format!("Failed to read {}: {}", path.display(), e).into())
+}
+
+fn cmd_create(
+    store: &mut Store,
+    title: String,
+    issue_type: String,
+    priority: u8,
+    description: Option<String>,
+    links: &CreateLinks,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let description =
+        description.unwrap_or_else(|| store.template(&issue_type).unwrap_or_default());
+    let issue = store.create_issue(
+        title,
+        &issue_type,
+        priority,
+        description,
+        &links.labels,
+        &links.blocked_by,
+    )?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issue).unwrap());
+    } else {
+        say!("Created {}", issue.id);
+    }
+
+    Ok(())
+}
+
+/// Create a fresh open issue from `id`'s type, priority, labels and
+/// description. Session, comments and dependencies are not copied.
+fn cmd_clone(
+    store: &mut Store,
+    id: &str,
+    title: Option<String>,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let id = store.resolve_id(id)?;
+    let source = store.issues[&id].clone();
+    let title = title.unwrap_or_else(|| format!("Copy of {}", source.title));
+    let issue = store.create_issue(
+        title,
+        &source.issue_type.to_string(),
+        source.priority,
+        source.description,
+        &source.labels,
+        &[],
+    )?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issue).unwrap());
+    } else {
+        say!("Created {} (copy of {})", issue.id, id);
+    }
+
+    Ok(())
+}
+
+/// One JSON line of `ba create --batch`; missing fields fall back to the flags.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchLine {
+    title: String,
+    #[serde(rename = "type")]
+    issue_type: Option<String>,
+    priority: Option<u8>,
+    description: Option<String>,
+}
+
+/// Create one issue per stdin line and save once. Bad lines are reported with
+/// their line number and skipped; the rest of the batch still goes in.
+fn cmd_create_batch(
+    store: &mut Store,
+    issue_type: &str,
+    priority: u8,
+    description: Option<&str>,
+    links: &CreateLinks,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let blockers = links.resolve_blockers(store)?;
+    let mut created: Vec<String> = vec![];
+    let mut errors: Vec<ImportError> = vec![];
+
+    for (line_num, line) in std::io::stdin().lock().lines().enumerate() {
+        let line_num = line_num + 1; // 1-indexed for user display
+        let line = match line {
+            Ok(l) => l,
+            Err(e) => {
+                errors.push(ImportError {
+                    line_num,
+                    issue_id: None,
+                    field: "line".to_string(),
+                    message: format!("Failed to read: {}", e),
+                });
+                continue;
+            }
+        };
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let fields = if trimmed.starts_with('{') {
+            match serde_json::from_str::<BatchLine>(trimmed) {
+                Ok(b) => b,
+                Err(e) => {
+                    errors.push(ImportError {
+                        line_num,
+                        issue_id: None,
+                        field: "json".to_string(),
+                        message: format!("Invalid issue: {}", e),
+                    });
+                    continue;
+                }
+            }
+        } else {
+            BatchLine {
+                title: trimmed.to_string(),
+                issue_type: None,
+                priority: None,
+                description: None,
+            }
+        };
+
+        let line_type = fields.issue_type.as_deref().unwrap_or(issue_type);
+        let description = fields
+            .description
+            .or_else(|| description.map(str::to_string))
+            .or_else(|| store.template(line_type))
+            .unwrap_or_default();
+        match store.create_issue(
+            fields.title,
+            line_type,
+            fields.priority.unwrap_or(priority),
+            description,
+            &links.labels,
+            &blockers,
+        ) {
+            Ok(issue) => created.push(issue.id),
+            Err(e) => errors.push(ImportError {
+                line_num,
+                issue_id: None,
+                field: "issue".to_string(),
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    if !created.is_empty() {
+        store.save()?;
+    }
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({ "created": created, "errors": errors.len() })
+        );
+    } else {
+        for id in &created {
+            say!("Created {}", id);
+        }
+        say!("{} created, {} errors", created.len(), errors.len());
+        if !errors.is_empty() {
+            println!();
+            println!("Errors:");
+            for err in &errors {
+                println!("  {}", err);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_list(
+    store: &Store,
+    filter: &ListFilter,
+    view: &ListView,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let mut issues: Vec<_> = store.issues.values().collect();
+    filter.apply(&mut issues)?;
+    print_list(store, issues, view, json_output)
+}
+
+/// Unlike `list`, closed issues match unless the expression rules them out.
+fn cmd_query(
+    store: &Store,
+    expr: &str,
+    archived: bool,
+    view: &ListView,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let query = Query::parse(expr)?;
+    let issues: Vec<_> = store
+        .issues
+        .values()
+        .filter(|issue| (archived || !issue.archived) && query.matches(issue))
+        .collect();
+    print_list(store, issues, view, json_output)
+}
+
+fn cmd_recent(store: &Store, limit: usize, json_output: bool) -> Result<(), BaError> {
+    let mut issues: Vec<_> = store.issues.values().filter(|i| !i.archived).collect();
+    issues.sort_by(|a, b| {
+        b.updated_at
+            .cmp(&a.updated_at)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    issues.truncate(limit);
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issues).unwrap());
+        return Ok(());
+    }
+
+    if issues.is_empty() {
+        println!("No issues found.");
+        return Ok(());
+    }
+
+    println!();
+    print_issue_table(
+        &issues,
+        &[
+            Column::Id,
+            Column::Priority,
+            Column::Type,
+            Column::Status,
+            Column::Updated,
+            Column::Title,
+        ],
+    );
+    println!();
+    println!("{} most recently updated issue(s)", issues.len());
+
+    Ok(())
+}
+
+/// Sort, page and render already-filtered issues the way `ba list` does.
+fn print_list(
+    store: &Store,
+    mut issues: Vec<&Issue>,
+    view: &ListView,
+    json_output: bool,
+) -> Result<(), BaError> {
+    // Validate up front so a typo errors even when --json ignores the columns
+    let columns = match &view.columns {
+        Some(spec) => Column::parse_list(spec)?,
+        None => Column::DEFAULT.to_vec(),
+    };
+
+    // Ties fall back to creation order so the listing is stable
+    let compare: fn(&Issue, &Issue) -> std::cmp::Ordering = match view.sort.as_str() {
+        "priority" => |a, b| a.cmp_priority(b),
+        "created" => |a, b| a.created_at.cmp(&b.created_at),
+        "updated" => |a, b| a.updated_at.cmp(&b.updated_at),
+        "id" => |a, b| a.id.cmp(&b.id),
+        "status" => |a, b| a.status.cmp(&b.status),
+        _ => {
+            return Err(format!(
+                "Unknown sort key: {} (valid: priority, created, updated, id, status)",
+                view.sort
+            )
+            .into());
+        }
+    };
+
+    issues.sort_by(|a, b| compare(a, b).then_with(|| a.created_at.cmp(&b.created_at)));
+    if view.reverse {
+        issues.reverse();
+    }
+    if view.ndjson && view.page.paginate {
+        return Err("--paginate wraps the --json array; it can't apply to --ndjson".into());
+    }
+    let total = view.page.apply(&mut issues);
+
+    if json_output || view.ndjson {
+        if view.with_ready {
+            let annotated: Vec<_> = issues
+                .iter()
+                .map(|issue| {
+                    let mut value = serde_json::to_value(issue).unwrap();
+                    value["ready"] = serde_json::json!(store.is_ready(issue));
+                    value
+                })
+                .collect();
+            if view.ndjson {
+                annotated.iter().for_each(|value| println!("{}", value));
+            } else {
+                view.page.print_json(total, &annotated);
+            }
+        } else if view.ndjson {
+            // Same serialization as issues.jsonl, one issue per line
+            for issue in &issues {
+                println!("{}", serde_json::to_string(issue).unwrap());
+            }
+        } else {
+            view.page.print_json(total, &issues);
+        }
+        return Ok(());
+    }
+
+    if issues.is_empty() {
+        match view.page.note(0, total) {
+            Some(note) => println!("{}", note),
+            None => println!("No issues found."),
+        }
+        return Ok(());
+    }
+
+    // Pretty print
+    println!();
+    print_issue_table(&issues, &columns);
+
+    let open = issues.iter().filter(|i| i.status == Status::Open).count();
+    let in_progress = issues
+        .iter()
+        .filter(|i| i.status == Status::InProgress)
+        .count();
+    let closed = issues.iter().filter(|i| i.status == Status::Closed).count();
+
+    println!();
+    println!(
+        "{} issues ({} open, {} in_progress, {} closed)",
+        issues.len(),
+        open,
+        in_progress,
+        closed
+    );
+    if let Some(note) = view.page.note(issues.len(), total) {
+        println!("{}", note);
+    }
+
+    Ok(())
+}
+
+fn cmd_count(store: &Store, filter: &ListFilter, json_output: bool) -> Result<(), BaError> {
+    let mut issues: Vec<_> = store.issues.values().collect();
+    filter.apply(&mut issues)?;
+
+    if json_output {
+        println!("{}", serde_json::json!({ "count": issues.len() }));
+    } else {
+        println!("{}", issues.len());
+    }
+
+    Ok(())
+}
+
+fn cmd_search(
+    store: &Store,
+    query: &str,
+    all: bool,
+    archived: bool,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let needle = query.to_lowercase();
+    if needle.trim().is_empty() {
+        return Err("Search query cannot be empty".into());
+    }
+
+    let mut hits: Vec<_> = store
+        .issues
+        .values()
+        .filter(|i| archived || (!i.archived && (all || i.status != Status::Closed)))
+        .filter_map(|i| i.search_field(&needle).map(|field| (i, field)))
+        .collect();
+
+    hits.sort_by(|(a, _), (b, _)| a.cmp_priority(b));
+
+    if json_output {
+        let annotated: Vec<_> = hits
+            .iter()
+            .map(|(issue, field)| {
+                let mut value = serde_json::to_value(issue).unwrap();
+                value["matched"] = serde_json::json!(field);
+                value
+            })
+            .collect();
+        println!("{}", serde_json::to_string(&annotated).unwrap());
+        return Ok(());
+    }
+
+    if hits.is_empty() {
+        println!("No issues match \"{}\"", query);
+        return Ok(());
+    }
+
+    println!();
+    println!(
+        "  {:<8} {:>2}  {:<11}  {:<12} TITLE",
+        "ID", "P", "MATCHED", "STATUS"
+    );
+    println!("  {}", "-".repeat(70));
+
+    for (issue, field) in &hits {
+        println!(
+            "  {:<8} {:>2}  {:<11}  {:<12} {}",
+            issue.id,
+            issue.priority,
+            field,
+            issue.status.to_string(),
+            truncate(&issue.title, 40)
+        );
+    }
+
+    println!();
+    println!("{} issue(s) match \"{}\"", hits.len(), query);
+
+    Ok(())
+}
+
+/// A column in the `list` table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Column {
+    Id,
+    Priority,
+    Type,
+    Status,
+    Title,
+    Labels,
+    Session,
+    Assignee,
+    Created,
+    Updated,
+    Closed,
+}
+
+impl Column {
+    const ALL: [Column; 11] = [
+        Column::Id,
+        Column::Priority,
+        Column::Type,
+        Column::Status,
+        Column::Title,
+        Column::Labels,
+        Column::Session,
+        Column::Assignee,
+        Column::Created,
+        Column::Updated,
+        Column::Closed,
+    ];
+
+    const DEFAULT: [Column; 5] = [
+        Column::Id,
+        Column::Priority,
+        Column::Type,
+        Column::Status,
+        Column::Title,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Column::Id => "id",
+            Column::Priority => "priority",
+            Column::Type => "type",
+            Column::Status => "status",
+            Column::Title => "title",
+            Column::Labels => "labels",
+            Column::Session => "session",
+            Column::Assignee => "assignee",
+            Column::Created => "created",
+            Column::Updated => "updated",
+            Column::Closed => "closed",
+        }
+    }
+
+    /// Parse a comma-separated column list like `id,priority,title`.
+    fn parse_list(spec: &str) -> Result<Vec<Column>, String> {
+        let columns = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                Column::ALL
+                    .into_iter()
+                    .find(|c| c.name() == name.to_lowercase())
+                    .ok_or_else(|| {
+                        let valid: Vec<_> = Column::ALL.iter().map(|c| c.name()).collect();
+                        format!("Unknown column: {} (valid: {})", name, valid.join(", "))
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if columns.is_empty() {
+            return Err("No columns given".to_string());
+        }
+        Ok(columns)
+    }
+
+    fn header(self) -> &'static str {
+        match self {
+            Column::Id => "ID",
+            Column::Priority => "P",
+            Column::Type => "TYPE",
+            Column::Status => "STATUS",
+            Column::Title => "TITLE",
+            Column::Labels => "LABELS",
+            Column::Session => "SESSION",
+            Column::Assignee => "ASSIGNEE",
+            Column::Created => "CREATED",
+            Column::Updated => "UPDATED",
+            Column::Closed => "CLOSED",
+        }
+    }
+
+    /// Display width in the fixed layout; cells longer than this are truncated.
+    fn width(self) -> usize {
+        match self {
+            Column::Id => 8,
+            Column::Priority => 2,
+            Column::Type => 8,
+            Column::Status => 12,
+            Column::Title => 40,
+            Column::Labels => 24,
+            Column::Session | Column::Assignee => 16,
+            Column::Created | Column::Updated | Column::Closed => 16,
+        }
+    }
+
+    fn cell(self, issue: &Issue, width: usize) -> String {
+        let text = match self {
+            Column::Id => issue.id.clone(),
+            Column::Priority => issue.priority.to_string(),
+            Column::Type => issue.issue_type.to_string(),
+            Column::Status => issue.status.to_string(),
+            Column::Title => issue.title.clone(),
+            Column::Labels => issue.labels.join(","),
+            Column::Session => issue.session_id.clone().unwrap_or_default(),
+            Column::Assignee => issue.assignee.clone().unwrap_or_default(),
+            Column::Created => issue.created_at.format("%Y-%m-%d %H:%M").to_string(),
+            Column::Updated => issue.updated_at.format("%Y-%m-%d %H:%M").to_string(),
+            Column::Closed => issue
+                .closed_at
+                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default(),
+        };
+        // IDs are never truncated; an unusually long one just widens its row
+        if self == Column::Id {
+            text
+        } else {
+            truncate(&text, width)
+        }
+    }
+}
+
+/// Narrowest the title column gets on a small terminal.
+const MIN_TITLE_WIDTH: usize = 20;
+
+/// Terminal width when stdout is a terminal: `$COLUMNS` if set, otherwise
+/// the size the terminal reports.
+fn terminal_width() -> Option<usize> {
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    let columns = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .filter(|width| *width > 0);
+    columns.or_else(|| {
+        terminal_size::terminal_size().map(|(terminal_size::Width(width), _)| usize::from(width))
+    })
+}
+
+/// Width of each column in this table. Without a terminal width this is the
+/// fixed layout; with one, the ID column fits the longest ID and the title
+/// column takes whatever the others leave over.
+fn table_widths(issues: &[&Issue], columns: &[Column], terminal: Option<usize>) -> Vec<usize> {
+    let mut widths: Vec<usize> = columns.iter().map(|c| c.width()).collect();
+    let Some(terminal) = terminal else {
+        return widths;
+    };
+
+    for (column, width) in columns.iter().zip(widths.iter_mut()) {
+        if *column == Column::Id {
+            let longest = issues.iter().map(|i| i.id.chars().count()).max();
+            *width = longest.unwrap_or(0).max(column.header().len());
+        }
+    }
+    if let Some(title) = columns.iter().position(|c| *c == Column::Title) {
+        // Leading space, then each cell's separator (priority has two)
+        let others: usize = columns
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .filter(|(i, _)| *i != title)
+            .map(|(_, (c, w))| w + if *c == Column::Priority { 2 } else { 1 })
+            .sum();
+        widths[title] = terminal.saturating_sub(1 + others + 1).max(MIN_TITLE_WIDTH);
+    }
+    widths
+}
+
+/// Render one table row. Cells are padded to their column width except the
+/// last, and the priority column gets an extra gap (matching the classic layout).
+/// Issue rows are colored by status, with P0/P1 priorities standing out.
+fn table_row(cells: &[(Column, String)], widths: &[usize], issue: Option<&Issue>) -> String {
+    let mut row = String::from(" ");
+    for (i, ((column, text), width)) in cells.iter().zip(widths).enumerate() {
+        let is_last = i == cells.len() - 1;
+        let cell = if is_last {
+            text.clone()
+        } else if *column == Column::Priority {
+            format!("{:>width$}", text, width = width)
+        } else {
+            format!("{:<width$}", text, width = width)
+        };
+        let color = issue.and_then(|issue| match column {
+            Column::Priority => priority_color(issue.priority).or(status_color(&issue.status)),
+            _ => status_color(&issue.status),
+        });
+        row.push(' ');
+        row.push_str(&paint(&cell, color));
+        if !is_last && *column == Column::Priority {
+            row.push(' ');
+        }
+    }
+    row
+}
+
+fn print_issue_table(issues: &[&Issue], columns: &[Column]) {
+    let terminal = terminal_width();
+    let widths = table_widths(issues, columns, terminal);
+    let header: Vec<_> = columns
+        .iter()
+        .map(|c| (*c, c.header().to_string()))
+        .collect();
+    println!("{}", table_row(&header, &widths, None));
+
+    let rule = match terminal {
+        Some(terminal) => terminal.saturating_sub(2),
+        None => {
+            let fixed: usize = columns[..columns.len() - 1]
+                .iter()
+                .zip(&widths)
+                .map(|(c, w)| w + if *c == Column::Priority { 2 } else { 1 })
+                .sum();
+            fixed.max(70)
+        }
+    };
+    println!("  {}", "-".repeat(rule));
+
+    for issue in issues {
+        let cells: Vec<_> = columns
+            .iter()
+            .zip(&widths)
+            .map(|(c, w)| (*c, c.cell(issue, *w)))
+            .collect();
+        println!("{}", table_row(&cells, &widths, Some(issue)));
+    }
+}
+
+fn cmd_show(
+    store: &Store,
+    id: &str,
+    comments_only: bool,
+    web: bool,
+    oneline: bool,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let id = &store.resolve_id(id)?;
+    let issue = store.issues.get(id).expect("resolved ID exists");
+    let url = if web {
+        Some(issue_url(&store.config, id)?)
+    } else {
+        None
+    };
+
+    if comments_only {
+        if json_output {
+            println!("{}", serde_json::to_string_pretty(&issue.comments).unwrap());
+        } else if issue.comments.is_empty() {
+            println!("No comments on {}.", id);
+        } else {
+            print_comments(issue);
+        }
+        return Ok(());
+    }
+
+    if json_output {
+        let mut value = serde_json::to_value(issue).unwrap();
+        if let Some(url) = url {
+            value["url"] = url.into();
+        }
+        println!("{}", serde_json::to_string_pretty(&value).unwrap());
+        return Ok(());
+    }
+
+    if oneline {
+        let labels = if issue.labels.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", issue.labels.join(", "))
+        };
+        println!(
+            "{} [{}] P{} {} {}{}",
+            issue.id,
+            issue.status.to_string().to_uppercase(),
+            issue.priority,
+            issue.issue_type,
+            issue.title,
+            labels
+        );
+        return Ok(());
+    }
+
+    print_issue_detail(store, issue);
+    if let Some(url) = url {
+        println!("Link:     {}", url);
+    }
+    Ok(())
+}
+
+/// `<web_base_url>/<id>`, or an error saying how to configure the viewer.
+fn issue_url(config: &Config, id: &str) -> Result<String, BaError> {
+    let base = config.web_base_url.as_deref().ok_or(
+        "No web viewer configured (set one with 'ba config set web-base-url https://...')",
+    )?;
+    Ok(format!("{}/{}", base, id))
+}
+
+fn cmd_link(store: &Store, id: &str, json_output: bool) -> Result<(), BaError> {
+    let id = store.resolve_id(id)?;
+    let url = issue_url(&store.config, &id)?;
+    if json_output {
+        println!("{}", serde_json::json!({"id": id, "url": url}));
+    } else {
+        println!("{}", url);
+    }
+    Ok(())
+}
+
+/// Full human-readable view of one issue, as printed by `show`.
+fn print_issue_detail(store: &Store, issue: &Issue) {
+    println!();
+    println!("{}: {}", issue.id, issue.title);
+    println!("{}", "-".repeat(60));
+    println!(
+        "Status:   {} Priority: {}",
+        paint(
+            &format!("{:<16}", issue.status.to_string()),
+            status_color(&issue.status)
+        ),
+        paint(
+            &format!("P{}", issue.priority),
+            priority_color(issue.priority)
+        )
+    );
+    println!("Type:     {}", issue.issue_type);
+    if let Some(ref parent) = issue.parent {
+        println!("Parent:   {}", parent);
+    }
+    if let Some(due) = issue.due {
+        println!("Due:      {}", due.format("%Y-%m-%d"));
+    }
+    if let Some(estimate) = issue.estimate {
+        println!("Estimate: {}", estimate);
+    }
+    if !issue.time_entries.is_empty() {
+        let running = issue.time_entries.iter().any(|(_, end)| end.is_none());
+        println!(
+            "Tracked:  {}{}",
+            format_duration(issue.tracked_time(Utc::now())),
+            if running { " (timer running)" } else { "" }
+        );
+    }
+    if let Some(ref assignee) = issue.assignee {
+        println!("Assignee: {}", assignee);
+    }
+    if let Some(ref session) = issue.session_id {
+        println!("Session:  {}", session);
+    }
+    if let Some(expires) = issue.claim_expires_at {
+        let lapsed = if issue.lease_expired(Utc::now()) {
+            " (expired)"
+        } else {
+            ""
+        };
+        println!("Lease:    {}{}", expires.format("%Y-%m-%d %H:%M"), lapsed);
+    }
+    println!("Created:  {}", issue.created_at.format("%Y-%m-%d %H:%M"));
+    println!("Updated:  {}", issue.updated_at.format("%Y-%m-%d %H:%M"));
+    if let Some(closed_at) = issue.closed_at {
+        match &issue.close_reason {
+            Some(reason) => println!(
+                "Closed:   {} (reason: {})",
+                closed_at.format("%Y-%m-%d %H:%M"),
+                reason
+            ),
+            None => println!("Closed:   {}", closed_at.format("%Y-%m-%d %H:%M")),
+        }
+    }
+    if issue.archived {
+        println!("Archived: yes");
+    }
+    if !issue.description.is_empty() {
+        println!();
+        println!("Description:");
+        println!("{}", issue.description);
+    }
+    if !issue.blocked_by.is_empty() {
+        let blockers: Vec<String> = issue
+            .blocked_by
+            .iter()
+            .map(|b| issue.describe_blocker(b))
+            .collect();
+        println!();
+        println!("Blocked by: {}", blockers.join(", "));
+    }
+    if !issue.blocks.is_empty() {
+        let blocked: Vec<String> = issue
+            .blocks
+            .iter()
+            .map(|b| {
+                match store
+                    .issues
+                    .get(b)
+                    .and_then(|other| other.edge(&issue.id).label())
+                {
+                    Some(label) => format!("{} ({})", b, label),
+                    None => b.clone(),
+                }
+            })
+            .collect();
+        println!("Blocks: {}", blocked.join(", "));
+    }
+    if !issue.related.is_empty() {
+        println!();
+        println!("Related: {}", issue.related.join(", "));
+    }
+    if let Some(canonical) = &issue.duplicate_of {
+        println!();
+        println!("Duplicate of: {}", canonical);
+    }
+    let mut duplicates: Vec<&str> = store
+        .issues
+        .values()
+        .filter(|other| other.duplicate_of.as_ref() == Some(&issue.id))
+        .map(|other| other.id.as_str())
+        .collect();
+    if !duplicates.is_empty() {
+        duplicates.sort();
+        println!();
+        println!("Duplicates: {}", duplicates.join(", "));
+    }
+    if !issue.labels.is_empty() {
+        println!();
+        println!("Labels: {}", issue.labels.join(", "));
+    }
+    if !issue.comments.is_empty() {
+        println!();
+        print_comments(issue);
+    }
+}
+
+/// The numbered comment thread, as `show` prints it.
+fn print_comments(issue: &Issue) {
+    println!("Comments ({}):", issue.comments.len());
+    for (index, comment) in issue.comments.iter().enumerate() {
+        println!(
+            "  {}. [{}] {}: {}{}",
+            index,
+            comment.created_at.format("%Y-%m-%d %H:%M"),
+            comment.author,
+            comment.text,
+            if comment.edited_at.is_some() {
+                " (edited)"
+            } else {
+                ""
+            }
+        );
+    }
+}
+
+fn cmd_close(
+    store: &mut Store,
+    id: &str,
+    reason: Option<String>,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let issue = store.close(id, reason)?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issue).unwrap());
+    } else {
+        say!("Closed {}", issue.id);
+    }
+
+    Ok(())
+}
+
+/// `close --cascade`: see [`Store::close_cascade`].
+fn cmd_close_cascade(
+    store: &mut Store,
+    id: &str,
+    reason: Option<String>,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let Cascade { closed, skipped } = store.close_cascade(id, reason)?;
+    for (skipped_id, session) in &skipped {
+        eprintln!(
+            "Warning: skipping {} (claimed by session {})",
+            skipped_id, session
+        );
+    }
+    store.save()?;
+
+    if json_output {
+        let skipped: Vec<&String> = skipped.iter().map(|(id, _)| id).collect();
+        println!(
+            "{}",
+            serde_json::json!({"closed": closed, "skipped": skipped})
+        );
+    } else {
+        for closed_id in &closed {
+            say!("Closed {}", closed_id);
+        }
+    }
+
+    Ok(())
+}
+
+/// `dup`: see [`Store::close_as_duplicate`].
+fn cmd_dup(
+    store: &mut Store,
+    id: &str,
+    canonical: &str,
+    author: &str,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let issue = store.close_as_duplicate(id, canonical, author)?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issue).unwrap());
+    } else {
+        say!(
+            "Closed {} as a duplicate of {}",
+            issue.id,
+            issue.duplicate_of.as_deref().unwrap_or_default()
+        );
+    }
+
+    Ok(())
+}
+
+/// `merge-issues`: see [`Store::merge_issues`].
+fn cmd_merge_issues(
+    store: &mut Store,
+    a: &str,
+    b: &str,
+    author: &str,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let b = store.resolve_id(b)?;
+    let merged = store.merge_issues(a, &b, author)?;
+    let a = &merged.id;
+
+    if let Some(path) = merged
+        .blocked_by
+        .iter()
+        .find_map(|blocker| store.blocker_path(blocker, a))
+    {
+        eprintln!(
+            "Warning: {} is now in a dependency cycle: {} -> {} (see ba cycles)",
+            a,
+            a,
+            path.join(" -> ")
+        );
+    }
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&merged).unwrap());
+    } else {
+        say!("Merged {} into {}", b, a);
+        say!(
+            "  {} label(s), {} comment(s), {} blocker(s), {} dependent(s)",
+            merged.labels.len(),
+            merged.comments.len(),
+            merged.blocked_by.len(),
+            merged.blocks.len()
+        );
+    }
+
+    Ok(())
+}
+
+fn cmd_bulk_close(
+    store: &mut Store,
+    filter: &ListFilter,
+    json_output: bool,
+) -> Result<(), BaError> {
+    if !filter.narrows() {
+        return Err(
+            "bulk-close needs a filter (--label, --status, --priority, --max-priority or --overdue)"
+                .into(),
+        );
+    }
+
+    let mut matched: Vec<_> = store.issues.values().collect();
+    filter.apply(&mut matched)?;
+    let ids: Vec<String> = matched.iter().map(|i| i.id.clone()).collect();
+    let Cascade { closed, skipped } = store.close_unclaimed(&ids)?;
+    // Claimed issues are someone's active work; they were left alone
+    for (id, session) in &skipped {
+        eprintln!("Warning: skipping {} (claimed by session {})", id, session);
+    }
+    let skipped: Vec<&String> = skipped.iter().map(|(id, _)| id).collect();
+
+    if !closed.is_empty() {
+        store.save()?;
+    }
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({"closed": closed, "skipped": skipped})
+        );
+    } else {
+        say!(
+            "Closed {} issue(s), skipped {} claimed",
+            closed.len(),
+            skipped.len()
+        );
+    }
+
+    Ok(())
+}
+
+fn cmd_delete(
+    store: &mut Store,
+    id: Option<String>,
+    label: Option<String>,
+    label_prefix: Option<String>,
+    force: bool,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let selector_count = usize::from(id.is_some())
+        + usize::from(label.is_some())
+        + usize::from(label_prefix.is_some());
+    if selector_count != 1 {
+        return Err(
+            "Provide exactly one selector: <id>, --label <label> or --label-prefix <prefix>".into(),
+        );
+    }
+
+    let selector_desc = match (&id, &label, &label_prefix) {
+        (Some(value), None, None) => format!("id '{}'", value),
+        (None, Some(value), None) => format!("label '{}'", value),
+        (None, None, Some(value)) => format!("label prefix '{}'", value),
+        _ => unreachable!("selector_count validated above"),
+    };
+
+    let mut matched_ids: Vec<String> = match &id {
+        Some(id) => {
+            let id = &store.resolve_id(id)?;
+            let issue = store.issues.get(id).expect("resolved ID exists");
+            // A claimed issue is someone's active work; make them release it first
+            if let Some(session) = &issue.session_id
+                && !force
+            {
+                return Err(format!(
+                    "{} is claimed by session {}. Use 'release' first, or --force to delete anyway.",
+                    id, session
+                ).into());
+            }
+            vec![id.clone()]
+        }
+        None => store
+            .issues
+            .values()
+            .filter(|issue| match (&label, &label_prefix) {
+                (Some(value), None) => issue.labels.iter().any(|existing| existing == value),
+                (None, Some(prefix)) => issue
+                    .labels
+                    .iter()
+                    .any(|existing| existing.starts_with(prefix)),
+                _ => false,
+            })
+            .map(|issue| issue.id.clone())
+            .collect(),
+    };
+    matched_ids.sort();
+
+    if matched_ids.is_empty() {
+        return Err(format!("No issues matched {}", selector_desc).into());
+    }
+
+    // Deleting by id is deliberate; the closed-only guard is for label sweeps
+    if !force && id.is_none() {
+        let non_closed: Vec<String> = matched_ids
+            .iter()
+            .filter_map(|id| {
+                store.issues.get(id).and_then(|issue| {
+                    if issue.status == Status::Closed {
+                        None
+                    } else {
+                        Some(id.clone())
+                    }
+                })
+            })
+            .collect();
+
+        if !non_closed.is_empty() {
+            return Err(format!(
+                "Refusing to delete non-closed issues without --force: {}",
+                non_closed.join(", ")
+            )
+            .into());
+        }
+    }
+
+    store.remove_issues(&matched_ids, Utc::now());
+    store.save()?;
+
+    let deleted_count = matched_ids.len();
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({
+                "selector": selector_desc,
+                "deleted": matched_ids,
+                "count": deleted_count,
+                "force": force
+            })
+        );
+    } else if let Some(id) = &id {
+        say!("Deleted {}", id);
+    } else {
+        say!(
+            "Deleted {} issue(s) matching {}",
+            deleted_count,
+            selector_desc
+        );
+    }
+
+    Ok(())
+}
+/// Delete closed issues whose `closed_at` predates `before`, appending each
+/// to archive.jsonl first. Issues still blocking unfinished work are kept.
+fn cmd_purge(
+    store: &mut Store,
+    before: &str,
+    dry_run: bool,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let now = Utc::now();
+    let cutoff = parse_since(before, now)?;
+
+    let mut candidates: Vec<&Issue> = store
+        .issues
+        .values()
+        .filter(|i| i.status == Status::Closed && i.closed_at.is_some_and(|at| at < cutoff))
+        .collect();
+    candidates.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let mut purged: Vec<String> = vec![];
+    let mut skipped: Vec<String> = vec![];
+    for issue in candidates {
+        let waiting: Vec<&str> = issue
+            .blocks
+            .iter()
+            .filter(|id| {
+                store
+                    .issues
+                    .get(*id)
+                    .is_some_and(|dep| dep.status != Status::Closed)
+            })
+            .map(String::as_str)
+            .collect();
+        if waiting.is_empty() {
+            purged.push(issue.id.clone());
+        } else {
+            eprintln!(
+                "Warning: keeping {}: still blocks {}",
+                issue.id,
+                waiting.join(", ")
+            );
+            skipped.push(issue.id.clone());
+        }
+    }
+
+    if !dry_run && !purged.is_empty() {
+        let removed = store.remove_issues(&purged, now);
+        // Archive before saving: a crash in between leaves a copy in both
+        // files rather than in neither
+        let path = store.ba_dir.join(ARCHIVE_FILE);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open archive: {}", e))?;
+        file.write_all(issues_jsonl(&removed)?.as_bytes())
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+        store.save()?;
+    }
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({
+                "before": cutoff,
+                "purged": purged,
+                "skipped": skipped,
+                "count": purged.len(),
+                "dry_run": dry_run,
+            })
+        );
+    } else {
+        say!(
+            "{} {} issue(s) closed before {}{}",
+            if dry_run { "Would purge" } else { "Purged" },
+            purged.len(),
+            cutoff.format("%Y-%m-%d %H:%M"),
+            if dry_run || purged.is_empty() {
+                String::new()
+            } else {
+                format!(" (archived to {})", ARCHIVE_FILE)
+            }
+        );
+        for id in &purged {
+            say!("  {}", id);
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_block(
+    store: &mut Store,
+    id: &str,
+    blocker: &str,
+    dep_type: &str,
+    soft: bool,
+    force: bool,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let edge = Edge {
+        dep_type: dep_type.parse()?,
+        hard: !soft,
+    };
+
+    let (id, blocker) = store.add_block(id, blocker, edge, force)?;
+    store.save()?;
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({
+                "blocked": id,
+                "blocker": blocker,
+                "type": edge.dep_type,
+                "hard": edge.hard
+            })
+        );
+    } else if let Some(label) = edge.label() {
+        say!("{} now blocked by {} ({})", id, blocker, label);
+    } else {
+        say!("{} now blocked by {}", id, blocker);
+    }
+
+    Ok(())
+}
+
+fn cmd_unblock(
+    store: &mut Store,
+    id: &str,
+    blocker: &str,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let (id, blocker) = store.unblock(id, blocker)?;
+    store.save()?;
+
+    if json_output {
+        println!(r#"{{"unblocked":"{}","was_blocker":"{}"}}"#, id, blocker);
+    } else {
+        say!("{} no longer blocked by {}", id, blocker);
+    }
+
+    Ok(())
+}
+
+/// Add (`link`) or remove a symmetric related link. Related issues are
+/// cross-references only: ready, tree and cycles never look at them.
+fn cmd_relate(
+    store: &mut Store,
+    id: &str,
+    other: &str,
+    link: bool,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let (id, other) = store.set_related(id, other, link)?;
+    store.save()?;
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({ "id": id, "other": other, "related": link })
+        );
+    } else if link {
+        say!("{} now related to {}", id, other);
+    } else {
+        say!("{} no longer related to {}", id, other);
+    }
+
+    Ok(())
+}
+
+/// Options shared by the text and JSON tree walkers.
+#[derive(Debug, Clone, Default)]
+struct TreeOptions {
+    /// Skip closed blockers (and everything behind them)
+    open_only: bool,
+    /// Walk `blocks` (what this unblocks) instead of `blocked_by`
+    downstream: bool,
+    /// Levels below the root to show; deeper nodes collapse to `...`
+    depth: Option<usize>,
+    /// When non-empty, skip issues (and everything behind them) whose status
+    /// isn't listed
+    statuses: Vec<Status>,
+    /// Show priority and claiming session on each node
+    verbose: bool,
+}
+
+impl TreeOptions {
+    /// Ids of `issue` to descend into, in stored order: blockers, or the
+    /// issues it blocks when walking downstream.
+    fn children<'a>(&self, store: &Store, issue: &'a Issue) -> Vec<&'a String> {
+        let next = if self.downstream {
+            &issue.blocks
+        } else {
+            &issue.blocked_by
+        };
+        next.iter()
+            .filter(|id| {
+                store.issues.get(*id).is_none_or(|child| {
+                    !(self.open_only && child.status == Status::Closed)
+                        && (self.statuses.is_empty() || self.statuses.contains(&child.status))
+                })
+            })
+            .collect()
+    }
+
+    /// Whether children of a node `level` steps below the root are cut off.
+    fn truncates(&self, level: usize) -> bool {
+        self.depth.is_some_and(|depth| level >= depth)
+    }
+
+    /// The edge between `issue` and `child`; it is always stored on the
+    /// blocked side.
+    fn edge(&self, store: &Store, issue: &Issue, child: &str) -> Edge {
+        if self.downstream {
+            store
+                .issues
+                .get(child)
+                .map(|c| c.edge(&issue.id))
+                .unwrap_or_default()
+        } else {
+            issue.edge(child)
+        }
+    }
+
+    /// JSON key holding a node's children.
+    fn key(&self) -> &'static str {
+        if self.downstream {
+            "blocks"
+        } else {
+            "blocked_by"
+        }
+    }
+}
+
+fn cmd_tree(
+    store: &Store,
+    id: &str,
+    options: &TreeOptions,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let id = &store.resolve_id(id)?;
+    let issue = store.issues.get(id).expect("resolved ID exists");
+
+    if json_output {
+        // Build tree structure as JSON
+        let tree = build_tree_json(store, id, None, options, &mut vec![]);
+        println!("{}", serde_json::to_string_pretty(&tree).unwrap());
+        return Ok(());
+    }
+
+    // Pretty print tree
+    println!();
+    print_tree_node(store, issue, None, options, "", true, &mut vec![]);
+
+    Ok(())
+}
+
+/// `edge` is the edge leading to this node (None for the root). `visited`
+/// holds the path from the root, so its length is this node's depth.
+fn build_tree_json(
+    store: &Store,
+    id: &str,
+    edge: Option<Edge>,
+    options: &TreeOptions,
+    visited: &mut Vec<String>,
+) -> serde_json::Value {
+    let mut node = if visited.contains(&id.to_string()) {
+        serde_json::json!({"id": id, "cycle": true})
+    } else if let Some(issue) = store.issues.get(id) {
+        let mut node = serde_json::json!({
+            "id": issue.id,
+            "title": issue.title,
+            "status": issue.status,
+        });
+        if options.verbose {
+            node["priority"] = serde_json::json!(issue.priority);
+            node["session_id"] = serde_json::json!(issue.session_id);
+        }
+        let child_ids = options.children(store, issue);
+        if options.truncates(visited.len()) && !child_ids.is_empty() {
+            node["truncated"] = serde_json::json!(true);
+        } else {
+            visited.push(id.to_string());
+            let children: Vec<_> = child_ids
+                .into_iter()
+                .map(|child_id| {
+                    build_tree_json(
+                        store,
+                        child_id,
+                        Some(options.edge(store, issue, child_id)),
+                        options,
+                        visited,
+                    )
+                })
+                .collect();
+            visited.pop();
+            node[options.key()] = serde_json::json!(children);
+        }
+        node
+    } else {
+        serde_json::json!({"id": id, "missing": true})
+    };
+
+    if let Some(edge) = edge {
+        node["dep_type"] = serde_json::json!(edge.dep_type);
+        node["hard"] = serde_json::json!(edge.hard);
+    }
+    node
+}
+
+/// Who is already on an issue, for `--verbose` lines: the claiming session
+/// (truncated), else the assignee, else nothing.
+fn claim_tag(issue: &Issue) -> String {
+    match (&issue.session_id, &issue.assignee) {
+        (Some(session), _) => format!(" [session {}]", truncate(session, 16)),
+        (None, Some(assignee)) => format!(" [assignee {}]", assignee),
+        (None, None) => String::new(),
+    }
+}
+
+/// Edge label appended to a tree line; plain `blocks` edges stay unlabeled.
+fn edge_tag(edge: Option<Edge>) -> String {
+    match edge.and_then(|e| e.label()) {
+        Some(label) => format!(" ({})", label),
+        None => String::new(),
+    }
+}
+
+fn print_tree_node(
+    store: &Store,
+    issue: &Issue,
+    edge: Option<Edge>,
+    options: &TreeOptions,
+    prefix: &str,
+    is_last: bool,
+    visited: &mut Vec<String>,
+) {
+    // Only the root is reached without an edge
+    let is_root = edge.is_none();
+    let mut status_tag = paint(
+        match issue.status {
+            Status::Open => "[OPEN]",
+            Status::InProgress => "[IN_PROGRESS]",
+            Status::Closed => "[CLOSED]",
+        },
+        status_color(&issue.status),
+    );
+    if options.verbose {
+        status_tag = format!(
+            "{} {}{}",
+            status_tag,
+            paint(
+                &format!("P{}", issue.priority),
+                priority_color(issue.priority)
+            ),
+            claim_tag(issue)
+        );
+    }
+    let edge_label = edge_tag(edge);
+
+    if visited.contains(&issue.id) {
+        if is_root {
+            println!("{}: {} [CYCLE]", issue.id, truncate(&issue.title, 30));
+        } else {
+            let connector = if is_last { "└── " } else { "├── " };
+            println!(
+                "{}{}{}: {} [CYCLE]{}",
+                prefix,
+                connector,
+                issue.id,
+                truncate(&issue.title, 30),
+                edge_label
+            );
+        }
+        return;
+    }
+    visited.push(issue.id.clone());
+
+    if is_root {
+        println!(
+            "{}: {} {}",
+            issue.id,
+            truncate(&issue.title, 30),
+            status_tag
+        );
+    } else {
+        let connector = if is_last { "└── " } else { "├── " };
+        println!(
+            "{}{}{}: {} {}{}",
+            prefix,
+            connector,
+            issue.id,
+            truncate(&issue.title, 30),
+            status_tag,
+            edge_label
+        );
+    }
+
+    let new_prefix = if is_root {
+        "".to_string()
+    } else if is_last {
+        format!("{}    ", prefix)
+    } else {
+        format!("{}│   ", prefix)
+    };
+
+    let blockers = options.children(store, issue);
+    // `visited` already includes this node
+    if options.truncates(visited.len() - 1) && !blockers.is_empty() {
+        println!("{}└── ...", new_prefix);
+        visited.pop();
+        return;
+    }
+    for (i, &blocker_id) in blockers.iter().enumerate() {
+        let is_last_child = i == blockers.len() - 1;
+        let child_edge = Some(options.edge(store, issue, blocker_id));
+        if let Some(blocker) = store.issues.get(blocker_id) {
+            print_tree_node(
+                store,
+                blocker,
+                child_edge,
+                options,
+                &new_prefix,
+                is_last_child,
+                visited,
+            );
+        } else {
+            let child_connector = if is_last_child {
+                "└── "
+            } else {
+                "├── "
+            };
+            println!(
+                "{}{}{} [MISSING]{}",
+                new_prefix,
+                child_connector,
+                blocker_id,
+                edge_tag(child_edge)
+            );
+        }
+    }
+
+    visited.pop();
+}
+
+fn cmd_parent(store: &mut Store, id: &str, epic: &str, json_output: bool) -> Result<(), BaError> {
+    let issue = store.set_parent(id, epic)?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issue).unwrap());
+    } else {
+        say!(
+            "{} is now a child of {}",
+            issue.id,
+            issue.parent.as_deref().unwrap_or_default()
+        );
+    }
+
+    Ok(())
+}
+
+/// Take `id` out of `epic`; the epic must be its current parent.
+fn cmd_unparent(store: &mut Store, id: &str, epic: &str, json_output: bool) -> Result<(), BaError> {
+    let epic = store.resolve_id(epic)?;
+    let issue = store.unparent(id, &epic)?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issue).unwrap());
+    } else {
+        say!("{} is no longer a child of {}", issue.id, epic);
+    }
+
+    Ok(())
+}
+
+/// `ba deps`: every kind of link, routed to the command that owns it so the
+/// symmetric fields and cycle checks stay in one place per link type.
+fn cmd_deps(
+    store: &mut Store,
+    id: &str,
+    action: DepsAction,
+    json_output: bool,
+) -> Result<(), BaError> {
+    match action {
+        DepsAction::Add {
+            target,
+            dep_type,
+            soft,
+            force,
+        } => match target {
+            DepsTarget {
+                blocked_by: Some(blocker),
+                ..
+            } => cmd_block(store, id, &blocker, &dep_type, soft, force, json_output),
+            DepsTarget {
+                blocks: Some(blocked),
+                ..
+            } => cmd_block(store, &blocked, id, &dep_type, soft, force, json_output),
+            DepsTarget {
+                related: Some(other),
+                ..
+            } => cmd_relate(store, id, &other, true, json_output),
+            DepsTarget {
+                parent: Some(epic), ..
+            } => cmd_parent(store, id, &epic, json_output),
+            _ => unreachable!("clap requires one link target"),
+        },
+        DepsAction::Rm { target } => match target {
+            DepsTarget {
+                blocked_by: Some(blocker),
+                ..
+            } => cmd_unblock(store, id, &blocker, json_output),
+            DepsTarget {
+                blocks: Some(blocked),
+                ..
+            } => cmd_unblock(store, &blocked, id, json_output),
+            DepsTarget {
+                related: Some(other),
+                ..
+            } => cmd_relate(store, id, &other, false, json_output),
+            DepsTarget {
+                parent: Some(epic), ..
+            } => cmd_unparent(store, id, &epic, json_output),
+            _ => unreachable!("clap requires one link target"),
+        },
+    }
+}
+
+fn cmd_children(store: &Store, epic: &str, json_output: bool) -> Result<(), BaError> {
+    let epic = &store.resolve_id(epic)?;
+
+    let mut children: Vec<_> = store
+        .issues
+        .values()
+        .filter(|i| i.parent.as_ref() == Some(epic))
+        .collect();
+
+    children.sort_by(|a, b| a.cmp_priority(b));
+
+    if json_output {
+        println!("{}", serde_json::to_string(&children).unwrap());
+        return Ok(());
+    }
+
+    if children.is_empty() {
+        println!("{} has no children", epic);
+        return Ok(());
+    }
+
+    println!();
+    print_issue_table(&children, &Column::DEFAULT);
+
+    let closed = children
+        .iter()
+        .filter(|i| i.status == Status::Closed)
+        .count();
+    println!();
+    println!(
+        "{} child issue(s) of {} ({} closed)",
+        children.len(),
+        epic,
+        closed
+    );
+
+    Ok(())
+}
+
+fn cmd_progress(store: &Store, epic: &str, json_output: bool) -> Result<(), BaError> {
+    let epic = &store.resolve_id(epic)?;
+    if !matches!(store.issues[epic].issue_type, IssueType::Epic) {
+        return Err(format!(
+            "{} is a {}, not an epic",
+            epic, store.issues[epic].issue_type
+        )
+        .into());
+    }
+
+    let children: Vec<_> = store
+        .issues
+        .values()
+        .filter(|i| i.parent.as_ref() == Some(epic))
+        .collect();
+    let count = |status: Status| children.iter().filter(|i| i.status == status).count();
+    let (open, in_progress, closed) = (
+        count(Status::Open),
+        count(Status::InProgress),
+        count(Status::Closed),
+    );
+    let total = children.len();
+    let percent = (closed * 100).checked_div(total).unwrap_or(0);
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({
+                "total": total,
+                "closed": closed,
+                "in_progress": in_progress,
+                "open": open,
+                "percent": percent,
+            })
+        );
+        return Ok(());
+    }
+
+    println!("epic {}: {}/{} closed ({}%)", epic, closed, total, percent);
+    println!("  open:        {}", open);
+    println!("  in_progress: {}", in_progress);
+    println!("  closed:      {}", closed);
+
+    Ok(())
+}
+
+fn cmd_cycles(store: &Store, open_only: bool, json_output: bool) -> Result<(), BaError> {
+    let unbaue_cycles = store.detect_cycles(open_only);
+
+    if json_output {
+        println!("{}", serde_json::to_string(&unbaue_cycles).unwrap());
+        return Ok(());
+    }
+
+    if unbaue_cycles.is_empty() {
+        println!("No cycles detected.");
+    } else {
+        println!("Found {} cycle(s):", unbaue_cycles.len());
+        for (i, cycle) in unbaue_cycles.iter().enumerate() {
+            println!("  {}. {} -> {}", i + 1, cycle.join(" -> "), cycle[0]);
+        }
+    }
+
+    Ok(())
+}
+
+/// Break every cycle by removing edges, one per pass, re-detecting after each
+/// removal. Edges carry no timestamp of their own, so the edge cut is the one
+/// whose blocked issue has the oldest `updated_at`: the least active claim.
+fn cmd_break_cycles(store: &mut Store, open_only: bool, json_output: bool) -> Result<(), BaError> {
+    let mut removed: Vec<(String, String, Vec<String>)> = vec![];
+
+    while let Some(cycle) = store.detect_cycles(open_only).into_iter().next() {
+        // cycle[i] is blocked_by cycle[i + 1], wrapping around
+        let (id, blocker) = (0..cycle.len())
+            .map(|i| (&cycle[i], &cycle[(i + 1) % cycle.len()]))
+            .min_by_key(|(id, _)| (store.issues[*id].updated_at, (*id).clone()))
+            .map(|(id, blocker)| (id.clone(), blocker.clone()))
+            .expect("cycles are non-empty");
+        store.remove_block(&id, &blocker, Utc::now());
+        removed.push((id, blocker, cycle));
+    }
+
+    if !removed.is_empty() {
+        store.save()?;
+    }
+
+    if json_output {
+        let removed: Vec<_> = removed
+            .iter()
+            .map(|(id, blocker, cycle)| {
+                serde_json::json!({"id": id, "blocker": blocker, "cycle": cycle})
+            })
+            .collect();
+        println!("{}", serde_json::json!({ "removed": removed }));
+        return Ok(());
+    }
+
+    if removed.is_empty() {
+        say!("No cycles detected.");
+    } else {
+        for (id, blocker, cycle) in &removed {
+            say!(
+                "{} no longer blocked by {} (broke cycle {} -> {})",
+                id,
+                blocker,
+                cycle.join(" -> "),
+                cycle[0]
+            );
+        }
+        say!("Broke {} cycle(s)", removed.len());
+    }
+
+    Ok(())
+}
+
+fn cmd_claim(
+    store: &mut Store,
+    id: &str,
+    session: &str,
+    ttl: Option<u32>,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let Claim {
+        issue,
+        taken_from,
+        reopened_from,
+    } = store.claim(id, session, ttl)?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issue).unwrap());
+    } else {
+        say!("Claimed {} for session {}", issue.id, session);
+        if let Some(old) = taken_from {
+            say!("  (took over from {}, whose lease had expired)", old);
+        }
+        if let Some(expires) = issue.claim_expires_at {
+            say!("  Lease expires {}", expires.format("%Y-%m-%d %H:%M"));
+        }
+    }
+
+    if let Some(closed_at) = reopened_from {
+        warn_acted_on_dependents(store, &issue, closed_at);
+    }
+
+    Ok(())
+}
+
+/// Advisory only (stderr, so `--json` output stays parseable): nothing is
+/// changed on the dependents, the operator decides what to do with them.
+fn warn_acted_on_dependents(store: &Store, issue: &Issue, closed_at: DateTime<Utc>) {
+    let acted = store.acted_on_dependents(issue, closed_at);
+    if acted.is_empty() {
+        return;
+    }
+    eprintln!(
+        "Warning: {} was reopened; {} dependent(s) were acted on after it closed:",
+        issue.id,
+        acted.len()
+    );
+    for dep in acted {
+        let session = dep
+            .session_id
+            .as_deref()
+            .map(|s| format!(", session {}", s))
+            .unwrap_or_default();
+        eprintln!(
+            "  {} [{}{}] {}",
+            dep.id,
+            dep.status,
+            session,
+            truncate(&dep.title, 40)
+        );
+    }
+}
+
+/// Claim, then print the full issue so the agent starts with its context.
+fn cmd_work(store: &mut Store, id: &str, session: &str, json_output: bool) -> Result<(), BaError> {
+    let id = store.resolve_id(id)?;
+    cmd_claim(store, &id, session, None, json_output)?;
+
+    if !json_output {
+        print_issue_detail(store, &store.issues[&id]);
+    }
+
+    Ok(())
+}
+
+fn cmd_reassign(
+    store: &mut Store,
+    id: &str,
+    from: String,
+    to: String,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let issue = store.reassign(id, &from, &to)?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issue).unwrap());
+    } else {
+        say!("Reassigned {} from {} to {}", issue.id, from, to);
+    }
+
+    Ok(())
+}
+
+fn cmd_release(store: &mut Store, id: &str, json_output: bool) -> Result<(), BaError> {
+    let (issue, old_session) = store.release(id)?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issue).unwrap());
+    } else {
+        say!("Released {} (was claimed by {})", issue.id, old_session);
+    }
+
+    Ok(())
+}
+
+fn cmd_finish(
+    store: &mut Store,
+    id: &str,
+    reason: Option<String>,
+    show_unblocked: bool,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let (issue, old_session) = store.finish(id, reason)?;
+    store.save()?;
+
+    // Dependents whose last open blocker was this issue
+    let unblocked = store.newly_ready(&issue);
+
+    if json_output {
+        if show_unblocked {
+            let mut value = serde_json::to_value(&issue).unwrap();
+            value["unblocked"] = serde_json::json!(unblocked);
+            println!("{}", value);
+        } else {
+            println!("{}", serde_json::to_string(&issue).unwrap());
+        }
+        return Ok(());
+    }
+
+    say!("Finished {} (was claimed by {})", issue.id, old_session);
+    if show_unblocked {
+        if unblocked.is_empty() {
+            println!("Nothing newly ready.");
+        } else {
+            println!("Now ready:");
+            for dep in unblocked {
+                println!(
+                    "  {}  P{}  {}",
+                    dep.id,
+                    dep.priority,
+                    truncate(&dep.title, 50)
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_reopen(store: &mut Store, id: &str, json_output: bool) -> Result<(), BaError> {
+    let id = &store.resolve_id(id)?;
+    let closed_at = store.issues[id].closed_at;
+    let issue_clone = store.apply_transition(id, Transition::Reopen)?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issue_clone).unwrap());
+    } else {
+        say!("Reopened {}", id);
+    }
+
+    if let Some(closed_at) = closed_at {
+        warn_acted_on_dependents(store, &issue_clone, closed_at);
+    }
+
+    Ok(())
+}
+
+/// Set or clear `archived`. Only closed issues can be archived.
+fn cmd_archive(
+    store: &mut Store,
+    id: &str,
+    archive: bool,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let issue = store.set_archived(id, archive)?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issue).unwrap());
+    } else {
+        say!(
+            "{} {}",
+            if archive { "Archived" } else { "Unarchived" },
+            issue.id
+        );
+    }
+
+    Ok(())
+}
+
+/// Release every in-progress issue whose lease has lapsed back to the pool.
+fn cmd_reap(store: &mut Store, json_output: bool) -> Result<(), BaError> {
+    let reaped = store.reap()?;
+    if !reaped.is_empty() {
+        store.save()?;
+    }
+
+    if json_output {
+        let ids: Vec<_> = reaped.iter().map(|(id, _)| id).collect();
+        println!(
+            "{}",
+            serde_json::json!({ "reaped": reaped.len(), "ids": ids })
+        );
+    } else {
+        for (id, session) in &reaped {
+            say!("Released {} (lease of {} expired)", id, session);
+        }
+        say!("Reaped {} expired claim(s)", reaped.len());
+    }
+
+    Ok(())
+}
+
+fn cmd_mine(store: &Store, session: &str, page: &Page, json_output: bool) -> Result<(), BaError> {
+    // A lapsed lease is up for grabs, so it no longer counts as ours
+    let now = Utc::now();
+    let mut mine: Vec<_> = store
+        .issues
+        .values()
+        .filter(|i| i.session_id.as_deref() == Some(session) && !i.lease_expired(now))
+        .collect();
+
+    mine.sort_by(|a, b| a.cmp_priority(b));
+    let total = page.apply(&mut mine);
+
+    if json_output {
+        page.print_json(total, &mine);
+        return Ok(());
+    }
+
+    if mine.is_empty() {
+        match page.note(0, total) {
+            Some(note) => println!("{}", note),
+            None => println!("No issues claimed by session {}", session),
+        }
+        return Ok(());
+    }
+
+    println!();
+    println!("  {:<8} {:>2}  {:<8} TITLE", "ID", "P", "TYPE");
+    println!("  {}", "-".repeat(60));
+
+    for issue in &mine {
+        println!(
+            "  {:<8} {:>2}  {:<8} {}",
+            issue.id,
+            issue.priority,
+            issue.issue_type,
+            truncate(&issue.title, 40)
+        );
+    }
+
+    println!();
+    println!("{} issue(s) claimed by session {}", mine.len(), session);
+    if let Some(note) = page.note(mine.len(), total) {
+        println!("{}", note);
+    }
+
+    Ok(())
+}
+
+fn cmd_whoami(store: &Store, session: &str, json_output: bool) -> Result<(), BaError> {
+    // Held means what `mine` lists: a lapsed lease is up for grabs
+    let now = Utc::now();
+    let mut held: Vec<_> = store
+        .issues
+        .values()
+        .filter(|i| i.session_id.as_deref() == Some(session) && !i.lease_expired(now))
+        .collect();
+
+    held.sort_by(|a, b| a.cmp_priority(b));
+
+    let blocked_count = held
+        .iter()
+        .filter(|i| !store.open_blockers(i).is_empty())
+        .count();
+    let finishable = held.len() - blocked_count;
+    let ready_to_claim = store.issues.values().filter(|i| store.is_ready(i)).count();
+
+    let suggestion = if held.is_empty() {
+        if ready_to_claim > 0 {
+            format!(
+                "{} issue(s) ready to claim - run 'ba ready'",
+                ready_to_claim
+            )
+        } else {
+            "Nothing ready to claim".to_string()
+        }
+    } else {
+        format!("{} ready to finish, {} blocked", finishable, blocked_count)
+    };
+
+    if json_output {
+        let issues: Vec<_> = held
+            .iter()
+            .map(|i| {
+                serde_json::json!({
+                    "id": i.id,
+                    "title": i.title,
+                    "priority": i.priority,
+                    "status": i.status,
+                    "blocked": !store.open_blockers(i).is_empty(),
+                    "open_blockers": store.open_blockers(i),
+                })
+            })
+            .collect();
+        println!(
+            "{}",
+            serde_json::json!({
+                "session": session,
+                "claimed": held.len(),
+                "ready_to_finish": finishable,
+                "blocked": blocked_count,
+                "ready_to_claim": ready_to_claim,
+                "suggestion": suggestion,
+                "issues": issues,
+            })
+        );
+        return Ok(());
+    }
+
+    println!();
+    println!("Session {}: {} issue(s) claimed", session, held.len());
+
+    if !held.is_empty() {
+        println!();
+        println!("  {:<8} {:>2}  {:<40} STATE", "ID", "P", "TITLE");
+        println!("  {}", "-".repeat(70));
+        for issue in &held {
+            let open_blockers = store.open_blockers(issue);
+            let state = if open_blockers.is_empty() {
+                "ready to finish".to_string()
+            } else {
+                format!(
+                    "blocked by {}",
+                    open_blockers
+                        .iter()
+                        .map(|s| s.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            };
+            println!(
+                "  {:<8} {:>2}  {:<40} {}",
+                issue.id,
+                issue.priority,
+                truncate(&issue.title, 40),
+                state
+            );
+        }
+    }
+
+    println!();
+    println!("Suggestion: {}", suggestion);
+
+    Ok(())
+}
+
+fn cmd_label(
+    store: &mut Store,
+    id: &str,
+    action: &str,
+    label: &str,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let (issue, reprioritized) = match action {
+        "add" => store.add_label(id, label)?,
+        "remove" => (store.remove_label(id, label)?, None),
+        _ => {
+            return Err(format!("Unknown action: {} (use 'add' or 'remove')", action).into());
+        }
+    };
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issue).unwrap());
+    } else {
+        say!(
+            "{} label '{}' {} {}",
+            if action == "add" { "Added" } else { "Removed" },
+            label,
+            if action == "add" { "to" } else { "from" },
+            issue.id
+        );
+        if let Some(old) = reprioritized {
+            say!(
+                "Priority of {}: P{} -> P{} (label-priority rule for '{}')",
+                issue.id,
+                old,
+                issue.priority,
+                label
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_label_query(
+    store: &mut Store,
+    query: &str,
+    action: &str,
+    label: &str,
+    dry_run: bool,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let adding = match action {
+        "add" => true,
+        "remove" => false,
+        _ => {
+            return Err(format!("Unknown action: {} (use 'add' or 'remove')", action).into());
+        }
+    };
+    let parsed = Query::parse(query)?;
+
+    let (
+        matched,
+        Relabel {
+            affected,
+            reprioritized,
+        },
+    ) = store.label_query(&parsed, label, adding, dry_run)?;
+    if !dry_run && !affected.is_empty() {
+        store.save()?;
+    }
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({
+                "query": query,
+                "action": action,
+                "label": label,
+                "matched": matched,
+                "affected": affected,
+                "count": affected.len(),
+                "reprioritized": reprioritized
+                    .iter()
+                    .map(|(id, from, to)| serde_json::json!({"id": id, "from": from, "to": to}))
+                    .collect::<Vec<_>>(),
+                "dry_run": dry_run
+            })
+        );
+    } else {
+        let verb = match (adding, dry_run) {
+            (true, false) => "Added",
+            (true, true) => "Would add",
+            (false, false) => "Removed",
+            (false, true) => "Would remove",
+        };
+        say!(
+            "{} label '{}' {} {} issue(s) ({} matched query)",
+            verb,
+            label,
+            if adding { "to" } else { "from" },
+            affected.len(),
+            matched
+        );
+        for id in &affected {
+            say!("  {}", id);
+        }
+        for (id, from, to) in &reprioritized {
+            say!(
+                "Priority of {}: P{} -> P{} (label-priority rule for '{}')",
+                id,
+                from,
+                to,
+                label
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_labels(store: &Store, json_output: bool) -> Result<(), BaError> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for issue in store.issues.values() {
+        for label in &issue.labels {
+            *counts.entry(label).or_default() += 1;
+        }
+    }
+
+    if json_output {
+        println!("{}", serde_json::to_string(&counts).unwrap());
+        return Ok(());
+    }
+
+    if counts.is_empty() {
+        println!("No labels.");
+        return Ok(());
+    }
+
+    // Most used first; ties alphabetical so the output is stable
+    let mut counts: Vec<(&str, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    let width = counts
+        .iter()
+        .map(|(label, _)| label.len())
+        .max()
+        .unwrap_or(0);
+    for (label, count) in counts {
+        println!("{:<width$}  {}", label, count, width = width);
+    }
+
+    Ok(())
+}
+
+/// Rename a label on every issue that has it, in one save.
+fn cmd_label_rename(
+    store: &mut Store,
+    old: &str,
+    new: &str,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let Relabel {
+        affected,
+        reprioritized,
+    } = store.rename_label(old, new)?;
+
+    if !affected.is_empty() {
+        store.save()?;
+    }
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({
+                "old": old,
+                "new": new,
+                "affected": affected,
+                "count": affected.len(),
+                "reprioritized": reprioritized
+                    .iter()
+                    .map(|(id, from, to)| serde_json::json!({"id": id, "from": from, "to": to}))
+                    .collect::<Vec<_>>(),
+            })
+        );
+    } else {
+        say!(
+            "Renamed label '{}' -> '{}' on {} issue(s)",
+            old,
+            new,
+            affected.len()
+        );
+        for (id, from, to) in &reprioritized {
+            say!(
+                "Priority of {}: P{} -> P{} (label-priority rule for '{}')",
+                id,
+                from,
+                to,
+                new
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_priority(
+    store: &mut Store,
+    id: &str,
+    value: u8,
+    bump_blockers: bool,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let PriorityChange {
+        issue,
+        from,
+        bumped,
+    } = store.set_priority(id, value, bump_blockers)?;
+    store.save()?;
+
+    if json_output {
+        if bump_blockers {
+            let bumped: Vec<_> = bumped
+                .iter()
+                .map(|(blocker_id, from)| {
+                    serde_json::json!({"id": blocker_id, "from": from, "to": value})
+                })
+                .collect();
+            println!("{}", serde_json::json!({"issue": issue, "bumped": bumped}));
+        } else {
+            println!("{}", serde_json::to_string(&issue).unwrap());
+        }
+    } else {
+        say!("Priority {} -> {} for {}", from, value, issue.id);
+        for (blocker_id, from) in &bumped {
+            say!(
+                "  Priority {} -> {} for blocker {}",
+                from,
+                value,
+                blocker_id
+            );
+        }
+        if bump_blockers && bumped.is_empty() {
+            say!("  No open blockers needed bumping");
+        }
+    }
+
+    Ok(())
+}
+
+/// Apply the `escalate-after` / `decay-after` rules (see [`Store::groom`]).
+fn cmd_groom(store: &mut Store, json_output: bool) -> Result<(), BaError> {
+    let changes = store.groom()?;
+    if !changes.is_empty() {
+        store.save()?;
+    }
+
+    if json_output {
+        println!("{}", serde_json::to_string(&changes).unwrap());
+    } else if changes.is_empty() {
+        say!("Nothing to groom");
+    } else {
+        for change in &changes {
+            say!(
+                "Priority {} -> {} for {} ({}, idle {}d): {}",
+                change.from,
+                change.to,
+                change.id,
+                if change.to < change.from {
+                    "escalated"
+                } else {
+                    "decayed"
+                },
+                change.idle_days,
+                change.title
+            );
+        }
+        say!("Groomed {} issue(s)", changes.len());
+    }
+
+    Ok(())
+}
+
+/// Order `id` just before or after `other` (see [`Store::rank`]).
+fn cmd_rank(
+    store: &mut Store,
+    id: &str,
+    other: &str,
+    before: bool,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let other = store.resolve_id(other)?;
+    let issue = store.rank(id, &other, before)?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issue).unwrap());
+    } else {
+        say!(
+            "Ranked {} {} {}",
+            issue.id,
+            if before { "before" } else { "after" },
+            other
+        );
+    }
+
+    Ok(())
+}
+
+fn cmd_due(
+    store: &mut Store,
+    id: &str,
+    date: Option<&str>,
+    json_output: bool,
+) -> Result<(), BaError> {
+    // Dates are days, not instants: pin them to midnight UTC
+    let due = match date {
+        Some(date) => Some(
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| format!("Invalid date: {} (expected YYYY-MM-DD)", date))?
+                .and_time(NaiveTime::MIN)
+                .and_utc(),
+        ),
+        None => None,
+    };
+
+    let issue = store.set_due(id, due)?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issue).unwrap());
+    } else if let Some(due) = due {
+        say!("{} is due {}", issue.id, due.format("%Y-%m-%d"));
+    } else {
+        say!("Cleared due date for {}", issue.id);
+    }
+
+    Ok(())
+}
+
+fn cmd_assign(
+    store: &mut Store,
+    id: &str,
+    name: Option<String>,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let issue = store.assign(id, name)?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issue).unwrap());
+    } else if let Some(assignee) = &issue.assignee {
+        say!("Assigned {} to {}", issue.id, assignee);
+    } else {
+        say!("Cleared assignee for {}", issue.id);
+    }
+
+    Ok(())
+}
+
+fn cmd_estimate(store: &mut Store, id: &str, value: u32, json_output: bool) -> Result<(), BaError> {
+    let issue = store.set_estimate(id, value)?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issue).unwrap());
+    } else {
+        say!("Estimate for {} set to {}", issue.id, value);
+    }
+
+    Ok(())
+}
+
+fn cmd_start(store: &mut Store, id: &str, json_output: bool) -> Result<(), BaError> {
+    let issue = store.start_timer(id)?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issue).unwrap());
+    } else {
+        say!("Started timer for {}", issue.id);
+    }
+
+    Ok(())
+}
+
+fn cmd_stop(store: &mut Store, id: &str, json_output: bool) -> Result<(), BaError> {
+    let (issue, interval) = store.stop_timer(id)?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&issue).unwrap());
+    } else {
+        say!(
+            "Stopped timer for {} ({}, {} total)",
+            issue.id,
+            format_duration(interval),
+            format_duration(issue.tracked_time(Utc::now()))
+        );
+    }
+
+    Ok(())
+}
+
+fn cmd_comment(
+    store: &mut Store,
+    id: &str,
+    text: &str,
+    author: &str,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let issue = store.add_comment(id, author, text)?;
+    let comment = issue.comments.last().expect("just added");
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(comment).unwrap());
+    } else {
+        say!(
+            "Added comment to {} ({} comments total)",
+            issue.id,
+            issue.comments.len()
+        );
+    }
+
+    Ok(())
+}
+
+fn cmd_comment_edit(
+    store: &mut Store,
+    id: &str,
+    index: usize,
+    text: &str,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let (issue, comment) = store.edit_comment(id, index, text)?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&comment).unwrap());
+    } else {
+        say!("Edited comment {} on {}", index, issue.id);
+    }
+
+    Ok(())
+}
+
+fn cmd_comment_delete(
+    store: &mut Store,
+    id: &str,
+    index: usize,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let (issue, removed) = store.delete_comment(id, index)?;
+    store.save()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&removed).unwrap());
+    } else {
+        say!(
+            "Deleted comment {} from {} ({} comments left)",
+            index,
+            issue.id,
+            issue.comments.len()
+        );
+    }
+
+    Ok(())
+}
+
+fn cmd_import(
+    store: &mut Store,
+    file: &Path,
+    keep_ids: bool,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let file_handle =
+        File::open(file).map_err(|e| format!("Failed to open '{}': {}", file.display(), e))?;
+    let ImportReport {
+        imported,
+        skipped,
+        errors,
+    } = store.import_beads(BufReader::new(file_handle), keep_ids)?;
+
+    store.save()?;
+
+    if json_output {
+        println!(
+            r#"{{"imported":{},"skipped":{},"errors":{}}}"#,
+            imported,
+            skipped,
+            errors.len()
+        );
+    } else {
+        say!(
+            "Imported {} issues ({} skipped, {} errors)",
+            imported,
+            skipped,
+            errors.len()
+        );
+        if !errors.is_empty() {
+            println!();
+            println!("Errors:");
+            for err in &errors {
+                println!("  {}", err);
+            }
+        }
     }
 
-    fs::create_dir_all(ac_dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+    Ok(())
+}
 
-    // Generate prefix from current directory hash
-    let cwd =
-        std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
-    let cwd_str = cwd.to_string_lossy();
-    let mut hasher = Sha256::new();
-    hasher.update(cwd_str.as_bytes());
-    let hash = hasher.finalize();
+/// Import issues written in ba's own storage format (e.g. by `ba export`).
+/// IDs, comments, labels, sessions, and edges are kept verbatim; issues whose
+/// ID already exists in the store are skipped.
+fn cmd_import_native(store: &mut Store, file: &Path, json_output: bool) -> Result<(), BaError> {
+    let file_handle =
+        File::open(file).map_err(|e| format!("Failed to open '{}': {}", file.display(), e))?;
+    let ImportReport {
+        imported,
+        skipped,
+        errors,
+    } = store.import_native(BufReader::new(file_handle))?;
 
-    // Take first 2 chars as base36
-    let prefix: String = hash[0..2]
-        .iter()
-        .map(|b| {
-            let idx = (b % 36) as usize;
-            if idx < 10 {
-                (b'0' + idx as u8) as char
-            } else {
-                (b'a' + (idx - 10) as u8) as char
+    store.save()?;
+
+    if json_output {
+        println!(
+            r#"{{"imported":{},"skipped":{},"errors":{}}}"#,
+            imported,
+            skipped,
+            errors.len()
+        );
+    } else {
+        say!(
+            "Imported {} issues ({} skipped, {} errors)",
+            imported,
+            skipped,
+            errors.len()
+        );
+        if !errors.is_empty() {
+            println!();
+            println!("Errors:");
+            for err in &errors {
+                println!("  {}", err);
             }
-        })
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_export(
+    store: &Store,
+    format: &str,
+    output: Option<&Path>,
+    all: bool,
+    json_output: bool,
+) -> Result<(), BaError> {
+    // Report formats cover open work by default; the native dump is a backup
+    let mut issues: Vec<_> = store
+        .issues
+        .values()
+        .filter(|i| all || i.status != Status::Closed)
         .collect();
+    issues.sort_by(|a, b| a.cmp_priority(b));
 
-    let config = Config { version: 1, prefix };
-    let config_path = ac_dir.join(CONFIG_FILE);
-    let config_json = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
-    fs::write(&config_path, config_json).map_err(|e| format!("Failed to write config: {}", e))?;
+    let report = match format {
+        "native-jsonl" | "native" => None,
+        "markdown" | "md" => Some(render_markdown(&issues)),
+        "csv" => Some(render_csv(&issues)),
+        other => {
+            return Err(format!(
+                "Unknown export format: {} (valid: native-jsonl, markdown, csv)",
+                other
+            )
+            .into());
+        }
+    };
 
-    // Create empty issues file
-    let issues_path = ac_dir.join(ISSUES_FILE);
-    File::create(&issues_path).map_err(|e| format!("Failed to create issues file: {}", e))?;
+    if let Some(report) = report {
+        return write_report(&report, issues.len(), output, json_output);
+    }
+
+    let Some(path) = output else {
+        // Same bytes as issues.jsonl: compact, sorted by ID
+        let mut sorted: Vec<_> = store.issues.values().collect();
+        sorted.sort_by(|a, b| a.id.cmp(&b.id));
+        for issue in sorted {
+            println!("{}", serde_json::to_string(issue).unwrap());
+        }
+        return Ok(());
+    };
+
+    write_issues_atomic(path, store.issues.values())?;
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({"exported": store.issues.len(), "path": path})
+        );
+    } else {
+        println!(
+            "Exported {} issues to {}",
+            store.issues.len(),
+            path.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Print a rendered export to stdout, or write it to `output`.
+fn write_report(
+    report: &str,
+    count: usize,
+    output: Option<&Path>,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let Some(path) = output else {
+        print!("{}", report);
+        return Ok(());
+    };
+
+    if !dry_run() {
+        fs::write(path, report)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+
+    if json_output {
+        println!("{}", serde_json::json!({"exported": count, "path": path}));
+    } else {
+        println!("Exported {} issues to {}", count, path.display());
+    }
+
+    Ok(())
+}
+
+/// Markdown status report: one `##` section per status, one checkbox per issue.
+fn render_markdown(issues: &[&Issue]) -> String {
+    let mut out = String::from("# Issues\n");
+
+    for (status, heading) in [
+        (Status::Open, "Open"),
+        (Status::InProgress, "In progress"),
+        (Status::Closed, "Closed"),
+    ] {
+        let section: Vec<_> = issues.iter().filter(|i| i.status == status).collect();
+        if section.is_empty() {
+            continue;
+        }
+
+        out.push_str(&format!("\n## {} ({})\n\n", heading, section.len()));
+        for issue in section {
+            let check = if issue.status == Status::Closed {
+                "x"
+            } else {
+                " "
+            };
+            out.push_str(&format!(
+                "- [{}] `{}` {} (P{})",
+                check, issue.id, issue.title, issue.priority
+            ));
+            for label in &issue.labels {
+                out.push_str(&format!(" `{}`", label));
+            }
+            out.push('\n');
+
+            if !issue.blocked_by.is_empty() {
+                let blockers: Vec<_> = issue
+                    .blocked_by
+                    .iter()
+                    .map(|b| format!("`{}`", b))
+                    .collect();
+                out.push_str(&format!("  - blocked by {}\n", blockers.join(", ")));
+            }
+
+            for comment in &issue.comments {
+                out.push_str(&format!(
+                    "\n  > **{}** ({}):\n",
+                    comment.author,
+                    comment.created_at.format("%Y-%m-%d")
+                ));
+                for line in comment.text.lines() {
+                    out.push_str(format!("  > {}", line).trim_end());
+                    out.push('\n');
+                }
+            }
+        }
+    }
+
+    out
+}
+
+/// Spreadsheet export: a header row, then one row per issue.
+fn render_csv(issues: &[&Issue]) -> String {
+    let mut out =
+        String::from("id,title,status,priority,type,labels,created_at,updated_at,closed_at\n");
+
+    for issue in issues {
+        let row = [
+            issue.id.clone(),
+            issue.title.clone(),
+            issue.status.to_string(),
+            issue.priority.to_string(),
+            issue.issue_type.to_string(),
+            issue.labels.join(";"),
+            issue.created_at.to_rfc3339(),
+            issue.updated_at.to_rfc3339(),
+            issue.closed_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+        ];
+        let cells: Vec<_> = row.iter().map(|cell| csv_field(cell)).collect();
+        out.push_str(&cells.join(","));
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Quote a CSV cell when it holds a comma, quote or line break (RFC 4180).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn snapshot_path(store: &Store, name: &str) -> Result<PathBuf, String> {
+    let valid = !name.is_empty()
+        && name != "list"
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && !name.starts_with('.');
+    if !valid {
+        return Err(format!(
+            "Invalid snapshot name: '{}' (use letters, digits, '-', '_', '.'; 'list' is reserved)",
+            name
+        ));
+    }
+    Ok(store
+        .ba_dir
+        .join(SNAPSHOTS_DIR)
+        .join(format!("{}.jsonl", name)))
+}
+
+fn cmd_snapshot(store: &Store, name: &str, force: bool, json_output: bool) -> Result<(), BaError> {
+    let path = snapshot_path(store, name)?;
+    if path.exists() && !force {
+        return Err(format!(
+            "Snapshot '{}' already exists (use --force to overwrite)",
+            name
+        )
+        .into());
+    }
+
+    if !dry_run() {
+        fs::create_dir_all(path.parent().unwrap())
+            .map_err(|e| format!("Failed to create snapshots directory: {}", e))?;
+    }
+    write_issues_atomic(&path, store.issues.values())?;
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({"snapshot": name, "issues": store.issues.len(), "path": path})
+        );
+    } else {
+        println!("Saved snapshot '{}' ({} issues)", name, store.issues.len());
+    }
+
+    Ok(())
+}
+
+fn cmd_snapshot_list(store: &Store, json_output: bool) -> Result<(), BaError> {
+    let dir = store.ba_dir.join(SNAPSHOTS_DIR);
+    let mut snapshots: Vec<(String, DateTime<Utc>)> = vec![];
+    if dir.is_dir() {
+        let entries = fs::read_dir(&dir).map_err(|e| format!("Failed to read snapshots: {}", e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let modified = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .map(DateTime::<Utc>::from)
+                .map_err(|e| format!("Failed to stat snapshot '{}': {}", name, e))?;
+            snapshots.push((name.to_string(), modified));
+        }
+    }
+    snapshots.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+
+    if json_output {
+        let items: Vec<_> = snapshots
+            .iter()
+            .map(|(name, at)| serde_json::json!({"name": name, "created_at": at}))
+            .collect();
+        println!("{}", serde_json::to_string(&items).unwrap());
+        return Ok(());
+    }
+
+    if snapshots.is_empty() {
+        println!("No snapshots.");
+        return Ok(());
+    }
+
+    for (name, at) in &snapshots {
+        println!("  {}  {}", at.format("%Y-%m-%d %H:%M"), name);
+    }
+
+    Ok(())
+}
+
+fn cmd_restore(store: &mut Store, name: &str, yes: bool, json_output: bool) -> Result<(), BaError> {
+    let path = snapshot_path(store, name)?;
+    if !path.exists() {
+        return Err(format!("Snapshot not found: {} (see 'ba snapshot list')", name).into());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read snapshot '{}': {}", name, e))?;
+    let mut restored: HashMap<String, Issue> = HashMap::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let issue: Issue = serde_json::from_str(line).map_err(|e| {
+            format!(
+                "Failed to parse snapshot '{}' at line {}: {}",
+                name,
+                index + 1,
+                e
+            )
+        })?;
+        restored.insert(issue.id.clone(), issue);
+    }
+
+    if !yes {
+        if !std::io::stdin().is_terminal() {
+            return Err("Refusing to restore without confirmation; pass --yes"
+                .to_string()
+                .into());
+        }
+        print!(
+            "Replace {} current issue(s) with snapshot '{}' ({} issues)? [y/N] ",
+            store.issues.len(),
+            name,
+            restored.len()
+        );
+        std::io::stdout()
+            .flush()
+            .map_err(|e| format!("Failed to flush stdout: {}", e))?;
+        let mut answer = String::new();
+        std::io::stdin()
+            .read_line(&mut answer)
+            .map_err(|e| format!("Failed to read confirmation: {}", e))?;
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            return Err("Restore cancelled".into());
+        }
+    }
+
+    let previous = store.issues.len();
+    store.issues = restored;
+    store.save()?;
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({"restored": name, "issues": store.issues.len(), "replaced": previous})
+        );
+    } else {
+        say!(
+            "Restored snapshot '{}' ({} issues, replaced {})",
+            name,
+            store.issues.len(),
+            previous
+        );
+    }
+
+    Ok(())
+}
+
+fn cmd_doctor(store: &mut Store, fix: bool, json_output: bool) -> Result<(), BaError> {
+    let problems = store.integrity_problems();
+    let fixed = if fix && problems.iter().any(|p| p.fixable) {
+        store.repair_integrity();
+        store.save()?;
+        problems.iter().filter(|p| p.fixable).count()
+    } else {
+        0
+    };
+    let remaining = problems.len() - fixed;
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({"problems": problems, "fixed": fixed})
+        );
+    } else if problems.is_empty() {
+        println!("No problems found.");
+    } else {
+        for problem in &problems {
+            let note = match (fix, problem.fixable) {
+                (true, true) => " [fixed]",
+                (_, false) => " [manual]",
+                (false, true) => "",
+            };
+            println!(
+                "  {:<8} {:<20} {}{}",
+                problem.id, problem.kind, problem.detail, note
+            );
+        }
+        println!();
+        if fix {
+            println!("{} problem(s) found, {} fixed", problems.len(), fixed);
+        } else {
+            println!(
+                "{} problem(s) found; run 'ba doctor --fix' to repair the fixable ones",
+                problems.len()
+            );
+        }
+    }
+
+    if remaining > 0 {
+        return Err(format!("{} problem(s) remain", remaining).into());
+    }
+    Ok(())
+}
+
+/// Fold the audit log and report where it disagrees with the store: issues
+/// it never mentions, statuses that drifted, and entries that skip a step.
+fn cmd_replay(store: &Store, log: Option<&Path>, json_output: bool) -> Result<(), BaError> {
+    let entries = match log {
+        Some(path) => read_audit_log(path)?,
+        None => store.audit_entries()?,
+    };
+    let problems = store.audit_problems(&entries);
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({"replayed": entries.len(), "problems": problems})
+        );
+    } else if problems.is_empty() {
+        println!(
+            "Replayed {} entries; the log matches issues.jsonl.",
+            entries.len()
+        );
+    } else {
+        for problem in &problems {
+            println!(
+                "  {:<8} {:<14} {}",
+                problem.id, problem.kind, problem.detail
+            );
+        }
+        println!();
+        println!(
+            "Replayed {} entries; {} mismatch(es) with issues.jsonl",
+            entries.len(),
+            problems.len()
+        );
+    }
 
-    println!(
-        "Initialized {} with prefix '{}'",
-        ac_dir.display(),
-        config.prefix
-    );
+    if !problems.is_empty() {
+        return Err(format!("{} mismatch(es) remain", problems.len()).into());
+    }
     Ok(())
 }
 
-fn cmd_create(
-    store: &mut Store,
-    title: String,
-    issue_type: String,
-    priority: u8,
-    description: String,
-    json_output: bool,
-) -> Result<(), String> {
-    let issue_type: IssueType = issue_type.parse()?;
+/// Git merge driver entry point: merges into `ours` in place.
+fn cmd_merge(ours: &Path, theirs: &Path, base: &Path, json_output: bool) -> Result<(), BaError> {
+    let (merged, conflicts) = merge_stores(
+        &read_issues_file(base)?,
+        &read_issues_file(ours)?,
+        &read_issues_file(theirs)?,
+    );
+    write_issues_atomic(ours, merged.values())?;
 
-    if priority > 4 {
-        return Err("Priority must be 0-4".to_string());
+    if json_output {
+        println!(
+            "{}",
+            serde_json::json!({"issues": merged.len(), "conflicts": conflicts})
+        );
+    } else {
+        println!(
+            "Merged {} issues ({} edited on both sides)",
+            merged.len(),
+            conflicts
+        );
     }
 
-    let now = Utc::now();
-    let id = store.generate_id(&title, &now);
+    Ok(())
+}
 
-    let issue = Issue {
-        id: id.clone(),
-        title,
-        description,
-        status: Status::Open,
-        priority,
-        issue_type,
-        session_id: None,
-        labels: vec![],
-        comments: vec![],
-        created_at: now,
-        updated_at: now,
-        closed_at: None,
-        blocks: vec![],
-        blocked_by: vec![],
-    };
+/// JSON Schema for one `Issue`, as written to issues.jsonl and printed by `--json`.
+// AIDEV-NOTE: hand-maintained mirror of `Issue`, `Comment` and `Edge`; update it
+// whenever a serialized field is added, renamed or changes optionality.
+fn issue_schema() -> serde_json::Value {
+    let timestamp = serde_json::json!({"type": "string", "format": "date-time"});
+    let id_list = serde_json::json!({"type": "array", "items": {"type": "string"}});
+    let comment = serde_json::json!({
+        "type": "object",
+        "required": ["author", "text", "created_at"],
+        "properties": {
+            "author": {"type": "string"},
+            "text": {"type": "string"},
+            "created_at": timestamp,
+            "edited_at": timestamp
+        }
+    });
+    let edge = serde_json::json!({
+        "type": "object",
+        "properties": {
+            "type": {"enum": ["blocks", "discovered-from", "parent-child"]},
+            "hard": {"type": "boolean", "default": true}
+        }
+    });
 
-    store.issues.insert(id.clone(), issue.clone());
-    store.save()?;
+    serde_json::json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "ba issue",
+        "type": "object",
+        "required": [
+            "id", "title", "description", "status", "priority", "issue_type",
+            "labels", "comments", "created_at", "updated_at", "blocks", "blocked_by"
+        ],
+        "properties": {
+            "id": {"type": "string", "description": "{prefix}-{hash}, e.g. ab-x7k2"},
+            "title": {"type": "string"},
+            "description": {"type": "string"},
+            "status": {"enum": ["open", "in_progress", "closed"]},
+            "priority": {
+                "type": "integer",
+                "minimum": 0,
+                "maximum": 4,
+                "description": "0 = critical, 4 = backlog"
+            },
+            "rank": {
+                "type": "number",
+                "description": "Manual order within a priority level; lower first, unranked last"
+            },
+            "issue_type": {"enum": ["task", "epic", "refactor", "spike"]},
+            "session_id": {
+                "type": "string",
+                "description": "Owning session; present exactly when in_progress"
+            },
+            "labels": {"type": "array", "items": {"type": "string"}},
+            "comments": {"type": "array", "items": comment},
+            "created_at": timestamp,
+            "updated_at": timestamp,
+            "closed_at": timestamp,
+            "close_reason": {"type": "string", "description": "Free text from close/finish --reason"},
+            "duplicate_of": {"type": "string", "description": "ID of the canonical issue, set by ba dup"},
+            "archived": {"type": "boolean", "default": false},
+            "blocks": id_list,
+            "blocked_by": id_list,
+            "edges": {
+                "type": "object",
+                "description": "Metadata for blocked_by edges, keyed by blocker ID; plain hard blocks are omitted",
+                "additionalProperties": edge
+            },
+            "related": id_list,
+            "parent": {"type": "string", "description": "ID of the containing epic"},
+            "assignee": {"type": "string", "description": "Person responsible; independent of session_id"},
+            "due": timestamp,
+            "estimate": {"type": "integer", "minimum": 0},
+            "time_entries": {
+                "type": "array",
+                "description": "[start, end] intervals; end is null while the timer runs",
+                "items": {
+                    "type": "array",
+                    "prefixItems": [timestamp, {"oneOf": [timestamp, {"type": "null"}]}],
+                    "minItems": 2,
+                    "maxItems": 2
+                }
+            },
+            "groomed_at": timestamp,
+            "idle_since": timestamp
+        }
+    })
+}
 
+/// `searched` is false when `--dir` was given, so nothing was walked.
+fn cmd_root(dir: &Path, searched: bool, json_output: bool) -> Result<(), BaError> {
+    if !dir.is_dir() {
+        let place = if searched {
+            "here or in any parent"
+        } else {
+            "at that path"
+        };
+        return Err(format!(
+            "No {} directory found {} (run 'ba init')",
+            dir.display(),
+            place
+        )
+        .into());
+    }
+    let dir = dir
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", dir.display(), e))?;
     if json_output {
-        println!("{}", serde_json::to_string(&issue).unwrap());
+        println!("{}", serde_json::json!({ "dir": dir }));
     } else {
-        println!("Created {}", id);
+        println!("{}", dir.display());
     }
-
     Ok(())
 }
 
-fn cmd_list(
-    store: &Store,
-    status_filter: Option<String>,
-    all: bool,
-    json_output: bool,
-) -> Result<(), String> {
-    let mut issues: Vec<_> = store.issues.values().collect();
+fn cmd_schema() {
+    println!("{}", serde_json::to_string_pretty(&issue_schema()).unwrap());
+}
 
-    // Filter
-    if let Some(status) = status_filter {
-        let status = match status.as_str() {
-            "open" => Status::Open,
-            "in_progress" => Status::InProgress,
-            "closed" => Status::Closed,
-            _ => return Err(format!("Unknown status: {}", status)),
-        };
-        issues.retain(|i| i.status == status);
-    } else if !all {
-        issues.retain(|i| i.status != Status::Closed);
-    }
+fn cmd_quickstart() {
+    println!(
+        r#"
+ba - Simple Task Tracking for LLM Sessions
 
-    // Sort by priority, then by created_at
-    issues.sort_by(|a, b| {
-        a.priority
-            .cmp(&b.priority)
-            .then_with(|| a.created_at.cmp(&b.created_at))
-    });
+GETTING STARTED
+  ba init           Initialize ba in your project (creates .ba/)
+  ba root           Print the .ba/ in use (found by walking up, like git)
+  ba schema         JSON Schema of the issue format (for --json consumers)
+  ba doctor         Check issues.jsonl for drift (--fix to repair)
+  ba replay         Check the audit log's statuses against issues.jsonl
+  ba reprefix ba    Switch to a readable ID prefix (renames existing issues)
+  ba config list                        Show project settings (ba config get <key>)
+  ba template edit spike                Description template for new spikes
+                                        (.ba/templates/<type>.md, used when -d is omitted)
+  ba config set default-type refactor   Type for create without -t
+  ba config set default-priority 1      Priority for create without -p
+  ba move <id> --to ../other/.ba   Hand an issue to another repo's tracker
+                    (--with-deps for its dependencies, --keep-id to keep the ID)
+  ba quickstart     Show this guide
+  --no-color        Plain output on a terminal (NO_COLOR works too)
+  -q, --quiet       No success chatter from mutating commands (for scripts)
+  --dry-run         Show what a command would do without writing anything
 
-    if json_output {
-        println!("{}", serde_json::to_string(&issues).unwrap());
-        return Ok(());
-    }
+CREATING ISSUES
+  ba create "Fix login bug" -p 1
+  ba create "Add caching layer" -t refactor -d "Description here"
+  ba create "Research auth options" -t spike -p 2
+  ba create "Cache plan" --description-file plan.md   Long description from a file (- = stdin)
+  ba create "Login 500s" --label bug --blocked-by x7k2
+                                   Labels and blockers in one step (repeatable)
+  ba batch < steps.txt            One subcommand per line, one lock, one save;
+                                   any failure saves nothing. $N = ID created
+                                   on line N (e.g. "block $2 $1")
+  ba create --batch < titles.txt   One per line: a title, or JSON
+                                   {{"title","type","priority","description"}}
+  ba clone x7k2 --title "Same for mobile"
+                                   New open issue with x7k2's type, priority,
+                                   labels and description
 
-    if issues.is_empty() {
-        println!("No issues found.");
-        return Ok(());
-    }
+ISSUE TYPES: task (default), epic, refactor, spike
+PRIORITIES: 0 (critical) → 4 (backlog), default is 2
+IDS: any unique prefix works, with or without the project prefix (x7k2, ab-x7)
 
-    // Pretty print
-    println!();
-    println!(
-        "  {:<8} {:>2}  {:<8} {:<12} {}",
-        "ID", "P", "TYPE", "STATUS", "TITLE"
-    );
-    println!("  {}", "-".repeat(70));
+VIEWING ISSUES
+  ba list           List open/in_progress issues
+  ba list --all     Include closed
+  ba list --status open
+  ba list --status open,in_progress  Any of several statuses (or repeat --status)
+  ba list --max-priority 1           Only P0 and P1 (--priority N for exact)
+  ba list --overdue                  Unfinished issues past their due date
+  ba list --label urgent             Only issues carrying a label
+  ba list --type spike               Only issues of a type
+  ba list --assignee alice           Only issues assigned to someone
+  ba list --since 2h                 Updated in the last 2h (or RFC 3339 / YYYY-MM-DD)
+  ba count --label urgent            Just the number (same filters as list)
+  ba search "login"                  Find issues by title, description or comment
+  ba recent [--limit 10]             Last-touched issues, any status (catching up)
+  ba query "status:open AND (label:api OR priority<=1) AND NOT type:spike"
+                                     Filter with AND/OR/NOT, parentheses, and
+                                     <, <=, >, >=, != on priority; title:x is a
+                                     substring match. Closed issues match too,
+                                     unless the query excludes them. Takes the
+                                     list view flags (--columns, --sort, --limit)
+  ba export --format markdown        Status report for a PR description (--all)
+  ba export --format csv -o out.csv  Spreadsheet export (--all)
+  ba list --columns id,priority,title,updated
+  ba list --sort updated --reverse   Most recently touched first
+                                     (priority, created, updated, id, status)
+  ba list --ndjson                   One JSON issue per line (for jq -c, grep)
+  ba list --limit 10 [--offset 10]   Top ten by sort order (also ready, mine);
+                                     --json --paginate adds {{total, offset, limit}}
+  ba show <id>      Show full details
+  ba show <id> --comments-only   Just the discussion thread
+  ba show <id> --oneline         ID [STATUS] P<n> type title (labels)
+  ba link <id>      Web viewer URL (ba config set web-base-url <url>);
+                    'ba show <id> --web' adds it to the details
+  ba ready          Show issues ready to work on (open + not blocked)
+  ba ready --transitive   Also require the whole upstream chain closed
+  ba ready --type refactor --label backend   Only ready work in your specialty
+  ba ready -v             Show the session or assignee already on each issue
+  ba ready --max-estimate 2   Quick wins (--min-estimate for chunky work)
+  ba blocked        What's stuck, and the open blockers holding each issue
+  ba next           Top ready issue (exits 1 when nothing is ready)
+  ba watch          Live ready list, redrawn on change (--interval ms)
+  ba stats          Backlog health: counts, blocked/ready, oldest open
+  ba stats --aging  Unfinished issues by time since last update (<1d .. >30d)
 
-    for issue in &issues {
-        println!(
-            "  {:<8} {:>2}  {:<8} {:<12} {}",
-            issue.id,
-            issue.priority,
-            issue.issue_type,
-            issue.status,
-            truncate(&issue.title, 40)
-        );
-    }
+OWNERSHIP-BASED WORKFLOW
+  ba claim <id> --session $SESSION    Take ownership (open → in_progress)
+                                      (export BA_SESSION to skip --session; the flag wins)
+  ba claim <id> --session $SESSION --ttl 60
+                                      Claim with a 60-minute lease
+  ba release <id>                     Abandon work (in_progress → open)
+  ba finish <id>                      Complete work (in_progress → closed)
+  ba finish <id> --show-unblocked     ...and list what that made ready
+  ba close <id>                       Close unclaimed issue (escape hatch)
+  ba close <id> --reason wontfix      Keep why (finish takes --reason too)
+  ba close <id> --cascade             ...and blockers nothing else open waits on
+  ba dup <id> --of <canonical>        Close as a duplicate, linked both ways
+  ba bulk-close --label done          Close every unclaimed match (list filters)
+  ba merge-issues <a> <b>             Fold duplicate b into a (text, labels, comments, links), delete b
+  ba reopen <id>                      Reopen closed issue (closed → open, unclaimed)
+  ba archive <id>                     Hide a closed issue even from --all (--archived shows it)
+  ba unarchive <id>                   Undo archive
 
-    let open = issues.iter().filter(|i| i.status == Status::Open).count();
-    let in_progress = issues
-        .iter()
-        .filter(|i| i.status == Status::InProgress)
-        .count();
-    let closed = issues.iter().filter(|i| i.status == Status::Closed).count();
+  Status is a side-effect of ownership transitions, not set directly.
 
-    println!();
-    println!(
-        "{} issues ({} open, {} in_progress, {} closed)",
-        issues.len(),
-        open,
-        in_progress,
-        closed
-    );
+MODIFYING ISSUES
+  ba priority <id> <0-4>              Set priority (0 = critical)
+  ba priority <id> 0 --bump-blockers  Also raise all open blockers to P0
+  ba groom                            Raise open issues idle for escalate-after days
+                                      a level; lower those idle for decay-after
+                                      days (longer) instead. Claimed work stays put
+  ba rank <id> --before <other>       Hand-order within a priority level
+                                      (--after too); list, ready and next
+                                      follow it before falling back to age
+  ba label <id> add urgent            Add a label
+  ba config set label-priority urgent=0,backlog=4
+                                      Adding those labels also sets the priority
+  ba label <id> remove urgent         Remove a label
+  ba label --query "status = closed" add archived [--dry-run]
+                                      Label every issue matching a query
+  ba label rename bug defect          Rename a label on every issue
+  ba labels                           Every label in use, with counts
+  ba comment <id> "text" --author X   Add a comment
+                                      (--author defaults to $BA_AUTHOR, then git user.name)
+  ba comment <id> --edit 0 "text"     Fix comment #0 (numbers as in 'ba show')
+  ba comment <id> --delete 0          Remove comment #0
+  ba due <id> 2025-03-01              Set a due date (--clear to remove)
+  ba estimate <id> 3                  Set an effort estimate (points or hours)
+  ba assign <id> alice                Set the responsible person (--clear to remove)
+
+  ba delete <id>                      Permanently remove (--force if claimed)
+  ba delete --label factory:bloodwork:skeleton
+  ba delete --label-prefix factory:bloodwork:
+  ba delete --label-prefix factory:bloodwork: --force  Delete even open/in_progress
+  ba purge --before 90d [--dry-run]   Drop long-closed issues (kept in archive.jsonl)
 
-    Ok(())
-}
+DEPENDENCIES
+  ba block <id> <blocker>    Mark <id> blocked by <blocker>
+  ba block <id> <blocker> --type discovered-from
+                             Typed edge (blocks, discovered-from, parent-child)
+  ba block <id> <blocker> --soft
+                             Advisory edge: shown, but doesn't gate ready
+  ba block <id> <blocker> --force    Allow an edge that closes a cycle (refused otherwise)
+  ba unblock <id> <blocker>  Remove block
+  ba relate <id> <other>     Non-blocking cross-reference (unrelate to drop)
+  ba deps <id> add|rm --blocked-by|--blocks|--related|--parent <other>
+                             Every link type in one command (the above are shorthands)
+  ba tree <id>               Show dependency tree
+  ba tree <id> --open-only   Hide closed blockers
+  ba tree <id> --blocks      What finishing <id> unblocks (downstream)
+  ba tree <id> --depth 2     Only two levels deep (... marks the cut)
+  ba tree <id> --status open,in_progress   Only descend into these statuses
+  ba tree <id> -v            Add priority and claiming session to each node
+  ba cycles                  Detect circular dependencies
+  ba cycles --open-only      Ignore cycles through closed issues
+  ba cycles --break          Cut the least recently touched edge of each cycle
 
-fn cmd_show(store: &Store, id: &str, json_output: bool) -> Result<(), String> {
-    let issue = store
-        .issues
-        .get(id)
-        .ok_or_else(|| format!("Issue not found: {}", id))?;
+EPICS
+  ba parent <id> <epic>      Put <id> inside an epic
+  ba deps <id> rm --parent <epic>   Take it out again
+  ba children <epic>         List the issues inside an epic
+  ba progress <epic>         Closed/total children, by status
 
-    if json_output {
-        println!("{}", serde_json::to_string_pretty(issue).unwrap());
-        return Ok(());
-    }
+MULTI-AGENT COORDINATION
+  ba claim <id> --session <session_id>  Claim issue for your session
+  ba work <id> --session <session_id>   Claim and print full details in one step
+  ba next --session <session_id> --claim
+                                        Claim the top ready issue in one step
+  ba mine --session <session_id>        Show your claimed issues
+  ba whoami --session <session_id>      Summarize your claims and next step
+  ba reassign <id> --from <old> --to <new>
+                                        Hand your claim to another session
+  ba release <id>                       Release claim (back to pool)
+  ba reap                               Release claims whose --ttl lease expired
+  ba load                               Estimated in-progress work per session
+  ba log [--id <id>]                    Audit log: who changed what, and when
+  ba history <id>                       Status/priority changes across git commits
+  ba diff [<id>]                        Uncommitted changes, field by field (vs git HEAD)
+  ba undo                               Reverse the latest command's status changes (repeatable)
 
-    println!();
-    println!("{}: {}", issue.id, issue.title);
-    println!("{}", "-".repeat(60));
-    println!(
-        "Status:   {:<16} Priority: P{}",
-        issue.status, issue.priority
-    );
-    println!("Type:     {}", issue.issue_type);
-    if let Some(ref session) = issue.session_id {
-        println!("Session:  {}", session);
-    }
-    println!("Created:  {}", issue.created_at.format("%Y-%m-%d %H:%M"));
-    println!("Updated:  {}", issue.updated_at.format("%Y-%m-%d %H:%M"));
-    if let Some(closed_at) = issue.closed_at {
-        println!("Closed:   {}", closed_at.format("%Y-%m-%d %H:%M"));
-    }
-    if !issue.description.is_empty() {
-        println!();
-        println!("Description:");
-        println!("{}", issue.description);
-    }
-    if !issue.blocked_by.is_empty() {
-        println!();
-        println!("Blocked by: {}", issue.blocked_by.join(", "));
-    }
-    if !issue.blocks.is_empty() {
-        println!("Blocks: {}", issue.blocks.join(", "));
-    }
-    if !issue.labels.is_empty() {
-        println!();
-        println!("Labels: {}", issue.labels.join(", "));
-    }
-    if !issue.comments.is_empty() {
-        println!();
-        println!("Comments ({}):", issue.comments.len());
-        for comment in &issue.comments {
-            println!(
-                "  [{}] {}: {}",
-                comment.created_at.format("%Y-%m-%d %H:%M"),
-                comment.author,
-                comment.text
-            );
-        }
-    }
+  Exit codes: 0 ok, 1 other error, 2 bad usage, 3 issue not found,
+  4 ambiguous ID, 5 already claimed (retry later), 6 invalid transition
+  With --json, errors go to stderr as {{"error": "...", "code": "not_found"}}
 
-    Ok(())
-}
+TIME TRACKING
+  ba start <id>                         Start a timer (one running timer per issue)
+  ba stop <id>                          Stop it; 'ba show' sums tracked time
 
-fn cmd_close(
-    store: &mut Store,
-    id: &str,
-    _reason: Option<String>,
-    json_output: bool,
-) -> Result<(), String> {
-    let issue = store
-        .issues
-        .get_mut(id)
-        .ok_or_else(|| format!("Issue not found: {}", id))?;
+  Tip: Use your Claude session ID as --session value
 
-    issue.apply(Transition::Close)?;
+IMPORTING FROM BEADS (bd)
+  ba import .beads/issues.jsonl --keep-ids
 
-    let issue_clone = issue.clone();
-    store.save()?;
+CHECKPOINTS (before risky bulk changes)
+  ba snapshot pre-triage             Save .ba/snapshots/pre-triage.jsonl
+  ba snapshot list                   Show checkpoints
+  ba restore pre-triage              Roll back (asks first; --yes to skip)
 
-    if json_output {
-        println!("{}", serde_json::to_string(&issue_clone).unwrap());
-    } else {
-        println!("Closed {}", id);
-    }
+BACKUP AND RESTORE (lossless, native format)
+  ba export --output backup.jsonl
+  ba import backup.jsonl --format native
 
-    Ok(())
-}
+JSON OUTPUT (for programmatic use)
+  ba --json list
+  ba --json show <id>
+  ba --json ready
+  ba --json list --with-ready   Each issue carries a computed "ready" flag
 
-fn cmd_delete(
-    store: &mut Store,
-    label: Option<String>,
-    label_prefix: Option<String>,
-    force: bool,
-    json_output: bool,
-) -> Result<(), String> {
-    let selector_count = usize::from(label.is_some()) + usize::from(label_prefix.is_some());
-    if selector_count != 1 {
-        return Err(
-            "Provide exactly one selector: --label <label> or --label-prefix <prefix>".to_string(),
-        );
-    }
+TYPICAL WORKFLOW
+  1. ba ready                          # Find unblocked work
+  2. ba claim <id> --session $SESSION  # Claim it (sets in_progress)
+  3. ... do the work ...
+  4. ba finish <id>                    # Complete (clears claim, closes)
 
-    let selector_desc = match (&label, &label_prefix) {
-        (Some(value), None) => format!("label '{}'", value),
-        (None, Some(value)) => format!("label prefix '{}'", value),
-        _ => unreachable!("selector_count validated above"),
-    };
+DISCOVERING NEW WORK
+  1. ba create "Found bug in X" -t bug -p 1
+  2. ba block <current_id> <new_id>    # If it blocks current work
+  3. ba tree <current_id>              # Verify dependency chain
+"#
+    );
+}
 
-    let mut matched_ids: Vec<String> = store
+fn cmd_ready(
+    store: &Store,
+    transitive: bool,
+    filter: &ListFilter,
+    verbose: bool,
+    page: &Page,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let mut memo = HashMap::new();
+    let mut ready: Vec<_> = store
         .issues
         .values()
-        .filter(|issue| match (&label, &label_prefix) {
-            (Some(value), None) => issue.labels.iter().any(|existing| existing == value),
-            (None, Some(prefix)) => issue
-                .labels
-                .iter()
-                .any(|existing| existing.starts_with(prefix)),
-            _ => false,
+        .filter(|issue| {
+            if transitive {
+                store.is_ready_transitive(issue, &mut memo)
+            } else {
+                store.is_ready(issue)
+            }
         })
-        .map(|issue| issue.id.clone())
         .collect();
-    matched_ids.sort();
+    // Ready issues are never closed, so the default status filter is moot
+    filter.apply(&mut ready)?;
 
-    if matched_ids.is_empty() {
-        return Err(format!("No issues matched {}", selector_desc));
-    }
+    ready.sort_by(|a, b| a.cmp_priority(b));
+    let total = page.apply(&mut ready);
 
-    if !force {
-        let non_closed: Vec<String> = matched_ids
-            .iter()
-            .filter_map(|id| {
-                store.issues.get(id).and_then(|issue| {
-                    if issue.status == Status::Closed {
-                        None
-                    } else {
-                        Some(id.clone())
-                    }
-                })
-            })
-            .collect();
+    if json_output {
+        page.print_json(total, &ready);
+        return Ok(());
+    }
 
-        if !non_closed.is_empty() {
-            return Err(format!(
-                "Refusing to delete non-closed issues without --force: {}",
-                non_closed.join(", ")
-            ));
+    if ready.is_empty() {
+        match page.note(0, total) {
+            Some(note) => println!("{}", note),
+            None => println!("No issues ready to work on."),
         }
+        return Ok(());
+    }
+
+    println!();
+    println!("  {:<8} {:>2}  {:<8} TITLE", "ID", "P", "TYPE");
+    println!("  {}", "-".repeat(60));
+
+    for issue in &ready {
+        println!(
+            "  {:<8} {}  {:<8} {}{}",
+            issue.id,
+            paint(
+                &format!("{:>2}", issue.priority),
+                priority_color(issue.priority)
+            ),
+            issue.issue_type.to_string(),
+            truncate(&issue.title, 40),
+            if verbose {
+                claim_tag(issue)
+            } else {
+                String::new()
+            }
+        );
     }
 
-    let delete_set: std::collections::HashSet<String> = matched_ids.iter().cloned().collect();
-    let now = Utc::now();
+    println!();
+    println!("{} issue(s) ready", ready.len());
+    if let Some(note) = page.note(ready.len(), total) {
+        println!("{}", note);
+    }
 
-    for issue in store.issues.values_mut() {
-        let blocks_before = issue.blocks.len();
-        let blocked_by_before = issue.blocked_by.len();
+    Ok(())
+}
 
-        issue.blocks.retain(|dep| !delete_set.contains(dep));
-        issue.blocked_by.retain(|dep| !delete_set.contains(dep));
+/// The inverse of `ready`: open or in-progress issues with a hard blocker
+/// that isn't closed yet.
+fn cmd_blocked(store: &Store, json_output: bool) -> Result<(), BaError> {
+    let mut blocked: Vec<(&Issue, Vec<&String>)> = store
+        .issues
+        .values()
+        .filter(|issue| issue.status != Status::Closed)
+        .map(|issue| (issue, store.open_blockers(issue)))
+        .filter(|(_, blockers)| !blockers.is_empty())
+        .collect();
+    blocked.sort_by(|(a, _), (b, _)| a.cmp_priority(b));
 
-        if issue.blocks.len() != blocks_before || issue.blocked_by.len() != blocked_by_before {
-            issue.updated_at = now;
-        }
+    if json_output {
+        let annotated: Vec<_> = blocked
+            .iter()
+            .map(|(issue, blockers)| {
+                let mut value = serde_json::to_value(issue).unwrap();
+                value["blocking_open"] = serde_json::json!(blockers);
+                value
+            })
+            .collect();
+        println!("{}", serde_json::to_string(&annotated).unwrap());
+        return Ok(());
     }
 
-    for id in &matched_ids {
-        store.issues.remove(id);
+    if blocked.is_empty() {
+        println!("No blocked issues.");
+        return Ok(());
     }
 
-    store.save()?;
+    println!();
+    println!("  {:<8} {:>2}  {:<8} TITLE", "ID", "P", "TYPE");
+    println!("  {}", "-".repeat(60));
 
-    let deleted_count = matched_ids.len();
-    if json_output {
-        println!(
-            "{}",
-            serde_json::json!({
-                "selector": selector_desc,
-                "deleted": matched_ids,
-                "count": deleted_count,
-                "force": force
-            })
-        );
-    } else {
+    for (issue, blockers) in &blocked {
         println!(
-            "Deleted {} issue(s) matching {}",
-            deleted_count, selector_desc
+            "  {:<8} {}  {:<8} {}",
+            issue.id,
+            paint(
+                &format!("{:>2}", issue.priority),
+                priority_color(issue.priority)
+            ),
+            issue.issue_type.to_string(),
+            truncate(&issue.title, 40)
         );
+        for blocker in blockers {
+            let blocker = &store.issues[*blocker];
+            println!(
+                "  {:<8}     waiting on {} ({}): {}",
+                "",
+                blocker.id,
+                blocker.status,
+                truncate(&blocker.title, 40)
+            );
+        }
     }
 
+    println!();
+    println!("{} issue(s) blocked", blocked.len());
+
     Ok(())
 }
-fn cmd_block(store: &mut Store, id: &str, blocker: &str, json_output: bool) -> Result<(), String> {
-    if id == blocker {
-        return Err("Issue cannot block itself".to_string());
-    }
 
-    // Verify both issues exist
-    if !store.issues.contains_key(id) {
-        return Err(format!("Issue not found: {}", id));
-    }
-    if !store.issues.contains_key(blocker) {
-        return Err(format!("Issue not found: {}", blocker));
+/// Starting text for a new template file.
+fn template_skeleton(issue_type: &IssueType) -> &'static str {
+    match issue_type {
+        IssueType::Spike => "## Question\n\n## Hypothesis\n\n## Findings\n",
+        IssueType::Refactor => "## Current\n\n## Target\n\n## Risk\n",
+        IssueType::Task | IssueType::Epic => "",
     }
+}
 
-    // Check if already blocked
-    {
-        let issue = store.issues.get(id).unwrap();
-        if issue.blocked_by.contains(&blocker.to_string()) {
-            return Err(format!("{} already blocked by {}", id, blocker));
-        }
+/// Create `.ba/templates/<type>.md` if missing, then open it in the user's
+/// editor. With --json, only create it and report the path.
+fn cmd_template_edit(ba_dir: &Path, issue_type: &str, json_output: bool) -> Result<(), BaError> {
+    if !ba_dir.join(CONFIG_FILE).exists() {
+        return Err("Not initialized. Run 'ba init' first.".into());
     }
-
-    // Add bidirectional relationship
-    let now = Utc::now();
-    {
-        let issue = store.issues.get_mut(id).unwrap();
-        issue.blocked_by.push(blocker.to_string());
-        issue.updated_at = now;
+    let issue_type: IssueType = issue_type.parse()?;
+    let path = template_path(ba_dir, &issue_type);
+
+    let created = !path.exists();
+    if dry_run() {
+        say!(
+            "Would {} {}",
+            if created { "create and edit" } else { "edit" },
+            path.display()
+        );
+        return Ok(());
     }
-    {
-        let blocker_issue = store.issues.get_mut(blocker).unwrap();
-        blocker_issue.blocks.push(id.to_string());
-        blocker_issue.updated_at = now;
+    if created {
+        fs::create_dir_all(ba_dir.join(TEMPLATES_DIR))
+            .map_err(|e| format!("Failed to create templates directory: {}", e))?;
+        fs::write(&path, template_skeleton(&issue_type))
+            .map_err(|e| format!("Failed to write template: {}", e))?;
     }
 
-    store.save()?;
-
     if json_output {
-        println!(r#"{{"blocked":"{}","blocker":"{}"}}"#, id, blocker);
-    } else {
-        println!("{} now blocked by {}", id, blocker);
+        println!(
+            "{}",
+            serde_json::json!({
+                "type": issue_type,
+                "path": path.display().to_string(),
+                "created": created,
+            })
+        );
+        return Ok(());
+    }
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // The editor setting may carry arguments (e.g. "code --wait")
+    let mut words = editor.split_whitespace();
+    let program = words.next().ok_or("$EDITOR is empty")?;
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(&path)
+        .status()
+        .map_err(|e| format!("Failed to run editor '{}': {}", editor, e))?;
+    if !status.success() {
+        return Err(format!("Editor '{}' exited with {}", editor, status).into());
     }
+    say!("Saved template {}", path.display());
 
     Ok(())
 }
 
-fn cmd_unblock(
-    store: &mut Store,
-    id: &str,
-    blocker: &str,
-    json_output: bool,
-) -> Result<(), String> {
-    // Verify both issues exist
-    if !store.issues.contains_key(id) {
-        return Err(format!("Issue not found: {}", id));
-    }
-    if !store.issues.contains_key(blocker) {
-        return Err(format!("Issue not found: {}", blocker));
-    }
+/// Redraw the ready list whenever issues.jsonl changes (mtime poll).
+fn cmd_watch(ba_dir: &Path, interval: u64, strict: bool, json_output: bool) -> Result<(), BaError> {
+    let issues_path = ba_dir.join(ISSUES_FILE);
+    let mut last_seen = None;
 
-    // Check if relationship exists
-    {
-        let issue = store.issues.get(id).unwrap();
-        if !issue.blocked_by.contains(&blocker.to_string()) {
-            return Err(format!("{} is not blocked by {}", id, blocker));
+    loop {
+        let modified = fs::metadata(&issues_path)
+            .and_then(|m| m.modified())
+            .map_err(|e| format!("Failed to stat {}: {}", issues_path.display(), e))?;
+
+        if last_seen != Some(modified) {
+            last_seen = Some(modified);
+            // Load per redraw and drop right away: never hold the lock while sleeping
+            let store = Store::load(ba_dir, LockMode::Shared)?;
+            store.check_corrupt(strict)?;
+
+            if !json_output {
+                print!("\x1b[2J\x1b[H");
+                println!(
+                    "ba watch - updated {} (Ctrl-C to exit)",
+                    Local::now().format("%H:%M:%S")
+                );
+            }
+            cmd_ready(
+                &store,
+                false,
+                &ListFilter::default(),
+                false,
+                &Page::default(),
+                json_output,
+            )?;
+            std::io::stdout()
+                .flush()
+                .map_err(|e| format!("Failed to flush stdout: {}", e))?;
         }
-    }
 
-    // Remove bidirectional relationship
-    let now = Utc::now();
-    {
-        let issue = store.issues.get_mut(id).unwrap();
-        issue.blocked_by.retain(|b| b != blocker);
-        issue.updated_at = now;
-    }
-    {
-        let blocker_issue = store.issues.get_mut(blocker).unwrap();
-        blocker_issue.blocks.retain(|b| b != id);
-        blocker_issue.updated_at = now;
+        std::thread::sleep(std::time::Duration::from_millis(interval));
     }
+}
 
-    store.save()?;
+/// Top ready issue; with `claim`, take it for `session` under the same lock.
+fn cmd_next(
+    store: &mut Store,
+    session: Option<&str>,
+    claim: bool,
+    json_output: bool,
+) -> Result<(), BaError> {
+    let next = store
+        .issues
+        .values()
+        .filter(|issue| store.is_ready(issue))
+        .min_by(|a, b| a.cmp_priority(b))
+        .map(|issue| issue.id.clone())
+        .ok_or_else(|| "No issues ready to work on".to_string())?;
+
+    if claim {
+        let session = session.expect("clap requires --session with --claim");
+        return cmd_claim(store, &next, session, None, json_output);
+    }
 
+    let issue = &store.issues[&next];
     if json_output {
-        println!(r#"{{"unblocked":"{}","was_blocker":"{}"}}"#, id, blocker);
+        println!("{}", serde_json::to_string(issue).unwrap());
     } else {
-        println!("{} no longer blocked by {}", id, blocker);
+        println!(
+            "{}  P{}  {}",
+            issue.id,
+            issue.priority,
+            truncate(&issue.title, 60)
+        );
     }
 
     Ok(())
 }
 
-fn cmd_tree(store: &Store, id: &str, json_output: bool) -> Result<(), String> {
-    let issue = store
-        .issues
-        .get(id)
-        .ok_or_else(|| format!("Issue not found: {}", id))?;
+fn cmd_stats(store: &Store, json_output: bool) -> Result<(), BaError> {
+    let issues: Vec<_> = store.issues.values().collect();
+    let unfinished: Vec<_> = issues
+        .iter()
+        .filter(|i| i.status != Status::Closed)
+        .collect();
+
+    let by_status: Vec<(String, usize)> = [Status::Open, Status::InProgress, Status::Closed]
+        .iter()
+        .map(|s| {
+            (
+                s.to_string(),
+                issues.iter().filter(|i| i.status == *s).count(),
+            )
+        })
+        .collect();
+
+    // Type and priority breakdowns describe remaining work, so skip closed issues
+    let by_type: Vec<(String, usize)> = ["task", "epic", "refactor", "spike"]
+        .iter()
+        .map(|t| {
+            let count = unfinished
+                .iter()
+                .filter(|i| i.issue_type.to_string() == *t)
+                .count();
+            (t.to_string(), count)
+        })
+        .collect();
+    let by_priority: Vec<(String, usize)> = (0..=4u8)
+        .map(|p| {
+            let count = unfinished.iter().filter(|i| i.priority == p).count();
+            (p.to_string(), count)
+        })
+        .collect();
+
+    let blocked = unfinished
+        .iter()
+        .filter(|i| !store.open_blockers(i).is_empty())
+        .count();
+    let ready = issues.iter().filter(|i| store.is_ready(i)).count();
+    let oldest_open = issues
+        .iter()
+        .filter(|i| i.status == Status::Open)
+        .min_by_key(|i| i.created_at);
 
     if json_output {
-        // Build tree structure as JSON
-        let tree = build_tree_json(store, id, &mut vec![]);
-        println!("{}", serde_json::to_string_pretty(&tree).unwrap());
+        let counts = |pairs: &[(String, usize)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.clone(), serde_json::json!(v)))
+                .collect::<serde_json::Map<_, _>>()
+        };
+        let output = serde_json::json!({
+            "total": issues.len(),
+            "by_status": counts(&by_status),
+            "by_type": counts(&by_type),
+            "by_priority": counts(&by_priority),
+            "blocked": blocked,
+            "ready": ready,
+            "oldest_open": oldest_open.map(|i| serde_json::json!({
+                "id": i.id,
+                "title": i.title,
+                "created_at": i.created_at,
+            })),
+        });
+        println!("{}", serde_json::to_string(&output).unwrap());
         return Ok(());
     }
 
-    // Pretty print tree
     println!();
-    print_tree_node(store, issue, "", true, true, &mut vec![]);
-
-    Ok(())
-}
+    println!("{} issues", issues.len());
 
-fn build_tree_json(store: &Store, id: &str, visited: &mut Vec<String>) -> serde_json::Value {
-    if visited.contains(&id.to_string()) {
-        return serde_json::json!({"id": id, "cycle": true});
+    println!();
+    println!("By status:");
+    for (status, count) in &by_status {
+        println!("  {:<12} {}", status, count);
     }
-    visited.push(id.to_string());
-
-    let issue = match store.issues.get(id) {
-        Some(i) => i,
-        None => return serde_json::json!({"id": id, "missing": true}),
-    };
-
-    let children: Vec<_> = issue
-        .blocked_by
-        .iter()
-        .map(|child_id| build_tree_json(store, child_id, visited))
-        .collect();
 
-    visited.pop();
-
-    serde_json::json!({
-        "id": issue.id,
-        "title": issue.title,
-        "status": issue.status,
-        "blocked_by": children
-    })
-}
-
-fn print_tree_node(
-    store: &Store,
-    issue: &Issue,
-    prefix: &str,
-    is_root: bool,
-    is_last: bool,
-    visited: &mut Vec<String>,
-) {
-    let status_tag = match issue.status {
-        Status::Open => "[OPEN]",
-        Status::InProgress => "[IN_PROGRESS]",
-        Status::Closed => "[CLOSED]",
-    };
+    println!();
+    println!("Open work by type:");
+    for (issue_type, count) in &by_type {
+        println!("  {:<12} {}", issue_type, count);
+    }
 
-    if visited.contains(&issue.id) {
-        if is_root {
-            println!("{}: {} [CYCLE]", issue.id, truncate(&issue.title, 30));
-        } else {
-            let connector = if is_last { "└── " } else { "├── " };
-            println!(
-                "{}{}{}: {} [CYCLE]",
-                prefix,
-                connector,
-                issue.id,
-                truncate(&issue.title, 30)
-            );
-        }
-        return;
+    println!();
+    println!("Open work by priority:");
+    for (priority, count) in &by_priority {
+        println!("  P{:<11} {}", priority, count);
     }
-    visited.push(issue.id.clone());
 
-    if is_root {
-        println!(
-            "{}: {} {}",
-            issue.id,
-            truncate(&issue.title, 30),
-            status_tag
-        );
-    } else {
-        let connector = if is_last { "└── " } else { "├── " };
+    println!();
+    println!("Blocked: {}", blocked);
+    println!("Ready:   {}", ready);
+    if let Some(issue) = oldest_open {
         println!(
-            "{}{}{}: {} {}",
-            prefix,
-            connector,
+            "Oldest open: {} {} (created {})",
             issue.id,
-            truncate(&issue.title, 30),
-            status_tag
+            truncate(&issue.title, 40),
+            issue.created_at.format("%Y-%m-%d")
         );
     }
 
-    let new_prefix = if is_root {
-        "".to_string()
-    } else if is_last {
-        format!("{}    ", prefix)
-    } else {
-        format!("{}│   ", prefix)
-    };
-
-    let blockers = &issue.blocked_by;
-    for (i, blocker_id) in blockers.iter().enumerate() {
-        let is_last_child = i == blockers.len() - 1;
-        if let Some(blocker) = store.issues.get(blocker_id) {
-            print_tree_node(store, blocker, &new_prefix, false, is_last_child, visited);
-        } else {
-            let child_connector = if is_last_child {
-                "└── "
-            } else {
-                "├── "
-            };
-            println!("{}{}{} [MISSING]", new_prefix, child_connector, blocker_id);
-        }
-    }
-
-    visited.pop();
+    Ok(())
 }
 
-fn cmd_cycles(store: &Store, json_output: bool) -> Result<(), String> {
-    let mut cycles: Vec<Vec<String>> = vec![];
-
-    for id in store.issues.keys() {
-        let mut visited = vec![];
-        let mut path = vec![];
-        find_cycles(store, id, &mut visited, &mut path, &mut cycles);
-    }
+/// Age buckets for `stats --aging`: label and upper bound in days.
+const AGING_BUCKETS: [(&str, Option<i64>); 4] = [
+    ("<1d", Some(1)),
+    ("1-7d", Some(7)),
+    ("7-30d", Some(30)),
+    (">30d", None),
+];
+
+/// Unfinished issues bucketed by how long ago they were last updated, with
+/// the stalest few of each bucket.
+fn cmd_stats_aging(store: &Store, json_output: bool) -> Result<(), BaError> {
+    const SHOWN: usize = 3;
+    let now = Utc::now();
+    let mut unfinished: Vec<&Issue> = store
+        .issues
+        .values()
+        .filter(|i| i.status != Status::Closed)
+        .collect();
+    unfinished.sort_by_key(|i| (i.updated_at, i.id.clone()));
 
-    // Deduplicate cycles (same cycle can be found from different starting points)
-    let mut unbaue_cycles: Vec<Vec<String>> = vec![];
-    for cycle in cycles {
-        let normalized = normalize_cycle(&cycle);
-        if !unbaue_cycles
+    let mut buckets: Vec<(&str, Vec<&Issue>)> = AGING_BUCKETS
+        .iter()
+        .map(|(label, _)| (*label, vec![]))
+        .collect();
+    for issue in unfinished {
+        let days = (now - issue.updated_at).num_days();
+        let index = AGING_BUCKETS
             .iter()
-            .any(|c| normalize_cycle(c) == normalized)
-        {
-            unbaue_cycles.push(cycle);
-        }
+            .position(|(_, limit)| limit.is_none_or(|limit| days < limit))
+            .expect("last bucket is unbounded");
+        buckets[index].1.push(issue);
     }
 
     if json_output {
-        println!("{}", serde_json::to_string(&unbaue_cycles).unwrap());
+        let output: Vec<_> = buckets
+            .iter()
+            .map(|(label, issues)| {
+                serde_json::json!({
+                    "bucket": label,
+                    "count": issues.len(),
+                    "oldest": issues.iter().take(SHOWN).map(|i| serde_json::json!({
+                        "id": i.id,
+                        "title": i.title,
+                        "status": i.status,
+                        "updated_at": i.updated_at,
+                    })).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string(&output).unwrap());
         return Ok(());
     }
 
-    if unbaue_cycles.is_empty() {
-        println!("No cycles detected.");
-    } else {
-        println!("Found {} cycle(s):", unbaue_cycles.len());
-        for (i, cycle) in unbaue_cycles.iter().enumerate() {
-            println!("  {}. {} -> {}", i + 1, cycle.join(" -> "), cycle[0]);
+    println!();
+    println!("Unfinished issues by time since last update:");
+    for (label, issues) in &buckets {
+        println!();
+        println!("  {:<6} {}", label, issues.len());
+        for issue in issues.iter().take(SHOWN) {
+            println!(
+                "    {:<8} {}  {}",
+                issue.id,
+                issue.updated_at.format("%Y-%m-%d"),
+                truncate(&issue.title, 40)
+            );
+        }
+        if issues.len() > SHOWN {
+            println!("    ... and {} more", issues.len() - SHOWN);
         }
     }
 
     Ok(())
 }
 
-fn find_cycles(
-    store: &Store,
-    id: &str,
-    visited: &mut Vec<String>,
-    path: &mut Vec<String>,
-    cycles: &mut Vec<Vec<String>>,
-) {
-    if path.contains(&id.to_string()) {
-        // Found a cycle
-        let cycle_start = path.iter().position(|x| x == id).unwrap();
-        let cycle: Vec<String> = path[cycle_start..].to_vec();
-        cycles.push(cycle);
-        return;
+fn cmd_log(store: &Store, id: Option<&str>, json_output: bool) -> Result<(), BaError> {
+    // Deleted issues only live on in the log, so fall back to the raw ID
+    let id = id.map(|id| store.resolve_id(id).unwrap_or_else(|_| id.to_string()));
+
+    let mut entries = store.audit_entries()?;
+    if let Some(id) = &id {
+        entries.retain(|entry| &entry.issue_id == id);
     }
 
-    if visited.contains(&id.to_string()) {
-        return;
+    if json_output {
+        println!("{}", serde_json::to_string(&entries).unwrap());
+        return Ok(());
     }
 
-    visited.push(id.to_string());
-    path.push(id.to_string());
+    if entries.is_empty() {
+        println!("No audit entries.");
+        return Ok(());
+    }
 
-    if let Some(issue) = store.issues.get(id) {
-        for blocker in &issue.blocked_by {
-            find_cycles(store, blocker, visited, path, cycles);
-        }
+    let status = |s: &Option<Status>| match s {
+        Some(status) => status.to_string(),
+        None => "-".to_string(),
+    };
+    for entry in &entries {
+        let who: Vec<String> = [
+            entry.session.as_deref().map(|s| format!("session {}", s)),
+            entry.author.as_deref().map(|a| format!("by {}", a)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let line = format!(
+            "{}  {:<10} {:<8} {:>11} -> {:<11} {}",
+            entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            entry.command,
+            entry.issue_id,
+            status(&entry.from_status),
+            status(&entry.to_status),
+            who.join("  ")
+        );
+        println!("{}", line.trim_end());
     }
 
-    path.pop();
+    Ok(())
 }
 
-fn normalize_cycle(cycle: &[String]) -> Vec<String> {
-    if cycle.is_empty() {
-        return vec![];
-    }
-    // Rotate so smallest element is first
-    let min_pos = cycle
-        .iter()
-        .enumerate()
-        .min_by_key(|(_, v)| *v)
-        .map(|(i, _)| i)
-        .unwrap_or(0);
-    let mut normalized: Vec<String> = cycle[min_pos..].to_vec();
-    normalized.extend(cycle[..min_pos].to_vec());
-    normalized
+/// How one issue differs from its committed version, for `ba diff`.
+#[derive(Debug, Serialize)]
+struct IssueDiff {
+    id: String,
+    title: String,
+    /// `added`, `deleted` or `changed`
+    change: &'static str,
+    /// Changed fields, for `changed` issues; `comments` reports counts
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fields: Vec<FieldDiff>,
 }
 
-fn cmd_claim(store: &mut Store, id: &str, session: &str, json_output: bool) -> Result<(), String> {
-    let issue = store
-        .issues
-        .get_mut(id)
-        .ok_or_else(|| format!("Issue not found: {}", id))?;
+#[derive(Debug, Serialize)]
+struct FieldDiff {
+    field: String,
+    from: serde_json::Value,
+    to: serde_json::Value,
+}
 
-    issue.apply(Transition::Claim {
-        session: session.to_string(),
-    })?;
+/// Compare the working `issues.jsonl` with the one in git HEAD, field by
+/// field. `updated_at` is left out: it changes with everything else.
+fn cmd_diff(store: &Store, id: Option<&str>, json_output: bool) -> Result<(), BaError> {
+    // Deleted issues only exist in HEAD, so fall back to the raw ID
+    let id = id.map(|id| store.resolve_id(id).unwrap_or_else(|_| id.to_string()));
+
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .arg("-C")
+            .arg(&store.ba_dir)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run git: {}", e))
+    };
+    if !git(&["rev-parse", "--is-inside-work-tree"])?
+        .status
+        .success()
+    {
+        return Err(format!(
+            "{} is not inside a git repository; nothing to diff against",
+            store.ba_dir.display()
+        )
+        .into());
+    }
 
-    let issue_clone = issue.clone();
-    store.save()?;
+    // No commits yet, or the file was never committed: everything is new
+    let spec = format!("HEAD:./{}", ISSUES_FILE);
+    let output = git(&["show", &spec])?;
+    let committed_text = if output.status.success() {
+        String::from_utf8_lossy(&output.stdout).to_string()
+    } else {
+        String::new()
+    };
+    let committed: BTreeMap<String, serde_json::Value> = committed_text
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Issue>(line).ok())
+        .map(|issue| (issue.id.clone(), serde_json::to_value(&issue).unwrap()))
+        .collect();
+    let current: BTreeMap<String, serde_json::Value> = store
+        .issues
+        .values()
+        .map(|issue| (issue.id.clone(), serde_json::to_value(issue).unwrap()))
+        .collect();
+
+    let ids: BTreeSet<&String> = committed.keys().chain(current.keys()).collect();
+    let mut diffs = vec![];
+    for issue_id in ids {
+        if id.as_ref().is_some_and(|id| id != issue_id) {
+            continue;
+        }
+        let title = |value: &serde_json::Value| value["title"].as_str().unwrap_or("").to_string();
+        let diff = match (committed.get(issue_id), current.get(issue_id)) {
+            (None, Some(now)) => IssueDiff {
+                id: issue_id.clone(),
+                title: title(now),
+                change: "added",
+                fields: vec![],
+            },
+            (Some(before), None) => IssueDiff {
+                id: issue_id.clone(),
+                title: title(before),
+                change: "deleted",
+                fields: vec![],
+            },
+            (Some(before), Some(now)) => {
+                let fields = diff_fields(before, now);
+                if fields.is_empty() {
+                    continue;
+                }
+                IssueDiff {
+                    id: issue_id.clone(),
+                    title: title(now),
+                    change: "changed",
+                    fields,
+                }
+            }
+            (None, None) => unreachable!(),
+        };
+        diffs.push(diff);
+    }
 
     if json_output {
-        println!("{}", serde_json::to_string(&issue_clone).unwrap());
-    } else {
-        println!("Claimed {} for session {}", id, session);
+        println!("{}", serde_json::to_string(&diffs).unwrap());
+        return Ok(());
     }
 
-    Ok(())
-}
-
-fn cmd_release(store: &mut Store, id: &str, json_output: bool) -> Result<(), String> {
-    let issue = store
-        .issues
-        .get_mut(id)
-        .ok_or_else(|| format!("Issue not found: {}", id))?;
-
-    let old_session = issue.apply(Transition::Release)?;
-
-    let issue_clone = issue.clone();
-    store.save()?;
+    if diffs.is_empty() {
+        println!("No changes since the last commit.");
+        return Ok(());
+    }
 
-    if json_output {
-        println!("{}", serde_json::to_string(&issue_clone).unwrap());
-    } else {
-        println!("Released {} (was claimed by {})", id, old_session.unwrap());
+    let show = |value: &serde_json::Value| match value {
+        serde_json::Value::Null => "-".to_string(),
+        serde_json::Value::String(s) => truncate(&s.replace('\n', " "), 40),
+        other => truncate(&other.to_string(), 40),
+    };
+    for diff in &diffs {
+        match diff.change {
+            "changed" => println!("{}: {}", diff.id, diff.title),
+            change => println!("{}: {} ({})", diff.id, diff.title, change),
+        }
+        for field in &diff.fields {
+            if field.field == "comments" {
+                let count = |v: &serde_json::Value| v.as_i64().unwrap_or(0);
+                match count(&field.to) - count(&field.from) {
+                    0 => println!("  comments: edited"),
+                    delta => println!("  comments: {:+}", delta),
+                }
+            } else {
+                println!(
+                    "  {}: {} -> {}",
+                    field.field,
+                    show(&field.from),
+                    show(&field.to)
+                );
+            }
+        }
     }
+    println!();
+    println!("{} issue(s) changed since the last commit", diffs.len());
 
     Ok(())
 }
 
-fn cmd_finish(store: &mut Store, id: &str, json_output: bool) -> Result<(), String> {
-    let issue = store
-        .issues
-        .get_mut(id)
-        .ok_or_else(|| format!("Issue not found: {}", id))?;
+/// Fields that differ between two serialized issues; missing fields count
+/// as null, and `comments` compares counts.
+fn diff_fields(before: &serde_json::Value, now: &serde_json::Value) -> Vec<FieldDiff> {
+    let empty = serde_json::Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let now = now.as_object().unwrap_or(&empty);
+    let keys: BTreeSet<&String> = before.keys().chain(now.keys()).collect();
+
+    let mut fields = vec![];
+    for key in keys {
+        if key == "updated_at" {
+            continue;
+        }
+        let null = serde_json::Value::Null;
+        let (from, to) = (
+            before.get(key).unwrap_or(&null),
+            now.get(key).unwrap_or(&null),
+        );
+        if from == to {
+            continue;
+        }
+        let (from, to) = if key == "comments" {
+            let count = |v: &serde_json::Value| v.as_array().map_or(0, Vec::len);
+            (count(from).into(), count(to).into())
+        } else {
+            (from.clone(), to.clone())
+        };
+        fields.push(FieldDiff {
+            field: key.clone(),
+            from,
+            to,
+        });
+    }
+    fields
+}
 
-    let old_session = issue.apply(Transition::Finish)?;
+/// One change to an issue recovered from git history of `issues.jsonl`.
+#[derive(Debug, Serialize)]
+struct HistoryEvent {
+    commit: String,
+    date: String,
+    subject: String,
+    /// `created`, `deleted`, `status` or `priority`
+    field: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    to: Option<String>,
+}
 
-    let issue_clone = issue.clone();
-    store.save()?;
+/// Walk `git log -p` of the issues file and report when `id` appeared,
+/// changed status or priority, or disappeared. Only committed states are seen.
+fn cmd_history(store: &Store, id: &str, json_output: bool) -> Result<(), BaError> {
+    // Deleted issues only live on in git, so fall back to the raw ID
+    let id = store.resolve_id(id).unwrap_or_else(|_| id.to_string());
+
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .arg("-C")
+            .arg(&store.ba_dir)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run git: {}", e))
+    };
+    if !git(&["rev-parse", "--is-inside-work-tree"])?
+        .status
+        .success()
+    {
+        return Err(format!(
+            "{} is not inside a git repository; history needs issues.jsonl committed to git",
+            store.ba_dir.display()
+        )
+        .into());
+    }
 
-    if json_output {
-        println!("{}", serde_json::to_string(&issue_clone).unwrap());
-    } else {
-        println!("Finished {} (was claimed by {})", id, old_session.unwrap());
+    // A fresh repository has nothing to log yet
+    let has_commits = git(&["rev-parse", "--verify", "--quiet", "HEAD"])?
+        .status
+        .success();
+
+    // \x1e marks commit headers so they can't be mistaken for diff lines
+    let output = git(&[
+        "log",
+        "--reverse",
+        "--unified=0",
+        "--format=\x1e%h\t%aI\t%s",
+        "-p",
+        "--",
+        ISSUES_FILE,
+    ])?;
+    if has_commits && !output.status.success() {
+        return Err(format!(
+            "git log failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
     }
 
-    Ok(())
-}
+    // (status, priority) as of the last commit that touched the issue
+    let state_of = |line: &str| -> Option<(String, String)> {
+        let value: serde_json::Value = serde_json::from_str(line).ok()?;
+        (value["id"] == id.as_str()).then(|| {
+            (
+                value["status"].as_str().unwrap_or("?").to_string(),
+                format!("P{}", value["priority"]),
+            )
+        })
+    };
+    let mut last: Option<(String, String)> = None;
+    let mut events = vec![];
+    for chunk in String::from_utf8_lossy(&output.stdout)
+        .split('\x1e')
+        .skip(1)
+    {
+        let mut lines = chunk.lines();
+        let header: Vec<&str> = lines.next().unwrap_or_default().splitn(3, '\t').collect();
+        let event = |field, from: Option<&String>, to: Option<&String>| HistoryEvent {
+            commit: header.first().unwrap_or(&"").to_string(),
+            date: header.get(1).unwrap_or(&"").to_string(),
+            subject: header.get(2).unwrap_or(&"").to_string(),
+            field,
+            from: from.cloned(),
+            to: to.cloned(),
+        };
 
-fn cmd_mine(store: &Store, session: &str, json_output: bool) -> Result<(), String> {
-    let mut mine: Vec<_> = store
-        .issues
-        .values()
-        .filter(|i| i.session_id.as_deref() == Some(session))
-        .collect();
+        let mut removed = false;
+        let mut added = None;
+        for line in lines {
+            if let Some(line) = line.strip_prefix('+').filter(|l| !l.starts_with("++")) {
+                added = added.or(state_of(line));
+            } else if let Some(line) = line.strip_prefix('-').filter(|l| !l.starts_with("--")) {
+                removed |= state_of(line).is_some();
+            }
+        }
 
-    mine.sort_by(|a, b| {
-        a.priority
-            .cmp(&b.priority)
-            .then_with(|| a.created_at.cmp(&b.created_at))
-    });
+        match (added, &last) {
+            (Some(now), None) => {
+                events.push(event("created", None, Some(&now.0)));
+                last = Some(now);
+            }
+            (Some(now), Some(before)) => {
+                if now.0 != before.0 {
+                    events.push(event("status", Some(&before.0), Some(&now.0)));
+                }
+                if now.1 != before.1 {
+                    events.push(event("priority", Some(&before.1), Some(&now.1)));
+                }
+                last = Some(now);
+            }
+            (None, Some(before)) if removed => {
+                events.push(event("deleted", Some(&before.0), None));
+                last = None;
+            }
+            (None, _) => {}
+        }
+    }
 
     if json_output {
-        println!("{}", serde_json::to_string(&mine).unwrap());
+        println!("{}", serde_json::to_string(&events).unwrap());
         return Ok(());
     }
 
-    if mine.is_empty() {
-        println!("No issues claimed by session {}", session);
+    if events.is_empty() {
+        println!("No committed history for {}.", id);
         return Ok(());
     }
 
-    println!();
-    println!("  {:<8} {:>2}  {:<8} {}", "ID", "P", "TYPE", "TITLE");
-    println!("  {}", "-".repeat(60));
-
-    for issue in &mine {
+    for event in &events {
+        let change = match event.field {
+            "created" => format!("created ({})", event.to.as_deref().unwrap_or("?")),
+            "deleted" => "deleted".to_string(),
+            field => format!(
+                "{} {} -> {}",
+                field,
+                event.from.as_deref().unwrap_or("?"),
+                event.to.as_deref().unwrap_or("?")
+            ),
+        };
+        // %aI is RFC 3339; the seconds and offset are noise here
         println!(
-            "  {:<8} {:>2}  {:<8} {}",
-            issue.id,
-            issue.priority,
-            issue.issue_type,
-            truncate(&issue.title, 40)
+            "{}  {}  {:<30} {}",
+            event
+                .date
+                .get(..16)
+                .unwrap_or(&event.date)
+                .replace('T', " "),
+            event.commit,
+            change,
+            event.subject
         );
     }
 
-    println!();
-    println!("{} issue(s) claimed by session {}", mine.len(), session);
-
     Ok(())
 }
 
-fn cmd_label(
+/// Move an issue into the store at `to` (see [`Store::move_to`]). The
+/// destination is saved before the source.
+fn cmd_move(
     store: &mut Store,
     id: &str,
-    action: &str,
-    label: &str,
+    to: &Path,
+    keep_id: bool,
+    with_deps: bool,
     json_output: bool,
-) -> Result<(), String> {
-    let issue = store
-        .issues
-        .get_mut(id)
-        .ok_or_else(|| format!("Issue not found: {}", id))?;
-
-    match action {
-        "add" => {
-            if issue.labels.contains(&label.to_string()) {
-                return Err(format!("Label '{}' already exists on {}", label, id));
-            }
-            issue.labels.push(label.to_string());
-            issue.labels.sort();
-        }
-        "remove" => {
-            if !issue.labels.contains(&label.to_string()) {
-                return Err(format!("Label '{}' not found on {}", label, id));
-            }
-            issue.labels.retain(|l| l != label);
-        }
-        _ => {
-            return Err(format!(
-                "Unknown action: {} (use 'add' or 'remove')",
-                action
-            ));
-        }
+) -> Result<(), BaError> {
+    // Accept the .ba directory itself or the project root containing it
+    let dest_dir = if !to.join(CONFIG_FILE).exists() && to.join(".ba").join(CONFIG_FILE).exists() {
+        to.join(".ba")
+    } else {
+        to.to_path_buf()
+    };
+    if fs::canonicalize(&dest_dir).ok() == fs::canonicalize(&store.ba_dir).ok() {
+        return Err("Source and destination are the same store".into());
     }
 
-    issue.updated_at = Utc::now();
-    let issue_clone = issue.clone();
+    let mut dest = Store::load(&dest_dir, LockMode::Exclusive)?;
+    dest.command = store.command.clone();
+    let moved = store.move_to(&mut dest, id, keep_id, with_deps)?;
+
+    dest.save()?;
     store.save()?;
 
     if json_output {
-        println!("{}", serde_json::to_string(&issue_clone).unwrap());
-    } else {
+        let moved: Vec<_> = moved
+            .iter()
+            .map(|(from, to)| serde_json::json!({"from": from, "to": to}))
+            .collect();
         println!(
-            "{} label '{}' {} {}",
-            if action == "add" { "Added" } else { "Removed" },
-            label,
-            if action == "add" { "to" } else { "from" },
-            id
+            "{}",
+            serde_json::json!({"moved": moved, "destination": dest_dir.display().to_string()})
         );
+    } else {
+        for (from, to) in &moved {
+            say!("Moved {} -> {}", from, to);
+        }
+        say!("{} issue(s) moved to {}", moved.len(), dest_dir.display());
     }
 
     Ok(())
 }
 
-fn cmd_priority(store: &mut Store, id: &str, value: u8, json_output: bool) -> Result<(), String> {
-    if value > 4 {
-        return Err("Priority must be 0-4".to_string());
-    }
-
-    let issue = store
-        .issues
-        .get_mut(id)
-        .ok_or_else(|| format!("Issue not found: {}", id))?;
-
-    let old_priority = issue.priority;
-    issue.priority = value;
-    issue.updated_at = Utc::now();
-
-    let issue_clone = issue.clone();
+fn cmd_reprefix(store: &mut Store, new_prefix: &str, json_output: bool) -> Result<(), BaError> {
+    let (old_prefix, renamed) = store.reprefix(new_prefix)?;
     store.save()?;
+    store.save_config()?;
 
     if json_output {
-        println!("{}", serde_json::to_string(&issue_clone).unwrap());
+        println!(
+            "{}",
+            serde_json::json!({
+                "old_prefix": old_prefix,
+                "prefix": new_prefix,
+                "renamed": renamed,
+            })
+        );
     } else {
-        println!("Priority {} -> {} for {}", old_priority, value, id);
+        say!(
+            "Changed prefix '{}' -> '{}' ({} issue(s) renamed)",
+            old_prefix,
+            new_prefix,
+            renamed
+        );
     }
 
     Ok(())
 }
 
-fn cmd_comment(
-    store: &mut Store,
-    id: &str,
-    text: &str,
-    author: &str,
-    json_output: bool,
-) -> Result<(), String> {
-    let issue = store
-        .issues
-        .get_mut(id)
-        .ok_or_else(|| format!("Issue not found: {}", id))?;
-
-    let comment = Comment {
-        author: author.to_string(),
-        text: text.to_string(),
-        created_at: Utc::now(),
-    };
+/// Config keys as `ba config` spells them, with their current values.
+fn config_entries(config: &Config) -> Vec<(&'static str, String)> {
+    vec![
+        ("version", config.version.to_string()),
+        ("prefix", config.prefix.clone()),
+        ("default-type", config.default_type.to_string()),
+        ("default-priority", config.default_priority.to_string()),
+        (
+            "web-base-url",
+            config.web_base_url.clone().unwrap_or_default(),
+        ),
+        (
+            "label-priority",
+            config
+                .label_priority
+                .iter()
+                .map(|(label, priority)| format!("{}={}", label, priority))
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+        (
+            "escalate-after",
+            config
+                .escalate_after_days
+                .map(|d| d.to_string())
+                .unwrap_or_default(),
+        ),
+        (
+            "decay-after",
+            config
+                .decay_after_days
+                .map(|d| d.to_string())
+                .unwrap_or_default(),
+        ),
+    ]
+}
 
-    issue.comments.push(comment.clone());
-    issue.updated_at = Utc::now();
+fn cmd_config_list(store: &Store, json_output: bool) -> Result<(), BaError> {
+    if json_output {
+        println!("{}", serde_json::to_string(&store.config).unwrap());
+        return Ok(());
+    }
+    for (key, value) in config_entries(&store.config) {
+        println!("{} = {}", key, value);
+    }
+    Ok(())
+}
 
-    let issue_clone = issue.clone();
-    store.save()?;
+fn cmd_config_get(store: &Store, key: &str, json_output: bool) -> Result<(), BaError> {
+    let wanted = key.replace('_', "-");
+    let (key, value) = config_entries(&store.config)
+        .into_iter()
+        .find(|(k, _)| *k == wanted)
+        .ok_or_else(|| unknown_config_key(key))?;
 
     if json_output {
-        println!("{}", serde_json::to_string(&comment).unwrap());
+        println!("{}", serde_json::json!({"key": key, "value": value}));
     } else {
-        println!(
-            "Added comment to {} ({} comments total)",
-            id,
-            issue_clone.comments.len()
-        );
+        println!("{}", value);
     }
-
     Ok(())
 }
 
-fn cmd_import(
+fn unknown_config_key(key: &str) -> BaError {
+    format!(
+        "Unknown config key: {} (valid: version, prefix, default-type, default-priority, web-base-url, label-priority, escalate-after, decay-after)",
+        key
+    )
+    .into()
+}
+
+fn cmd_config_set(
     store: &mut Store,
-    file: &Path,
-    keep_ids: bool,
+    key: &str,
+    value: &str,
     json_output: bool,
-) -> Result<(), String> {
-    use std::io::BufRead;
-
-    let file_handle =
-        File::open(file).map_err(|e| format!("Failed to open '{}': {}", file.display(), e))?;
-    let reader = BufReader::new(file_handle);
-
-    let mut imported = 0;
-    let mut skipped = 0;
-    let mut errors: Vec<ImportError> = vec![];
-    let mut id_map: HashMap<String, String> = HashMap::new(); // old_id -> new_id
-
-    // First pass: parse all issues and build ID map
-    let mut beads_issues: Vec<(usize, BeadsIssue)> = vec![];
-
-    for (line_num, line) in reader.lines().enumerate() {
-        let line_num = line_num + 1; // 1-indexed for user display
-        let line = match line {
-            Ok(l) => l,
-            Err(e) => {
-                errors.push(ImportError {
-                    line_num,
-                    issue_id: None,
-                    field: "line".to_string(),
-                    message: format!("Failed to read: {}", e),
-                });
-                continue;
-            }
-        };
-
-        if line.trim().is_empty() {
-            continue;
+) -> Result<(), BaError> {
+    match key.replace('_', "-").as_str() {
+        // Changing the prefix renames every issue, so it goes through reprefix
+        "prefix" => return cmd_reprefix(store, value, json_output),
+        "version" => return Err("version is managed by ba and can't be set".into()),
+        "default-type" => store.config.default_type = value.parse()?,
+        "default-priority" => {
+            store.config.default_priority = value
+                .parse()
+                .ok()
+                .filter(|p| *p <= 4)
+                .ok_or("Priority must be 0-4")?;
         }
-
-        // First try to get the ID for better error messages
-        let raw: serde_json::Value = match serde_json::from_str(&line) {
-            Ok(v) => v,
-            Err(e) => {
-                errors.push(ImportError {
-                    line_num,
-                    issue_id: None,
-                    field: "json".to_string(),
-                    message: format!("Invalid JSON: {}", e),
-                });
-                continue;
-            }
-        };
-
-        let issue_id = raw
-            .get("id")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string());
-
-        // Now parse as BeadsIssue
-        let beads_issue: BeadsIssue = match serde_json::from_value(raw.clone()) {
-            Ok(i) => i,
-            Err(e) => {
-                // Try to identify which field failed
-                let field = if raw.get("title").is_none() {
-                    "title (missing)"
-                } else if raw.get("status").is_none() {
-                    "status (missing)"
-                } else if raw.get("issue_type").is_none() {
-                    "issue_type (missing)"
-                } else if raw.get("created_at").is_none() {
-                    "created_at (missing)"
-                } else if raw.get("updated_at").is_none() {
-                    "updated_at (missing)"
-                } else {
-                    "parsing"
-                };
-                errors.push(ImportError {
-                    line_num,
-                    issue_id,
-                    field: field.to_string(),
-                    message: format!("{}", e),
-                });
-                continue;
+        // An empty value turns the web links off again
+        "web-base-url" if value.is_empty() => store.config.web_base_url = None,
+        "web-base-url" => {
+            if !value.starts_with("http://") && !value.starts_with("https://") {
+                return Err("web-base-url must start with http:// or https://".into());
             }
-        };
-
-        beads_issues.push((line_num, beads_issue));
-    }
-
-    // Build ID map (before creating issues, so we can resolve dependencies)
-    for (_, beads) in &beads_issues {
-        let new_id = if keep_ids {
-            beads.id.clone()
-        } else {
-            // Parse timestamp for ID generation
-            let ts = DateTime::parse_from_rfc3339(&beads.created_at)
-                .map(|dt| dt.with_timezone(&Utc))
-                .unwrap_or_else(|_| Utc::now());
-            store.generate_id(&beads.title, &ts)
-        };
-        id_map.insert(beads.id.clone(), new_id);
-    }
-
-    // Second pass: create issues with resolved dependencies
-    for (line_num, beads) in beads_issues {
-        let new_id = id_map.get(&beads.id).unwrap().clone();
-
-        // Check for duplicate
-        if store.issues.contains_key(&new_id) {
-            skipped += 1;
-            continue;
+            store.config.web_base_url = Some(value.trim_end_matches('/').to_string());
         }
-
-        // Parse status
-        let status = match beads.status.as_str() {
-            "open" => Status::Open,
-            "in_progress" => Status::InProgress,
-            "closed" => Status::Closed,
-            other => {
-                errors.push(ImportError {
-                    line_num,
-                    issue_id: Some(beads.id.clone()),
-                    field: "status".to_string(),
-                    message: format!(
-                        "Unknown status '{}', expected open/in_progress/closed",
-                        other
-                    ),
-                });
-                continue;
-            }
-        };
-
-        // Parse issue_type (unknown types map to task)
-        let issue_type: IssueType = match beads.issue_type.parse() {
-            Ok(t) => t,
-            Err(_) => {
-                errors.push(ImportError {
-                    line_num,
-                    issue_id: Some(beads.id.clone()),
-                    field: "issue_type".to_string(),
-                    message: format!("Unknown type '{}' mapped to 'task'", beads.issue_type),
-                });
-                IssueType::Task
-            }
-        };
-
-        // Parse timestamps
-        let created_at = match DateTime::parse_from_rfc3339(&beads.created_at) {
-            Ok(dt) => dt.with_timezone(&Utc),
-            Err(e) => {
-                errors.push(ImportError {
-                    line_num,
-                    issue_id: Some(beads.id.clone()),
-                    field: "created_at".to_string(),
-                    message: format!("Invalid timestamp '{}': {}", beads.created_at, e),
-                });
-                continue;
-            }
-        };
-
-        let updated_at = match DateTime::parse_from_rfc3339(&beads.updated_at) {
-            Ok(dt) => dt.with_timezone(&Utc),
-            Err(e) => {
-                errors.push(ImportError {
-                    line_num,
-                    issue_id: Some(beads.id.clone()),
-                    field: "updated_at".to_string(),
-                    message: format!("Invalid timestamp '{}': {}", beads.updated_at, e),
-                });
-                continue;
-            }
-        };
-
-        let closed_at = if let Some(ref ca) = beads.closed_at {
-            match DateTime::parse_from_rfc3339(ca) {
-                Ok(dt) => Some(dt.with_timezone(&Utc)),
-                Err(e) => {
-                    errors.push(ImportError {
-                        line_num,
-                        issue_id: Some(beads.id.clone()),
-                        field: "closed_at".to_string(),
-                        message: format!("Invalid timestamp '{}': {}", ca, e),
-                    });
-                    continue;
-                }
-            }
-        } else {
-            None
-        };
-
-        // Build blocked_by from dependencies where this issue depends on another
-        let mut blocked_by: Vec<String> = vec![];
-        for dep in &beads.dependencies {
-            if dep.dep_type == "blocks" && dep.issue_id == beads.id {
-                if let Some(new_blocker_id) = id_map.get(&dep.depends_on_id) {
-                    blocked_by.push(new_blocker_id.clone());
-                }
+        // label=priority pairs, comma-separated; replaces the whole map
+        "label-priority" => {
+            let mut rules = BTreeMap::new();
+            for rule in value.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+                let (label, priority) = rule
+                    .split_once('=')
+                    .map(|(l, p)| (l.trim(), p.trim().parse::<u8>().ok()))
+                    .filter(|(l, p)| !l.is_empty() && p.is_some_and(|p| p <= 4))
+                    .ok_or_else(|| {
+                        format!(
+                            "Invalid rule '{}' (expected label=priority, priority 0-4)",
+                            rule
+                        )
+                    })?;
+                rules.insert(label.to_string(), priority.unwrap());
             }
+            store.config.label_priority = rules;
         }
-
-        let issue = Issue {
-            id: new_id.clone(),
-            title: beads.title,
-            description: beads.description,
-            status,
-            priority: beads.priority.min(4),
-            issue_type,
-            session_id: None,
-            labels: vec![],
-            comments: vec![],
-            created_at,
-            updated_at,
-            closed_at,
-            blocks: vec![], // Will be filled in next pass
-            blocked_by,
-        };
-
-        store.issues.insert(new_id, issue);
-        imported += 1;
-    }
-
-    // Third pass: populate `blocks` field (reverse of blocked_by)
-    let ids: Vec<String> = store.issues.keys().cloned().collect();
-    for id in ids {
-        let blocked_by = store.issues.get(&id).unwrap().blocked_by.clone();
-        for blocker_id in blocked_by {
-            if let Some(blocker) = store.issues.get_mut(&blocker_id) {
-                if !blocker.blocks.contains(&id) {
-                    blocker.blocks.push(id.clone());
-                }
+        // Days for `ba groom`; empty turns the rule off. Decay is for issues
+        // idle even longer, so it has to wait more days than escalation
+        "escalate-after" | "decay-after" => {
+            let days = match value {
+                "" => None,
+                _ => Some(
+                    value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|d| *d > 0)
+                        .ok_or("Days must be a positive whole number")?,
+                ),
+            };
+            let (escalate, decay) = if key.starts_with("escalate") {
+                (days, store.config.decay_after_days)
+            } else {
+                (store.config.escalate_after_days, days)
+            };
+            if let (Some(escalate), Some(decay)) = (escalate, decay)
+                && decay <= escalate
+            {
+                return Err(format!(
+                    "decay-after ({}) must be longer than escalate-after ({})",
+                    decay, escalate
+                )
+                .into());
             }
+            store.config.escalate_after_days = escalate;
+            store.config.decay_after_days = decay;
         }
+        _ => return Err(unknown_config_key(key)),
+    }
+    store.save_config()?;
+
+    if json_output {
+        println!("{}", serde_json::to_string(&store.config).unwrap());
+    } else {
+        say!("Set {} = {}", key, value);
     }
 
+    Ok(())
+}
+
+fn cmd_undo(store: &mut Store, json_output: bool) -> Result<(), BaError> {
+    let Undo {
+        undone,
+        reversed,
+        deleted,
+    } = store.undo()?;
     store.save()?;
 
     if json_output {
         println!(
-            r#"{{"imported":{},"skipped":{},"errors":{}}}"#,
-            imported,
-            skipped,
-            errors.len()
+            "{}",
+            serde_json::json!({"undone": undone, "issues": reversed, "deleted": deleted})
         );
     } else {
-        println!(
-            "Imported {} issues ({} skipped, {} errors)",
-            imported,
-            skipped,
-            errors.len()
-        );
-        if !errors.is_empty() {
-            println!();
-            println!("Errors:");
-            for err in &errors {
-                println!("  {}", err);
+        for entry in &undone {
+            match (&entry.from_status, &entry.to_status) {
+                (Some(from), Some(to)) => {
+                    say!(
+                        "Undid '{}' on {} ({} -> {})",
+                        entry.command,
+                        entry.issue_id,
+                        to,
+                        from
+                    )
+                }
+                _ => say!("Undid '{}': removed {}", entry.command, entry.issue_id),
             }
         }
     }
@@ -1781,145 +5857,97 @@ fn cmd_import(
     Ok(())
 }
 
-fn cmd_quickstart() {
-    println!(
-        r#"
-ba - Simple Task Tracking for LLM Sessions
-
-GETTING STARTED
-  ba init           Initialize ba in your project (creates .ba/)
-  ba quickstart     Show this guide
-
-CREATING ISSUES
-  ba create "Fix login bug" -p 1
-  ba create "Add caching layer" -t refactor -d "Description here"
-  ba create "Research auth options" -t spike -p 2
-
-ISSUE TYPES: task (default), epic, refactor, spike
-PRIORITIES: 0 (critical) → 4 (backlog), default is 2
-
-VIEWING ISSUES
-  ba list           List open/in_progress issues
-  ba list --all     Include closed
-  ba list --status open
-  ba show <id>      Show full details
-  ba ready          Show issues ready to work on (open + not blocked)
-
-OWNERSHIP-BASED WORKFLOW
-  ba claim <id> --session $SESSION    Take ownership (open → in_progress)
-  ba release <id>                     Abandon work (in_progress → open)
-  ba finish <id>                      Complete work (in_progress → closed)
-  ba close <id>                       Close unclaimed issue (escape hatch)
-
-  Status is a side-effect of ownership transitions, not set directly.
-
-MODIFYING ISSUES
-  ba priority <id> <0-4>              Set priority (0 = critical)
-  ba label <id> add urgent            Add a label
-  ba label <id> remove urgent         Remove a label
-  ba comment <id> "text" --author X   Add a comment
-
-  ba delete --label factory:bloodwork:skeleton
-  ba delete --label-prefix factory:bloodwork:
-  ba delete --label-prefix factory:bloodwork: --force  Delete even open/in_progress
-DEPENDENCIES
-  ba block <id> <blocker>    Mark <id> blocked by <blocker>
-  ba unblock <id> <blocker>  Remove block
-  ba tree <id>               Show dependency tree
-  ba cycles                  Detect circular dependencies
-
-MULTI-AGENT COORDINATION
-  ba claim <id> --session <session_id>  Claim issue for your session
-  ba mine --session <session_id>        Show your claimed issues
-  ba release <id>                       Release claim (back to pool)
-
-  Tip: Use your Claude session ID as --session value
-
-IMPORTING FROM BEADS (bd)
-  ba import .beads/issues.jsonl --keep-ids
-
-JSON OUTPUT (for programmatic use)
-  ba --json list
-  ba --json show <id>
-  ba --json ready
-
-TYPICAL WORKFLOW
-  1. ba ready                          # Find unblocked work
-  2. ba claim <id> --session $SESSION  # Claim it (sets in_progress)
-  3. ... do the work ...
-  4. ba finish <id>                    # Complete (clears claim, closes)
-
-DISCOVERING NEW WORK
-  1. ba create "Found bug in X" -t bug -p 1
-  2. ba block <current_id> <new_id>    # If it blocks current work
-  3. ba tree <current_id>              # Verify dependency chain
-"#
-    );
-}
-
-fn cmd_ready(store: &Store, json_output: bool) -> Result<(), String> {
-    // Ready = open issues where all blockers are closed (or no blockers)
-    let mut ready: Vec<_> = store
-        .issues
-        .values()
-        .filter(|issue| {
-            // Must be open
-            if issue.status != Status::Open {
-                return false;
-            }
-            // All blockers must be closed
-            issue.blocked_by.iter().all(|blocker_id| {
-                store
-                    .issues
-                    .get(blocker_id)
-                    .map(|b| b.status == Status::Closed)
-                    .unwrap_or(true) // Missing blocker = not blocking
-            })
-        })
-        .collect();
-
-    // Sort by priority, then by created_at
-    ready.sort_by(|a, b| {
-        a.priority
-            .cmp(&b.priority)
-            .then_with(|| a.created_at.cmp(&b.created_at))
-    });
+/// Claimed work per session: in_progress issues and their summed estimates.
+fn cmd_load(store: &Store, json_output: bool) -> Result<(), BaError> {
+    // session -> (issue IDs, estimate total, IDs without an estimate)
+    let mut sessions: BTreeMap<&str, (Vec<&str>, u32, Vec<&str>)> = BTreeMap::new();
+    for issue in store.issues.values() {
+        if issue.status != Status::InProgress {
+            continue;
+        }
+        let Some(session) = issue.session_id.as_deref() else {
+            continue;
+        };
+        let entry = sessions.entry(session).or_default();
+        entry.0.push(&issue.id);
+        match issue.estimate {
+            Some(estimate) => entry.1 += estimate,
+            None => entry.2.push(&issue.id),
+        }
+    }
+    for (ids, _, unestimated) in sessions.values_mut() {
+        ids.sort();
+        unestimated.sort();
+    }
 
     if json_output {
-        println!("{}", serde_json::to_string(&ready).unwrap());
+        let load: Vec<_> = sessions
+            .iter()
+            .map(|(session, (ids, total, unestimated))| {
+                serde_json::json!({
+                    "session": session,
+                    "issues": ids,
+                    "estimate": total,
+                    "unestimated": unestimated,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string(&load).unwrap());
         return Ok(());
     }
 
-    if ready.is_empty() {
-        println!("No issues ready to work on.");
+    if sessions.is_empty() {
+        println!("No issues in progress.");
         return Ok(());
     }
 
     println!();
-    println!("  {:<8} {:>2}  {:<8} {}", "ID", "P", "TYPE", "TITLE");
+    println!(
+        "  {:<24} {:>6}  {:>8}  UNESTIMATED",
+        "SESSION", "ISSUES", "ESTIMATE"
+    );
     println!("  {}", "-".repeat(60));
 
-    for issue in &ready {
-        println!(
-            "  {:<8} {:>2}  {:<8} {}",
-            issue.id,
-            issue.priority,
-            issue.issue_type,
-            truncate(&issue.title, 40)
+    for (session, (ids, total, unestimated)) in &sessions {
+        let row = format!(
+            "  {:<24} {:>6}  {:>8}  {}",
+            truncate(session, 24),
+            ids.len(),
+            total,
+            unestimated.join(", ")
         );
+        println!("{}", row.trim_end());
     }
 
-    println!();
-    println!("{} issue(s) ready", ready.len());
+    if sessions
+        .values()
+        .any(|(_, _, unestimated)| !unestimated.is_empty())
+    {
+        println!();
+        println!("Unestimated issues count as 0 (set one with 'ba estimate <id> <n>')");
+    }
 
     Ok(())
 }
 
+/// Compact duration for humans: `45m`, `2h 05m`, `0m` for anything under a minute.
+fn format_duration(duration: chrono::Duration) -> String {
+    let minutes = duration.num_minutes();
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+/// Shorten `s` to at most `max` characters, ending in `...` when cut. Counts
+/// chars rather than bytes so multibyte titles never split mid-character.
 fn truncate(s: &str, max: usize) -> String {
-    if s.len() <= max {
+    if s.chars().count() <= max {
         s.to_string()
     } else {
-        format!("{}...", &s[..max - 3])
+        let kept: String = s.chars().take(max.saturating_sub(3)).collect();
+        format!("{}...", kept)
     }
 }
 
@@ -1942,84 +5970,551 @@ fn find_ba_dir() -> Option<PathBuf> {
     }
 }
 
+/// Who is running ba: `BA_AUTHOR`, else `git config user.name`, else
+/// "anonymous". Used for comments without `--author` and for audit entries.
+fn default_author() -> String {
+    if let Some(author) = std::env::var("BA_AUTHOR")
+        .ok()
+        .filter(|a| !a.trim().is_empty())
+    {
+        return author.trim().to_string();
+    }
+    std::process::Command::new("git")
+        .args(["config", "user.name"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "anonymous".to_string())
+}
+
+/// Run one store-backed command. Commands that never load the store are
+/// dispatched in `main` before this is reached.
+fn run_command(store: &mut Store, command: Commands, json_output: bool) -> Result<(), BaError> {
+    match command {
+        Commands::Init
+        | Commands::Quickstart
+        | Commands::Schema
+        | Commands::Root
+        | Commands::Merge { .. }
+        | Commands::Template { .. }
+        | Commands::Watch { .. } => unreachable!(),
+        Commands::Create {
+            title,
+            issue_type,
+            priority,
+            description,
+            description_file,
+            links,
+            ..
+        } => {
+            let issue_type = issue_type
+                .unwrap_or_else(|| store.config.default_type.to_string());
+            let priority = priority.unwrap_or(store.config.default_priority);
+            let description = match description_file {
+                Some(path) => Some(read_description_file(&path)?),
+                None => description,
+            };
+            match title {
+                Some(title) => cmd_create(
+                    store,
+                    title,
+                    issue_type,
+                    priority,
+                    description,
+                    &links,
+                    json_output,
+                ),
+                None => cmd_create_batch(
+                    store,
+                    &issue_type,
+                    priority,
+                    description.as_deref(),
+                    &links,
+                    json_output,
+                ),
+            }
+        }
+        Commands::Clone { id, title } => cmd_clone(store, &id, title, json_output),
+        Commands::List { filter, view } => cmd_list(store, &filter, &view, json_output),
+        Commands::Count { filter } => cmd_count(store, &filter, json_output),
+        Commands::Search {
+            query,
+            all,
+            archived,
+        } => cmd_search(store, &query, all, archived, json_output),
+        Commands::Query {
+            expr,
+            archived,
+            view,
+        } => cmd_query(store, &expr, archived, &view, json_output),
+        Commands::Recent { limit } => cmd_recent(store, limit, json_output),
+        Commands::Show {
+            id,
+            comments_only,
+            web,
+            oneline,
+        } => cmd_show(store, &id, comments_only, web, oneline, json_output),
+        Commands::Link { id } => cmd_link(store, &id, json_output),
+        Commands::Close {
+            id,
+            reason,
+            cascade: false,
+        } => cmd_close(store, &id, reason, json_output),
+        Commands::Close {
+            id,
+            reason,
+            cascade: true,
+        } => cmd_close_cascade(store, &id, reason, json_output),
+        Commands::BulkClose { filter } => cmd_bulk_close(store, &filter, json_output),
+        Commands::Delete {
+            id,
+            label,
+            label_prefix,
+            force,
+        } => cmd_delete(store, id, label, label_prefix, force, json_output),
+        Commands::Purge { before } => {
+            cmd_purge(store, &before, dry_run(), json_output)
+        }
+        Commands::Block {
+            id,
+            blocker,
+            dep_type,
+            soft,
+            force,
+        } => cmd_block(store, &id, &blocker, &dep_type, soft, force, json_output),
+        Commands::Unblock { id, blocker } => {
+            cmd_unblock(store, &id, &blocker, json_output)
+        }
+        Commands::Relate { id, other } => {
+            cmd_relate(store, &id, &other, true, json_output)
+        }
+        Commands::Unrelate { id, other } => {
+            cmd_relate(store, &id, &other, false, json_output)
+        }
+        Commands::Tree {
+            id,
+            open_only,
+            blocks,
+            depth,
+            status,
+            verbose,
+        } => status
+            .iter()
+            .map(|s| s.trim().parse())
+            .collect::<Result<Vec<Status>, String>>()
+            .map_err(BaError::from)
+            .and_then(|statuses| {
+                cmd_tree(
+                    store,
+                    &id,
+                    &TreeOptions {
+                        open_only,
+                        downstream: blocks,
+                        depth,
+                        statuses,
+                        verbose,
+                    },
+                    json_output,
+                )
+            }),
+        Commands::Parent { id, epic } => cmd_parent(store, &id, &epic, json_output),
+        Commands::Deps { id, action } => cmd_deps(store, &id, action, json_output),
+        Commands::Children { epic } => cmd_children(store, &epic, json_output),
+        Commands::Progress { epic } => cmd_progress(store, &epic, json_output),
+        Commands::Cycles {
+            open_only,
+            break_cycles: false,
+        } => cmd_cycles(store, open_only, json_output),
+        Commands::Cycles {
+            open_only,
+            break_cycles: true,
+        } => cmd_break_cycles(store, open_only, json_output),
+        Commands::Blocked => cmd_blocked(store, json_output),
+        Commands::Ready {
+            transitive,
+            issue_type,
+            label,
+            min_estimate,
+            max_estimate,
+            verbose,
+            page,
+        } => {
+            let filter = ListFilter {
+                issue_type,
+                label,
+                min_estimate,
+                max_estimate,
+                ..ListFilter::default()
+            };
+            cmd_ready(store, transitive, &filter, verbose, &page, json_output)
+        }
+        Commands::Next { session, claim } => {
+            cmd_next(store, session.as_deref(), claim, json_output)
+        }
+        Commands::Stats { aging: false } => cmd_stats(store, json_output),
+        Commands::Stats { aging: true } => cmd_stats_aging(store, json_output),
+        Commands::Log { id } => cmd_log(store, id.as_deref(), json_output),
+        Commands::History { id } => cmd_history(store, &id, json_output),
+        Commands::Diff { id } => cmd_diff(store, id.as_deref(), json_output),
+        Commands::Reprefix { prefix } => cmd_reprefix(store, &prefix, json_output),
+        Commands::Move {
+            id,
+            to,
+            keep_id,
+            with_deps,
+        } => cmd_move(store, &id, &to, keep_id, with_deps, json_output),
+        Commands::Config { action } => match action {
+            ConfigAction::List => cmd_config_list(store, json_output),
+            ConfigAction::Get { key } => cmd_config_get(store, &key, json_output),
+            ConfigAction::Set { key, value } => {
+                cmd_config_set(store, &key, &value, json_output)
+            }
+        },
+        Commands::Undo => cmd_undo(store, json_output),
+        Commands::Doctor { fix } => cmd_doctor(store, fix, json_output),
+        Commands::Replay { log } => cmd_replay(store, log.as_deref(), json_output),
+        Commands::Load => cmd_load(store, json_output),
+        Commands::Start { id } => cmd_start(store, &id, json_output),
+        Commands::Stop { id } => cmd_stop(store, &id, json_output),
+        Commands::Claim { id, session, ttl } => {
+            cmd_claim(store, &id, &session, ttl, json_output)
+        }
+        Commands::Reap => cmd_reap(store, json_output),
+        Commands::Work { id, session } => {
+            cmd_work(store, &id, &session, json_output)
+        }
+        Commands::Reassign { id, from, to } => {
+            cmd_reassign(store, &id, from, to, json_output)
+        }
+        Commands::Release { id } => cmd_release(store, &id, json_output),
+        Commands::Finish {
+            id,
+            reason,
+            show_unblocked,
+        } => cmd_finish(store, &id, reason, show_unblocked, json_output),
+        Commands::Dup { id, of, author } => {
+            let author = author.or_else(|| store.author.clone()).unwrap_or_else(default_author);
+            cmd_dup(store, &id, &of, &author, json_output)
+        }
+        Commands::MergeIssues { a, b, author } => {
+            let author = author.or_else(|| store.author.clone()).unwrap_or_else(default_author);
+            cmd_merge_issues(store, &a, &b, &author, json_output)
+        }
+        Commands::Reopen { id } => cmd_reopen(store, &id, json_output),
+        Commands::Archive { id } => cmd_archive(store, &id, true, json_output),
+        Commands::Unarchive { id } => cmd_archive(store, &id, false, json_output),
+        Commands::Mine { session, page } => cmd_mine(store, &session, &page, json_output),
+        Commands::Whoami { session } => cmd_whoami(store, &session, json_output),
+        Commands::Labels => cmd_labels(store, json_output),
+        Commands::Label {
+            args,
+            query,
+        } => match (query, args.as_slice()) {
+            (None, [rename, old, new]) if rename == "rename" => {
+                cmd_label_rename(store, old, new, json_output)
+            }
+            (None, [id, action, label]) => {
+                cmd_label(store, id, action, label, json_output)
+            }
+            (Some(query), [action, label]) => cmd_label_query(
+                store, &query, action, label, dry_run(), json_output,
+            ),
+            _ => Err("Usage: ba label <id> <add|remove> <label>, ba label --query <expr> <add|remove> <label>, or ba label rename <old> <new>".into()),
+        },
+        Commands::Priority {
+            id,
+            value,
+            bump_blockers,
+        } => cmd_priority(store, &id, value, bump_blockers, json_output),
+        Commands::Groom => cmd_groom(store, json_output),
+        Commands::Rank { id, before, after } => match (before, after) {
+            (Some(other), _) => cmd_rank(store, &id, &other, true, json_output),
+            (None, Some(other)) => cmd_rank(store, &id, &other, false, json_output),
+            (None, None) => unreachable!("clap requires --before or --after"),
+        },
+        Commands::Due { id, date, .. } => {
+            cmd_due(store, &id, date.as_deref(), json_output)
+        }
+        Commands::Assign { id, name, .. } => {
+            cmd_assign(store, &id, name, json_output)
+        }
+        Commands::Estimate { id, value } => {
+            cmd_estimate(store, &id, value, json_output)
+        }
+        Commands::Comment {
+            id,
+            text,
+            author,
+            edit,
+            delete,
+        } => match (edit, delete, text) {
+            (_, Some(index), _) => {
+                cmd_comment_delete(store, &id, index, json_output)
+            }
+            (Some(index), None, Some(text)) => {
+                cmd_comment_edit(store, &id, index, &text, json_output)
+            }
+            (None, None, Some(text)) => {
+                let author = author.or_else(|| store.author.clone()).unwrap_or_else(default_author);
+                cmd_comment(store, &id, &text, &author, json_output)
+            }
+            (_, None, None) => unreachable!("clap requires text without --delete"),
+        },
+        Commands::Import {
+            file,
+            keep_ids,
+            format,
+        } => match format.as_str() {
+            "beads" => cmd_import(store, &file, keep_ids, json_output),
+            "native" | "native-jsonl" => cmd_import_native(store, &file, json_output),
+            other => Err(format!(
+                "Unknown import format: {} (valid: beads, native)",
+                other
+            )
+            .into()),
+        },
+        Commands::Snapshot { name, force } => {
+            if name == "list" {
+                cmd_snapshot_list(store, json_output)
+            } else {
+                cmd_snapshot(store, &name, force, json_output)
+            }
+        }
+        Commands::Restore { name, yes } => {
+            cmd_restore(store, &name, yes, json_output)
+        }
+        Commands::Export {
+            format,
+            output,
+            all,
+        } => cmd_export(store, &format, output.as_deref(), all, json_output),
+        Commands::Batch => cmd_batch(store, json_output),
+    }
+}
+
+/// One line of `ba batch`: a subcommand with its arguments, no `ba` in front.
+#[derive(Parser)]
+#[command(name = "ba", no_binary_name = true)]
+struct BatchStep {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+/// Why `command` can't run inside a batch: it needs stdin, or it writes files
+/// besides issues.jsonl that a failed batch couldn't take back.
+fn batch_conflict(command: &Commands) -> Option<&'static str> {
+    match command {
+        Commands::Batch => Some("batches don't nest"),
+        Commands::Init
+        | Commands::Quickstart
+        | Commands::Schema
+        | Commands::Root
+        | Commands::Merge { .. }
+        | Commands::Template { .. }
+        | Commands::Watch { .. } => Some("it doesn't work on the store"),
+        Commands::Create { title: None, .. } => {
+            Some("create --batch reads stdin, which holds the batch")
+        }
+        Commands::Create {
+            description_file: Some(path),
+            ..
+        } if path == Path::new("-") => {
+            Some("--description-file - reads stdin, which holds the batch")
+        }
+        Commands::Move { .. }
+        | Commands::Reprefix { .. }
+        | Commands::Purge { .. }
+        | Commands::Config {
+            action: ConfigAction::Set { .. },
+        }
+        | Commands::Export {
+            output: Some(_), ..
+        } => Some("it writes files a failed batch can't roll back"),
+        Commands::Snapshot { name, .. } if name != "list" => {
+            Some("it writes files a failed batch can't roll back")
+        }
+        _ => None,
+    }
+}
+
+/// Split a batch line into words. Single and double quotes group words;
+/// a backslash escapes the next character outside single quotes.
+fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\\' => {
+                let escaped = chars.next().ok_or("Trailing backslash")?;
+                word.get_or_insert_default().push(escaped);
+            }
+            '\'' | '"' => {
+                let word = word.get_or_insert_default();
+                loop {
+                    match chars.next() {
+                        Some(ch) if ch == c => break,
+                        Some('\\') if c == '"' => {
+                            word.push(chars.next().ok_or("Trailing backslash")?)
+                        }
+                        Some(ch) => word.push(ch),
+                        None => return Err("Unterminated quote".to_string()),
+                    }
+                }
+            }
+            c => word.get_or_insert_default().push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Apply every stdin line as a subcommand against one in-memory store under
+/// one lock, then save once. `$N` stands for the ID of the issue created on
+/// line N. The first failing line aborts the batch with nothing written.
+fn cmd_batch(store: &mut Store, json_output: bool) -> Result<(), BaError> {
+    let mut created: HashMap<usize, String> = HashMap::new();
+    let mut applied = 0;
+    store.defer_save = true;
+
+    for (line_num, line) in std::io::stdin().lock().lines().enumerate() {
+        let line_num = line_num + 1; // 1-indexed for user display
+        let context = format!("Batch line {} (nothing was saved)", line_num);
+        let line =
+            line.map_err(|e| BaError::from(format!("Failed to read: {}", e)).context(&context))?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let words = split_words(trimmed)
+            .map_err(|e| BaError::from(e).context(&context))?
+            .into_iter()
+            .map(
+                |word| match word.strip_prefix('$').map(str::parse::<usize>) {
+                    Some(Ok(n)) => created.get(&n).cloned().ok_or_else(|| {
+                        format!("${} refers to line {}, which created no issue", n, n)
+                    }),
+                    _ => Ok(word),
+                },
+            )
+            .collect::<Result<Vec<String>, String>>()
+            .map_err(|e| BaError::from(e).context(&context))?;
+        let step = BatchStep::try_parse_from(&words).map_err(|e| {
+            let message = e.to_string();
+            let first = message.lines().next().unwrap_or_default();
+            BaError::from(first.trim_start_matches("error: ")).context(&context)
+        })?;
+        if let Some(reason) = batch_conflict(&step.command) {
+            return Err(
+                BaError::from(format!("'{}' can't run in a batch: {}", words[0], reason))
+                    .context(&context),
+            );
+        }
+
+        let before: HashSet<String> = store.issues.keys().cloned().collect();
+        run_command(store, step.command, json_output).map_err(|e| e.context(&context))?;
+        let mut new_ids = store.issues.keys().filter(|id| !before.contains(*id));
+        if let (Some(id), None) = (new_ids.next(), new_ids.next()) {
+            created.insert(line_num, id.clone());
+        }
+        applied += 1;
+    }
+
+    store.defer_save = false;
+    store.save()?;
+
+    if !json_output {
+        say!("Batch: {} command(s) applied, saved once", applied);
+    }
+
+    Ok(())
+}
+
 fn main() {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let command_name = matches.subcommand_name().unwrap_or_default().to_string();
 
     // Walk up to find .ba/ (like git finds .git/) when --dir is the default.
     // If the user explicitly passed --dir, use it as-is.
-    let dir = if cli.dir == PathBuf::from(".ba") {
+    let searched = matches.value_source("dir") == Some(ValueSource::DefaultValue);
+    let dir = if searched {
         find_ba_dir().unwrap_or(cli.dir)
     } else {
         cli.dir
     };
 
+    QUIET.store(cli.quiet && !cli.json, std::sync::atomic::Ordering::Relaxed);
+    DRY_RUN.store(cli.dry_run, std::sync::atomic::Ordering::Relaxed);
+    let read_only = cli.command.is_read_only();
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    COLOR.store(
+        !cli.json && !cli.no_color && !no_color_env && std::io::stdout().is_terminal(),
+        std::sync::atomic::Ordering::Relaxed,
+    );
+
     let result = match cli.command {
         Commands::Init => cmd_init(&dir),
         Commands::Quickstart => {
             cmd_quickstart();
             Ok(())
         }
+        Commands::Schema => {
+            cmd_schema();
+            Ok(())
+        }
+        // Only reports where the store is; no need to load or lock it
+        Commands::Root => cmd_root(&dir, searched, cli.json),
+        // Loads the store per redraw so the lock isn't held between polls
+        Commands::Watch { interval } => cmd_watch(&dir, interval, cli.strict, cli.json),
+        // The editor can stay open indefinitely; don't hold the lock meanwhile
+        Commands::Template {
+            action: TemplateAction::Edit { issue_type },
+        } => cmd_template_edit(&dir, &issue_type, cli.json),
+        // Runs inside git on temp files; no store (or lock) involved
+        Commands::Merge { ours, theirs, base } => cmd_merge(&ours, &theirs, &base, cli.json),
         _ => {
             // All other commands need a loaded store
-            match Store::load(&dir) {
-                Ok(mut store) => match cli.command {
-                    Commands::Init | Commands::Quickstart => unreachable!(),
-                    Commands::Create {
-                        title,
-                        issue_type,
-                        priority,
-                        description,
-                    } => cmd_create(
-                        &mut store,
-                        title,
-                        issue_type,
-                        priority,
-                        description,
-                        cli.json,
-                    ),
-                    Commands::List { status, all } => cmd_list(&store, status, all, cli.json),
-                    Commands::Show { id } => cmd_show(&store, &id, cli.json),
-                    Commands::Close { id, reason } => cmd_close(&mut store, &id, reason, cli.json),
-                    Commands::Delete {
-                        label,
-                        label_prefix,
-                        force,
-                    } => cmd_delete(&mut store, label, label_prefix, force, cli.json),
-                    Commands::Block { id, blocker } => {
-                        cmd_block(&mut store, &id, &blocker, cli.json)
-                    }
-                    Commands::Unblock { id, blocker } => {
-                        cmd_unblock(&mut store, &id, &blocker, cli.json)
-                    }
-                    Commands::Tree { id } => cmd_tree(&store, &id, cli.json),
-                    Commands::Cycles => cmd_cycles(&store, cli.json),
-                    Commands::Ready => cmd_ready(&store, cli.json),
-                    Commands::Claim { id, session } => {
-                        cmd_claim(&mut store, &id, &session, cli.json)
-                    }
-                    Commands::Release { id } => cmd_release(&mut store, &id, cli.json),
-                    Commands::Finish { id } => cmd_finish(&mut store, &id, cli.json),
-                    Commands::Mine { session } => cmd_mine(&store, &session, cli.json),
-                    Commands::Label { id, action, label } => {
-                        cmd_label(&mut store, &id, &action, &label, cli.json)
-                    }
-                    Commands::Priority { id, value } => {
-                        cmd_priority(&mut store, &id, value, cli.json)
-                    }
-                    Commands::Comment { id, text, author } => {
-                        cmd_comment(&mut store, &id, &text, &author, cli.json)
-                    }
-                    Commands::Import { file, keep_ids } => {
-                        cmd_import(&mut store, &file, keep_ids, cli.json)
+            let lock_mode = if cli.command.is_read_only() {
+                LockMode::Shared
+            } else {
+                LockMode::Exclusive
+            };
+            let loaded = Store::load(&dir, lock_mode).and_then(|store| {
+                store.check_corrupt(cli.strict)?;
+                Ok(store)
+            });
+            match loaded {
+                Ok(mut store) => {
+                    store.command = command_name;
+                    if !read_only {
+                        store.author = Some(default_author());
                     }
-                },
+                    run_command(&mut store, cli.command, cli.json)
+                }
                 Err(e) => Err(e),
             }
         }
     };
 
     if let Err(e) = result {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
+        if cli.json {
+            eprintln!(
+                "{}",
+                serde_json::json!({ "error": e.to_string(), "code": e.code() })
+            );
+        } else {
+            eprintln!("Error: {}", e);
+        }
+        std::process::exit(e.exit_code());
+    }
+    // stderr, so --json output stays parseable
+    if cli.dry_run && !read_only {
+        eprintln!("Dry run: nothing was written");
     }
 }
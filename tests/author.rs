@@ -0,0 +1,46 @@
+//! Without `--author`, comments and audit entries are attributed to
+//! `BA_AUTHOR` (or git's user.name) instead of "anonymous".
+
+mod common;
+
+use std::path::Path;
+use std::process::Output;
+
+use common::{bin, cleanup, init_store};
+
+fn ba(dir: &Path, author: &str, args: &[&str]) -> Output {
+    bin()
+        .env("BA_AUTHOR", author)
+        .arg("--dir")
+        .arg(dir)
+        .args(args)
+        .output()
+        .expect("failed to run ba")
+}
+
+#[test]
+fn default_author_comes_from_the_environment() {
+    let dir = init_store("author");
+    let out = ba(&dir, "alice", &["--json", "create", "first"]);
+    let issue: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    let id = issue["id"].as_str().unwrap();
+
+    assert!(ba(&dir, "bob", &["comment", id, "mine"]).status.success());
+    let explicit = ba(&dir, "bob", &["comment", id, "theirs", "--author", "carol"]);
+    assert!(explicit.status.success());
+
+    let out = ba(&dir, "bob", &["--json", "show", id]);
+    let issue: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(issue["comments"][0]["author"], "bob");
+    assert_eq!(issue["comments"][1]["author"], "carol");
+
+    let out = ba(&dir, "bob", &["--json", "log", "--id", id]);
+    let entries: Vec<serde_json::Value> = serde_json::from_slice(&out.stdout).unwrap();
+    let authors: Vec<&str> = entries
+        .iter()
+        .map(|entry| entry["author"].as_str().unwrap())
+        .collect();
+    assert_eq!(authors, ["alice", "bob", "bob"]);
+
+    cleanup(&dir);
+}
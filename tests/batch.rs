@@ -0,0 +1,88 @@
+//! `ba batch` applies every line against one in-memory store and saves once:
+//! all of it lands, or none of it does.
+
+mod common;
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Output, Stdio};
+
+use common::{ba, bin, cleanup, init_store};
+
+fn batch(dir: &Path, script: &str) -> Output {
+    let mut child = bin()
+        .arg("--dir")
+        .arg(dir)
+        .arg("batch")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run ba");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(script.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn issues(dir: &Path) -> Vec<serde_json::Value> {
+    let out = ba(dir, &["--json", "list", "--all"]);
+    assert!(out.status.success(), "list failed: {:?}", out);
+    serde_json::from_slice(&out.stdout).unwrap()
+}
+
+#[test]
+fn batch_applies_every_line_with_references_to_created_issues() {
+    let dir = init_store("batch");
+
+    let out = batch(
+        &dir,
+        "# new work\n\
+         create \"Fix login\" -p 1\n\
+         create \"Upgrade auth lib\"\n\
+         \n\
+         block $2 $3\n\
+         label $2 add \"needs review\"\n",
+    );
+    assert!(out.status.success(), "batch failed: {:?}", out);
+
+    let issues = issues(&dir);
+    assert_eq!(issues.len(), 2);
+    let login = issues.iter().find(|i| i["title"] == "Fix login").unwrap();
+    let upgrade = issues
+        .iter()
+        .find(|i| i["title"] == "Upgrade auth lib")
+        .unwrap();
+    assert_eq!(login["blocked_by"][0], upgrade["id"]);
+    assert_eq!(upgrade["blocks"][0], login["id"]);
+    assert_eq!(login["labels"][0], "needs review");
+
+    cleanup(&dir);
+}
+
+#[test]
+fn failing_line_rolls_back_the_whole_batch() {
+    let dir = init_store("batch-rollback");
+    let out = ba(&dir, &["create", "existing"]);
+    assert!(out.status.success(), "create failed: {:?}", out);
+    let before = fs::read(dir.join("issues.jsonl")).unwrap();
+
+    let out = batch(
+        &dir,
+        "create first\nclaim $1 --session agent-a\nclose $1\ncreate never\n",
+    );
+    assert_eq!(out.status.code(), Some(5), "claimed close should fail");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("Batch line 3"), "{}", stderr);
+
+    let out = batch(&dir, "create second\nblock $1 ab-nope\n");
+    assert_eq!(out.status.code(), Some(3), "missing blocker should fail");
+
+    assert_eq!(fs::read(dir.join("issues.jsonl")).unwrap(), before);
+
+    cleanup(&dir);
+}
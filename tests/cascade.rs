@@ -0,0 +1,56 @@
+//! `close --cascade` closes the blockers only the closed issue was waiting
+//! on, leaves shared and claimed ones open, and copes with cycles.
+
+mod common;
+
+use std::path::Path;
+
+use common::{ba, cleanup, create, init_store, show};
+
+fn status(dir: &Path, id: &str) -> String {
+    show(dir, id)["status"].as_str().unwrap().to_string()
+}
+
+#[test]
+fn cascade_closes_only_exclusive_blockers() {
+    let dir = init_store("cascade");
+    let [epic, cyc_a, cyc_b, shared, other, claimed, behind_claimed] = [
+        "epic", "cyc-a", "cyc-b", "shared", "other", "claimed", "behind",
+    ]
+    .map(|title| create(&dir, &[title]));
+
+    for (id, blocker, extra) in [
+        (&epic, &cyc_a, None),
+        (&epic, &shared, None),
+        (&other, &shared, None),
+        (&cyc_a, &cyc_b, None),
+        (&cyc_b, &cyc_a, Some("--force")),
+        (&epic, &claimed, None),
+        (&claimed, &behind_claimed, None),
+    ] {
+        let mut args = vec!["block", id.as_str(), blocker.as_str()];
+        args.extend(extra);
+        assert!(ba(&dir, &args).status.success(), "block {:?}", args);
+    }
+    let out = ba(&dir, &["claim", &claimed, "--session", "agent-a"]);
+    assert!(out.status.success());
+
+    let out = ba(
+        &dir,
+        &["--json", "close", &epic, "--cascade", "--reason", "wontfix"],
+    );
+    assert!(out.status.success(), "cascade failed: {:?}", out);
+    let result: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(result["closed"].as_array().unwrap().len(), 3);
+    assert_eq!(result["skipped"][0], claimed.as_str());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("claimed by session agent-a"));
+
+    for id in [&epic, &cyc_a, &cyc_b] {
+        assert_eq!(status(&dir, id), "closed", "{}", id);
+    }
+    assert_eq!(status(&dir, &shared), "open", "other still needs it");
+    assert_eq!(status(&dir, &claimed), "in_progress");
+    assert_eq!(status(&dir, &behind_claimed), "open", "the claim needs it");
+
+    cleanup(&dir);
+}
@@ -0,0 +1,55 @@
+//! Helpers shared by the integration tests: each test runs the `ba` binary
+//! against its own store in a temp directory.
+
+// Every test file pulls this in as its own module and uses only part of it
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// The `ba` binary under test, with no arguments yet.
+pub fn bin() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_ba"))
+}
+
+pub fn ba(dir: &Path, args: &[&str]) -> Output {
+    bin()
+        .arg("--dir")
+        .arg(dir)
+        .args(args)
+        .output()
+        .expect("failed to run ba")
+}
+
+/// Fresh initialized store in a unique temp directory.
+pub fn init_store(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("ba-test-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    let dir = root.join(".ba");
+    let out = ba(&dir, &["init"]);
+    assert!(out.status.success(), "init failed: {:?}", out);
+    dir
+}
+
+/// Remove the temp directory [`init_store`] made for `dir`.
+pub fn cleanup(dir: &Path) {
+    let _ = fs::remove_dir_all(dir.parent().unwrap());
+}
+
+/// `ba create` with `args` (title first), returning the new ID.
+pub fn create(dir: &Path, args: &[&str]) -> String {
+    let mut full = vec!["--json", "create"];
+    full.extend_from_slice(args);
+    let out = ba(dir, &full);
+    assert!(out.status.success(), "create failed: {:?}", out);
+    let issue: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    issue["id"].as_str().unwrap().to_string()
+}
+
+pub fn show(dir: &Path, id: &str) -> serde_json::Value {
+    let out = ba(dir, &["--json", "show", id]);
+    assert!(out.status.success(), "show failed: {:?}", out);
+    serde_json::from_slice(&out.stdout).unwrap()
+}
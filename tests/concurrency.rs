@@ -0,0 +1,253 @@
+//! Concurrency stress tests for the store lock.
+//!
+//! Mutating `ba` commands take an exclusive lock on `.ba/issues.lock` for the
+//! whole load → mutate → save cycle; read-only commands share it. These tests
+//! hammer one store from many processes at once and check that no write is
+//! lost, ownership stays exclusive, and readers never see a half-written file.
+
+mod common;
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+
+use common::{ba, cleanup, create, init_store};
+
+/// Parse issues.jsonl strictly: every non-empty line must be a JSON object.
+fn read_issues(dir: &Path) -> HashMap<String, serde_json::Value> {
+    let content = fs::read_to_string(dir.join("issues.jsonl")).unwrap();
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let issue: serde_json::Value =
+                serde_json::from_str(line).expect("issues.jsonl has a corrupt line");
+            (issue["id"].as_str().unwrap().to_string(), issue)
+        })
+        .collect()
+}
+
+fn strings(value: &serde_json::Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .map(|v| v.as_str().unwrap().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[test]
+fn concurrent_claims_have_exactly_one_winner_per_issue() {
+    const ISSUES: usize = 6;
+    const SESSIONS: usize = 8;
+
+    let dir = init_store("claims");
+    let ids: Vec<String> = (0..ISSUES)
+        .map(|i| create(&dir, &[&format!("issue {}", i)]))
+        .collect();
+    let ids = Arc::new(ids);
+
+    // Every session races to claim every issue
+    let handles: Vec<_> = (0..SESSIONS)
+        .map(|s| {
+            let dir = dir.clone();
+            let ids = Arc::clone(&ids);
+            thread::spawn(move || {
+                let session = format!("session-{}", s);
+                let mut won = vec![];
+                for id in ids.iter() {
+                    if ba(&dir, &["claim", id, "--session", &session])
+                        .status
+                        .success()
+                    {
+                        won.push(id.clone());
+                    }
+                }
+                (session, won)
+            })
+        })
+        .collect();
+
+    let mut winners: HashMap<String, String> = HashMap::new();
+    for handle in handles {
+        let (session, won) = handle.join().unwrap();
+        for id in won {
+            let previous = winners.insert(id.clone(), session.clone());
+            assert!(
+                previous.is_none(),
+                "{} claimed by both {:?} and {}",
+                id,
+                previous,
+                session
+            );
+        }
+    }
+    assert_eq!(winners.len(), ISSUES, "every issue should have one winner");
+
+    // The file agrees with what each winner was told
+    let issues = read_issues(&dir);
+    for (id, session) in &winners {
+        assert_eq!(issues[id]["status"], "in_progress");
+        assert_eq!(issues[id]["session_id"].as_str(), Some(session.as_str()));
+    }
+
+    // Winners finish concurrently; no finish may be lost
+    let handles: Vec<_> = winners
+        .keys()
+        .map(|id| {
+            let dir = dir.clone();
+            let id = id.clone();
+            thread::spawn(move || ba(&dir, &["finish", &id]).status.success())
+        })
+        .collect();
+    for handle in handles {
+        assert!(handle.join().unwrap(), "finish failed");
+    }
+
+    let issues = read_issues(&dir);
+    assert_eq!(issues.len(), ISSUES);
+    for issue in issues.values() {
+        assert_eq!(issue["status"], "closed");
+        assert!(issue.get("session_id").is_none());
+    }
+
+    cleanup(&dir);
+}
+
+#[test]
+fn concurrent_mutations_keep_every_write_and_dependency_symmetry() {
+    const PAIRS: usize = 8;
+
+    let dir = init_store("deps");
+    let pairs: Vec<(String, String)> = (0..PAIRS)
+        .map(|i| {
+            (
+                create(&dir, &[&format!("blocked {}", i)]),
+                create(&dir, &[&format!("blocker {}", i)]),
+            )
+        })
+        .collect();
+
+    // Each thread blocks one pair, labels the blocked issue and claims the
+    // blocker: three writes per thread, all racing against each other
+    let handles: Vec<_> = pairs
+        .iter()
+        .enumerate()
+        .map(|(i, (blocked, blocker))| {
+            let dir = dir.clone();
+            let (blocked, blocker) = (blocked.clone(), blocker.clone());
+            thread::spawn(move || {
+                let session = format!("agent-{}", i);
+                let label = format!("batch-{}", i);
+                assert!(ba(&dir, &["block", &blocked, &blocker]).status.success());
+                assert!(
+                    ba(&dir, &["label", &blocked, "add", &label])
+                        .status
+                        .success()
+                );
+                assert!(
+                    ba(&dir, &["claim", &blocker, "--session", &session])
+                        .status
+                        .success()
+                );
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let issues = read_issues(&dir);
+    assert_eq!(issues.len(), PAIRS * 2);
+
+    for (i, (blocked, blocker)) in pairs.iter().enumerate() {
+        assert_eq!(
+            strings(&issues[blocked]["blocked_by"]),
+            vec![blocker.clone()]
+        );
+        assert_eq!(strings(&issues[blocker]["blocks"]), vec![blocked.clone()]);
+        assert_eq!(
+            strings(&issues[blocked]["labels"]),
+            vec![format!("batch-{}", i)]
+        );
+        assert_eq!(
+            issues[blocker]["session_id"].as_str(),
+            Some(format!("agent-{}", i).as_str())
+        );
+    }
+
+    // Global symmetry: every blocked_by edge has its blocks mirror and back
+    let mut forward: HashSet<(String, String)> = HashSet::new();
+    let mut backward: HashSet<(String, String)> = HashSet::new();
+    for (id, issue) in &issues {
+        for blocker in strings(&issue["blocked_by"]) {
+            forward.insert((id.clone(), blocker));
+        }
+        for blocked in strings(&issue["blocks"]) {
+            backward.insert((blocked, id.clone()));
+        }
+    }
+    assert_eq!(forward, backward);
+
+    cleanup(&dir);
+}
+
+#[test]
+fn two_threads_claiming_different_issues_both_persist() {
+    let dir = init_store("two-claims");
+    let first = create(&dir, &["first"]);
+    let second = create(&dir, &["second"]);
+
+    let handles: Vec<_> = [(first.clone(), "agent-a"), (second.clone(), "agent-b")]
+        .into_iter()
+        .map(|(id, session)| {
+            let dir = dir.clone();
+            thread::spawn(move || ba(&dir, &["claim", &id, "--session", session]))
+        })
+        .collect();
+    for handle in handles {
+        let out = handle.join().unwrap();
+        assert!(out.status.success(), "claim failed: {:?}", out);
+    }
+
+    let issues = read_issues(&dir);
+    assert_eq!(issues[&first]["session_id"], "agent-a");
+    assert_eq!(issues[&second]["session_id"], "agent-b");
+
+    cleanup(&dir);
+}
+
+#[test]
+fn readers_see_whole_stores_while_writers_run() {
+    const WRITES: usize = 20;
+
+    let dir = init_store("readers");
+    let writer = {
+        let dir = dir.clone();
+        thread::spawn(move || {
+            for i in 0..WRITES {
+                create(&dir, &[&format!("issue {}", i)]);
+            }
+        })
+    };
+
+    // Each read must parse and never go backwards
+    let mut last_seen = 0;
+    while !writer.is_finished() {
+        let out = ba(&dir, &["--json", "list"]);
+        assert!(out.status.success(), "list failed: {:?}", out);
+        let issues: Vec<serde_json::Value> = serde_json::from_slice(&out.stdout).unwrap();
+        assert!(issues.len() >= last_seen, "a read lost issues");
+        last_seen = issues.len();
+    }
+    writer.join().unwrap();
+
+    assert_eq!(read_issues(&dir).len(), WRITES);
+
+    cleanup(&dir);
+}
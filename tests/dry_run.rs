@@ -0,0 +1,79 @@
+//! `--dry-run` runs a command's logic and prints its result, but must leave
+//! every file in the store exactly as it was.
+
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{ba, cleanup, create, init_store};
+
+/// Every file in the store, by name, so a stray write anywhere shows up.
+fn snapshot(dir: &Path) -> Vec<(String, Vec<u8>)> {
+    let mut files: Vec<(String, Vec<u8>)> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_file())
+        .map(|path| {
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+            (name, fs::read(&path).unwrap())
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn dry_run_leaves_the_store_untouched() {
+    let dir = init_store("dry-run");
+    let first = create(&dir, &["first"]);
+    let second = create(&dir, &["second"]);
+    let before = snapshot(&dir);
+
+    let runs: [&[&str]; 7] = [
+        &["create", "never saved"],
+        &["claim", &first, "--session", "agent-a"],
+        &["close", &first, "--reason", "done"],
+        &["block", &first, &second],
+        &["label", &first, "add", "urgent"],
+        &["priority", &second, "0"],
+        &["delete", &second],
+    ];
+    for args in runs {
+        let mut full = vec!["--dry-run"];
+        full.extend_from_slice(args);
+        let out = ba(&dir, &full);
+        assert!(out.status.success(), "{:?} failed: {:?}", args, out);
+        assert!(
+            String::from_utf8_lossy(&out.stderr).contains("Dry run: nothing was written"),
+            "{:?} should say it wrote nothing",
+            args
+        );
+        assert_eq!(snapshot(&dir), before, "{:?} changed the store", args);
+    }
+
+    cleanup(&dir);
+}
+
+#[test]
+fn dry_run_prints_the_resulting_issue() {
+    let dir = init_store("dry-run-json");
+    let id = create(&dir, &["first"]);
+    let before = fs::read(dir.join("issues.jsonl")).unwrap();
+
+    let out = ba(
+        &dir,
+        &["--json", "--dry-run", "claim", &id, "--session", "agent-a"],
+    );
+    assert!(out.status.success(), "claim failed: {:?}", out);
+    let issue: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(issue["status"], "in_progress");
+    assert_eq!(issue["session_id"], "agent-a");
+
+    assert_eq!(fs::read(dir.join("issues.jsonl")).unwrap(), before);
+    let out = ba(&dir, &["--json", "show", &id]);
+    let issue: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(issue["status"], "open");
+
+    cleanup(&dir);
+}
@@ -0,0 +1,36 @@
+//! Under `--json`, failures are JSON too: an object on stderr whose `code`
+//! names the kind of error and matches the exit code.
+
+mod common;
+
+use common::{ba, cleanup, init_store};
+
+#[test]
+fn json_errors_carry_a_stable_code() {
+    let dir = init_store("json-errors");
+    let out = ba(&dir, &["--json", "create", "first"]);
+    let issue: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    let id = issue["id"].as_str().unwrap();
+
+    for (args, exit, code) in [
+        (vec!["show", "ab-nope"], 3, "not_found"),
+        (vec!["finish", id], 6, "invalid_transition"),
+        (vec!["priority", id, "9"], 1, "other"),
+    ] {
+        let mut full = vec!["--json"];
+        full.extend_from_slice(&args);
+        let out = ba(&dir, &full);
+        assert_eq!(out.status.code(), Some(exit), "{:?}", args);
+        let error: serde_json::Value = serde_json::from_slice(&out.stderr)
+            .unwrap_or_else(|e| panic!("{:?}: stderr is not JSON: {}", args, e));
+        assert_eq!(error["code"], code, "{:?}", args);
+        assert!(!error["error"].as_str().unwrap().is_empty(), "{:?}", args);
+        assert!(out.stdout.is_empty(), "{:?}", args);
+    }
+
+    // Without --json the message stays plain text
+    let out = ba(&dir, &["show", "ab-nope"]);
+    assert!(String::from_utf8_lossy(&out.stderr).starts_with("Error: "));
+
+    cleanup(&dir);
+}
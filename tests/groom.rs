@@ -0,0 +1,84 @@
+//! `groom` raises open issues idle past `escalate-after` and lowers those idle
+//! past `decay-after` instead, one level per period.
+
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use chrono::{Duration, Utc};
+
+use common::{ba, cleanup, create, init_store, show};
+
+/// Pretend `id` was last updated `days` ago.
+fn age(dir: &Path, id: &str, days: i64) {
+    let path = dir.join("issues.jsonl");
+    let when = (Utc::now() - Duration::days(days)).to_rfc3339();
+    let lines: Vec<String> = fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let mut issue: serde_json::Value = serde_json::from_str(line).unwrap();
+            if issue["id"] == id {
+                issue["updated_at"] = when.clone().into();
+            }
+            issue.to_string()
+        })
+        .collect();
+    fs::write(&path, lines.join("\n") + "\n").unwrap();
+}
+
+fn groom(dir: &Path) -> Vec<serde_json::Value> {
+    let out = ba(dir, &["--json", "groom"]);
+    assert!(out.status.success(), "groom failed: {:?}", out);
+    serde_json::from_slice(&out.stdout).unwrap()
+}
+
+#[test]
+fn groom_escalates_stale_issues_and_decays_older_ones() {
+    let dir = init_store("groom");
+    assert!(
+        ba(&dir, &["config", "set", "escalate-after", "10"])
+            .status
+            .success()
+    );
+    let out = ba(&dir, &["config", "set", "decay-after", "10"]);
+    assert_eq!(out.status.code(), Some(1), "{:?}", out);
+    assert!(
+        ba(&dir, &["config", "set", "decay-after", "30"])
+            .status
+            .success()
+    );
+
+    let stale = create(&dir, &["stale"]);
+    let forgotten = create(&dir, &["forgotten"]);
+    let claimed = create(&dir, &["claimed"]);
+    let critical = create(&dir, &["critical", "-p", "0"]);
+    let fresh = create(&dir, &["fresh"]);
+    assert!(
+        ba(&dir, &["claim", &claimed, "--session", "s1"])
+            .status
+            .success()
+    );
+    age(&dir, &stale, 15);
+    age(&dir, &forgotten, 40);
+    age(&dir, &claimed, 40);
+    age(&dir, &critical, 15);
+
+    let changes = groom(&dir);
+    assert_eq!(changes.len(), 2, "{:?}", changes);
+    assert_eq!(show(&dir, &stale)["priority"], 1);
+    assert_eq!(show(&dir, &forgotten)["priority"], 3);
+    for (id, priority) in [(&claimed, 2), (&critical, 0), (&fresh, 2)] {
+        assert_eq!(show(&dir, id)["priority"], priority, "{}", id);
+    }
+
+    // Idle time still counts from the last real change, but a groomed issue
+    // waits out a full period before it moves again
+    assert_eq!(groom(&dir), Vec::<serde_json::Value>::new());
+    let out = ba(&dir, &["--json", "show", &stale]);
+    let issue: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert!(issue["idle_since"].is_string());
+
+    cleanup(&dir);
+}
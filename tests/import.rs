@@ -0,0 +1,59 @@
+//! `import --format native` keeps links only between the issues it adds, so
+//! nothing in the file can attach to an unrelated local issue.
+
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{ba, cleanup, create, init_store, show};
+
+fn import(dir: &Path, file: &Path) -> serde_json::Value {
+    let out = ba(
+        dir,
+        &[
+            "--json",
+            "import",
+            file.to_str().unwrap(),
+            "--format",
+            "native",
+        ],
+    );
+    assert!(out.status.success(), "import failed: {:?}", out);
+    serde_json::from_slice(&out.stdout).unwrap()
+}
+
+#[test]
+fn links_to_skipped_or_missing_issues_are_dropped() {
+    let source = init_store("import-native-source");
+    let blocker = create(&source, &["blocker"]);
+    let blocked = create(&source, &["blocked", "--blocked-by", &blocker]);
+    let backup = source.parent().unwrap().join("backup.jsonl");
+    let out = ba(&source, &["export", "--output", backup.to_str().unwrap()]);
+    assert!(out.status.success(), "export failed: {:?}", out);
+
+    // Only the blocked issue: its blocker is nowhere in the target store
+    let target = init_store("import-native-target");
+    let only_blocked: String = fs::read_to_string(&backup)
+        .unwrap()
+        .lines()
+        .filter(|line| line.contains(&format!("\"id\":\"{}\"", blocked)))
+        .collect();
+    let partial = target.parent().unwrap().join("partial.jsonl");
+    fs::write(&partial, only_blocked).unwrap();
+    let report = import(&target, &partial);
+    assert_eq!(report["imported"], 1);
+    assert_eq!(report["errors"], 1);
+    assert_eq!(show(&target, &blocked)["blocked_by"], serde_json::json!([]));
+
+    // Now the blocked issue is skipped, so the blocker can't link to it
+    let report = import(&target, &backup);
+    assert_eq!(report["imported"], 1);
+    assert_eq!(report["skipped"], 1);
+    assert_eq!(report["errors"], 1);
+    assert_eq!(show(&target, &blocker)["blocks"], serde_json::json!([]));
+    assert!(ba(&target, &["doctor"]).status.success());
+
+    cleanup(&source);
+    cleanup(&target);
+}
@@ -0,0 +1,50 @@
+//! A `label-priority` rule applies however the label arrives: `create
+//! --label`, `label <id> add`, `label --query` and `label rename`.
+
+mod common;
+
+use std::path::Path;
+
+use common::{ba, cleanup, init_store, show};
+
+fn priority_of(dir: &Path, id: &str) -> u64 {
+    show(dir, id)["priority"].as_u64().unwrap()
+}
+
+#[test]
+fn label_priority_applies_on_every_path_that_adds_a_label() {
+    let dir = init_store("label-priority");
+    let out = ba(
+        &dir,
+        &["config", "set", "label-priority", "urgent=0,backlog=4"],
+    );
+    assert!(out.status.success());
+
+    let out = ba(
+        &dir,
+        &["--json", "create", "created urgent", "--label", "urgent"],
+    );
+    let issue: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(issue["priority"], 0);
+
+    let out = ba(&dir, &["--json", "create", "plain"]);
+    let issue: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    let plain = issue["id"].as_str().unwrap().to_string();
+    let out = ba(&dir, &["label", "--query", "status:open", "add", "urgent"]);
+    assert!(out.status.success(), "{:?}", out);
+    assert_eq!(priority_of(&dir, &plain), 0);
+
+    // The most urgent rule among the issue's labels wins
+    let out = ba(&dir, &["label", &plain, "add", "backlog"]);
+    assert!(out.status.success(), "{:?}", out);
+    assert_eq!(priority_of(&dir, &plain), 0);
+
+    let out = ba(&dir, &["--json", "create", "someday", "--label", "later"]);
+    let issue: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    let later = issue["id"].as_str().unwrap().to_string();
+    let out = ba(&dir, &["label", "rename", "later", "backlog"]);
+    assert!(out.status.success(), "{:?}", out);
+    assert_eq!(priority_of(&dir, &later), 4);
+
+    cleanup(&dir);
+}
@@ -0,0 +1,137 @@
+//! Three-way merge of `issues.jsonl` (`ba merge <ours> <theirs> <base>`), as
+//! git runs it when registered as a merge driver.
+
+mod common;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Value, json};
+
+use common::bin;
+
+fn issue(id: &str, title: &str, updated_at: &str) -> Value {
+    json!({
+        "id": id,
+        "title": title,
+        "status": "open",
+        "issue_type": "task",
+        "created_at": "2025-01-01T00:00:00Z",
+        "updated_at": updated_at,
+    })
+}
+
+fn write_jsonl(path: &Path, issues: &[Value]) {
+    let lines: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+    fs::write(path, lines.join("\n")).unwrap();
+}
+
+/// Merge three versions in a fresh temp dir and return the merged issues by ID.
+fn merge(name: &str, base: &[Value], ours: &[Value], theirs: &[Value]) -> HashMap<String, Value> {
+    let dir: PathBuf =
+        std::env::temp_dir().join(format!("ba-merge-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let (base_path, ours_path, theirs_path) =
+        (dir.join("base"), dir.join("ours"), dir.join("theirs"));
+    write_jsonl(&base_path, base);
+    write_jsonl(&ours_path, ours);
+    write_jsonl(&theirs_path, theirs);
+
+    let out = bin()
+        .arg("merge")
+        .args([&ours_path, &theirs_path, &base_path])
+        .output()
+        .expect("failed to run ba");
+    assert!(out.status.success(), "merge failed: {:?}", out);
+
+    let merged = fs::read_to_string(&ours_path).unwrap();
+    let _ = fs::remove_dir_all(&dir);
+    merged
+        .lines()
+        .map(|line| {
+            let issue: Value = serde_json::from_str(line).unwrap();
+            (issue["id"].as_str().unwrap().to_string(), issue)
+        })
+        .collect()
+}
+
+#[test]
+fn add_add_keeps_both_and_resolves_same_id_by_updated_at() {
+    let mut ours_dup = issue("ab-dup1", "ours", "2025-01-02T00:00:00Z");
+    ours_dup["labels"] = json!(["from-ours"]);
+    let mut theirs_dup = issue("ab-dup1", "theirs", "2025-01-03T00:00:00Z");
+    theirs_dup["labels"] = json!(["from-theirs"]);
+
+    let merged = merge(
+        "add-add",
+        &[],
+        &[
+            issue("ab-ours", "only ours", "2025-01-02T00:00:00Z"),
+            ours_dup,
+        ],
+        &[
+            issue("ab-thrs", "only theirs", "2025-01-02T00:00:00Z"),
+            theirs_dup,
+        ],
+    );
+
+    assert_eq!(merged.len(), 3);
+    assert_eq!(merged["ab-ours"]["title"], "only ours");
+    assert_eq!(merged["ab-thrs"]["title"], "only theirs");
+    assert_eq!(merged["ab-dup1"]["title"], "theirs");
+    assert_eq!(
+        merged["ab-dup1"]["labels"],
+        json!(["from-theirs", "from-ours"])
+    );
+}
+
+#[test]
+fn edit_edit_takes_newer_fields_and_merges_labels_and_comments() {
+    let mut base = issue("ab-edit", "base", "2025-01-01T00:00:00Z");
+    base["labels"] = json!(["shared", "dropped"]);
+
+    let mut ours = issue("ab-edit", "ours", "2025-01-02T00:00:00Z");
+    ours["labels"] = json!(["shared", "dropped", "ours"]);
+    ours["comments"] = json!([
+        {"author": "a", "text": "from ours", "created_at": "2025-01-02T00:00:00Z"}
+    ]);
+
+    // Theirs is newer and removed a label
+    let mut theirs = issue("ab-edit", "theirs", "2025-01-03T00:00:00Z");
+    theirs["priority"] = json!(0);
+    theirs["labels"] = json!(["shared", "theirs"]);
+    theirs["comments"] = json!([
+        {"author": "b", "text": "from theirs", "created_at": "2025-01-03T00:00:00Z"}
+    ]);
+
+    let merged = merge("edit-edit", &[base], &[ours], &[theirs]);
+    let issue = &merged["ab-edit"];
+
+    assert_eq!(issue["title"], "theirs");
+    assert_eq!(issue["priority"], 0);
+    assert_eq!(issue["updated_at"], "2025-01-03T00:00:00Z");
+    assert_eq!(issue["labels"], json!(["shared", "theirs", "ours"]));
+    let texts: Vec<&str> = issue["comments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["text"].as_str().unwrap())
+        .collect();
+    assert_eq!(texts, vec!["from ours", "from theirs"]);
+}
+
+#[test]
+fn delete_edit_keeps_the_edit_and_delete_unchanged_removes() {
+    let edited = issue("ab-kept", "base", "2025-01-01T00:00:00Z");
+    let untouched = issue("ab-gone", "base", "2025-01-01T00:00:00Z");
+    let base = [edited, untouched.clone()];
+
+    // Ours deletes both; theirs edits one and leaves the other alone
+    let theirs_edit = issue("ab-kept", "edited", "2025-01-02T00:00:00Z");
+    let merged = merge("delete-edit", &base, &[], &[theirs_edit, untouched]);
+
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged["ab-kept"]["title"], "edited");
+}
@@ -0,0 +1,77 @@
+//! `merge-issues` folds a duplicate into the issue that stays and moves every
+//! link pointing at the duplicate onto it.
+
+mod common;
+
+use std::path::Path;
+
+use common::{ba, cleanup, create, init_store, show};
+
+fn run(dir: &Path, args: &[&str]) {
+    let out = ba(dir, args);
+    assert!(out.status.success(), "{:?} failed: {:?}", args, out);
+}
+
+#[test]
+fn merge_moves_content_and_links_onto_the_survivor() {
+    let dir = init_store("merge-issues");
+    let a = create(
+        &dir,
+        &["Login fails", "-d", "first report", "--label", "auth"],
+    );
+    let b = create(
+        &dir,
+        &["Login broken", "-d", "second report", "--label", "bug"],
+    );
+    let blocker = create(&dir, &["Blocker"]);
+    let dependent = create(&dir, &["Dependent"]);
+    let other = create(&dir, &["Other"]);
+    run(&dir, &["block", &b, &blocker]);
+    run(&dir, &["block", &dependent, &b]);
+    run(&dir, &["relate", &b, &other]);
+    run(&dir, &["comment", &b, "me too", "--author", "bob"]);
+
+    let out = ba(
+        &dir,
+        &["--json", "merge-issues", &a, &b, "--author", "alice"],
+    );
+    assert!(out.status.success(), "merge failed: {:?}", out);
+    let merged: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+
+    let description = merged["description"].as_str().unwrap();
+    assert!(description.starts_with("first report"));
+    assert!(description.contains("second report"));
+    assert_eq!(merged["labels"], serde_json::json!(["auth", "bug"]));
+    let comments = merged["comments"].as_array().unwrap();
+    assert_eq!(comments[0]["author"], "bob");
+    assert!(comments[0]["text"].as_str().unwrap().contains("me too"));
+    assert_eq!(comments[1]["author"], "alice");
+    assert_eq!(merged["blocked_by"], serde_json::json!([blocker]));
+    assert_eq!(merged["blocks"], serde_json::json!([dependent]));
+    assert_eq!(merged["related"], serde_json::json!([other]));
+
+    // The other side of each link now names the survivor
+    assert_eq!(show(&dir, &blocker)["blocks"], serde_json::json!([a]));
+    assert_eq!(show(&dir, &dependent)["blocked_by"], serde_json::json!([a]));
+    assert_eq!(show(&dir, &other)["related"], serde_json::json!([a]));
+    assert_eq!(ba(&dir, &["show", &b]).status.code(), Some(3));
+
+    cleanup(&dir);
+}
+
+#[test]
+fn merge_refuses_self_and_closed_targets() {
+    let dir = init_store("merge-issues-guards");
+    let a = create(&dir, &["Kept"]);
+    let b = create(&dir, &["Dropped"]);
+
+    assert_eq!(ba(&dir, &["merge-issues", &a, &a]).status.code(), Some(1));
+
+    run(&dir, &["close", &a]);
+    let out = ba(&dir, &["merge-issues", &a, &b]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("reopen"));
+    assert_eq!(show(&dir, &b)["title"], "Dropped");
+
+    cleanup(&dir);
+}
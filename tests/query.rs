@@ -0,0 +1,81 @@
+//! `ba query` filter expressions: precedence of NOT over AND over OR,
+//! parentheses, and parse errors that point at the problem.
+
+mod common;
+
+use std::path::Path;
+
+use common::{ba, cleanup, create, init_store};
+
+/// IDs matched by `expr`, sorted.
+fn query(dir: &Path, expr: &str) -> Vec<String> {
+    let out = ba(dir, &["--json", "query", expr]);
+    assert!(out.status.success(), "query {:?} failed: {:?}", expr, out);
+    let issues: Vec<serde_json::Value> = serde_json::from_slice(&out.stdout).unwrap();
+    let mut ids: Vec<String> = issues
+        .iter()
+        .map(|issue| issue["id"].as_str().unwrap().to_string())
+        .collect();
+    ids.sort();
+    ids
+}
+
+fn sorted(ids: &[&String]) -> Vec<String> {
+    let mut ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn boolean_operators_group_and_bind_in_order() {
+    let dir = init_store("query");
+    let login = create(&dir, &["Login page", "-p", "1", "--label", "auth"]);
+    let cache = create(
+        &dir,
+        &["Cache", "-t", "refactor", "-p", "0", "--label", "backend"],
+    );
+    let spike = create(&dir, &["Try OR", "-t", "spike", "-p", "3", "--label", "or"]);
+    let done = create(&dir, &["Done", "-p", "3", "--label", "backend"]);
+    assert!(ba(&dir, &["close", &done]).status.success());
+
+    assert_eq!(
+        query(
+            &dir,
+            "status:open AND label:backend AND priority<=1 AND type:refactor"
+        ),
+        sorted(&[&cache])
+    );
+    // AND binds tighter than OR
+    assert_eq!(
+        query(&dir, "label:auth OR priority:3 AND label:backend"),
+        sorted(&[&login, &done])
+    );
+    assert_eq!(
+        query(&dir, "(label:auth OR priority:3) AND label:backend"),
+        sorted(&[&done])
+    );
+    // Closed issues match unless the expression excludes them
+    assert_eq!(query(&dir, "NOT status:open"), sorted(&[&done]));
+    assert_eq!(
+        query(&dir, "label:backend AND NOT status:closed"),
+        sorted(&[&cache])
+    );
+    // Quoted values are never keywords; title is a substring match
+    assert_eq!(query(&dir, "label:\"or\""), sorted(&[&spike]));
+    assert_eq!(query(&dir, "title:\"try or\""), sorted(&[&spike]));
+
+    for (expr, message) in [
+        ("status:open OR", "dangling OR"),
+        ("(status:open", "Missing ')'"),
+        ("status:open)", "Unmatched ')'"),
+        ("status:open label:auth", "Expected AND or OR"),
+        ("status:started", "status"),
+    ] {
+        let out = ba(&dir, &["query", expr]);
+        assert_eq!(out.status.code(), Some(1), "{:?} should fail", expr);
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        assert!(stderr.contains(message), "{:?}: {}", expr, stderr);
+    }
+
+    cleanup(&dir);
+}
@@ -0,0 +1,44 @@
+//! Without `--dir`, ba uses the nearest `.ba/` at or above the working
+//! directory, the way git finds `.git/`.
+
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::process::Output;
+
+use common::bin;
+
+fn ba_in(cwd: &Path, args: &[&str]) -> Output {
+    bin()
+        .current_dir(cwd)
+        .args(args)
+        .output()
+        .expect("failed to run ba")
+}
+
+#[test]
+fn commands_find_the_store_from_a_subdirectory() {
+    let root = std::env::temp_dir().join(format!("ba-test-root-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let nested = root.join("src").join("deep");
+    fs::create_dir_all(&nested).unwrap();
+    assert!(ba_in(&root, &["init"]).status.success());
+
+    let out = ba_in(&nested, &["--json", "root"]);
+    assert!(out.status.success(), "root failed: {:?}", out);
+    let found: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    let expected = root.join(".ba").canonicalize().unwrap();
+    assert_eq!(found["dir"], expected.to_str().unwrap());
+
+    assert!(ba_in(&nested, &["create", "from below"]).status.success());
+    let out = ba_in(&root, &["--json", "list"]);
+    let issues: Vec<serde_json::Value> = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(issues[0]["title"], "from below");
+
+    // An explicit --dir is taken as given, even when it names the default
+    let out = ba_in(&nested, &["--dir", ".ba", "root"]);
+    assert_eq!(out.status.code(), Some(1));
+
+    let _ = fs::remove_dir_all(&root);
+}
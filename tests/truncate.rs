@@ -0,0 +1,61 @@
+//! Titles are cut to fit table and tree columns. The cut must land on a
+//! character boundary, whatever mix of ASCII and multibyte text the title has.
+
+mod common;
+
+use std::process::Output;
+
+use common::{ba, cleanup, init_store};
+
+fn stdout(out: &Output) -> String {
+    assert!(out.status.success(), "ba failed: {:?}", out);
+    String::from_utf8(out.stdout.clone()).unwrap()
+}
+
+#[test]
+fn multibyte_titles_truncate_on_char_boundaries() {
+    let dir = init_store("truncate");
+
+    // Shift the title by one ASCII char at a time so every column width cuts
+    // through é and 🚀 at some offset
+    let mut root: Option<String> = None;
+    for offset in 0..6 {
+        let title = format!("{}{}", "x".repeat(offset), "café 🚀 résumé ".repeat(5));
+        let out = ba(&dir, &["--json", "create", title.trim_end()]);
+        let issue: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+        let id = issue["id"].as_str().unwrap().to_string();
+        if let Some(root) = &root {
+            stdout(&ba(&dir, &["block", root, &id]));
+        } else {
+            root = Some(id);
+        }
+    }
+    let root = root.unwrap();
+
+    let list = stdout(&ba(&dir, &["list"]));
+    let rows: Vec<&str> = list.lines().filter(|line| line.contains("café")).collect();
+    assert_eq!(rows.len(), 6);
+    for row in rows {
+        assert!(row.trim_end().ends_with("..."), "title is cut: {}", row);
+    }
+
+    let tree = stdout(&ba(&dir, &["tree", &root]));
+    assert_eq!(
+        tree.matches("...").count(),
+        6,
+        "every title is cut:\n{}",
+        tree
+    );
+    for line in tree.lines().filter(|line| line.contains("...")) {
+        let title = line.split(": ").nth(1).unwrap();
+        let cut = title.split("...").next().unwrap();
+        assert_eq!(
+            cut.chars().count(),
+            27,
+            "tree titles keep 27 chars: {}",
+            line
+        );
+    }
+
+    cleanup(&dir);
+}
@@ -0,0 +1,39 @@
+//! `undo` reverses everything one command saved, not just one issue of it.
+
+mod common;
+
+use common::{ba, cleanup, create, init_store, show};
+
+#[test]
+fn undoing_a_create_strips_its_links() {
+    let dir = init_store("undo-create");
+    let blocker = create(&dir, &["blocker"]);
+    let new = create(&dir, &["new", "--blocked-by", &blocker]);
+
+    let out = ba(&dir, &["undo"]);
+    assert!(out.status.success(), "undo failed: {:?}", out);
+    assert_eq!(ba(&dir, &["show", &new]).status.code(), Some(3));
+    assert_eq!(show(&dir, &blocker)["blocks"], serde_json::json!([]));
+    assert!(ba(&dir, &["doctor"]).status.success());
+
+    cleanup(&dir);
+}
+
+#[test]
+fn undoing_a_cascade_reopens_every_issue_it_closed() {
+    let dir = init_store("undo-cascade");
+    let [a, b] = ["a", "b"].map(|title| create(&dir, &[title]));
+    let top = create(&dir, &["top", "--blocked-by", &a, "--blocked-by", &b]);
+    let out = ba(&dir, &["close", &top, "--cascade"]);
+    assert!(out.status.success(), "cascade failed: {:?}", out);
+
+    let out = ba(&dir, &["--json", "undo"]);
+    assert!(out.status.success(), "undo failed: {:?}", out);
+    let result: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(result["undone"].as_array().unwrap().len(), 3);
+    for id in [&a, &b, &top] {
+        assert_eq!(show(&dir, id)["status"], "open", "{}", id);
+    }
+
+    cleanup(&dir);
+}